@@ -0,0 +1,22 @@
+-- Migration: Allow Kill Team and Middle-earth projects
+
+-- SQLite can't alter a CHECK constraint, so the projects table is rebuilt.
+-- Migrations run with foreign keys off, so dropping the old table leaves
+-- miniatures in place.
+CREATE TABLE projects_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(255) NOT NULL,
+    game_system VARCHAR(50) NOT NULL CHECK (game_system IN ('age_of_sigmar', 'horus_heresy', 'kill_team', 'middle_earth', 'warhammer_40k')),
+    army VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO projects_new (id, name, game_system, army, description, created_at, updated_at)
+SELECT id, name, game_system, army, description, created_at, updated_at FROM projects;
+
+DROP TABLE projects;
+ALTER TABLE projects_new RENAME TO projects;
+
+CREATE INDEX idx_projects_game_system ON projects(game_system);
@@ -0,0 +1,43 @@
+-- Migration: Allow vehicle and monster miniatures and recipes
+
+-- SQLite can't alter a CHECK constraint, so both tables are rebuilt.
+-- Migrations run with foreign keys off, so photos and recipe links survive.
+CREATE TABLE miniatures_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    miniature_type VARCHAR(20) NOT NULL CHECK (miniature_type IN ('troop', 'character', 'vehicle', 'monster')),
+    progress_status VARCHAR(50) NOT NULL CHECK (progress_status IN ('unpainted', 'primed', 'basecoated', 'detailed', 'completed')),
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+INSERT INTO miniatures_new (id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at)
+SELECT id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at FROM miniatures;
+
+DROP TABLE miniatures;
+ALTER TABLE miniatures_new RENAME TO miniatures;
+
+CREATE INDEX idx_miniatures_project_id ON miniatures(project_id);
+
+CREATE TABLE painting_recipes_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(255) NOT NULL,
+    miniature_type VARCHAR(20) NOT NULL CHECK (miniature_type IN ('troop', 'character', 'vehicle', 'monster')),
+    steps TEXT NOT NULL, -- JSON array of steps
+    paints_used TEXT, -- JSON array of paint names/codes
+    techniques TEXT, -- JSON array of techniques
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO painting_recipes_new (id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
+SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes;
+
+DROP TABLE painting_recipes;
+ALTER TABLE painting_recipes_new RENAME TO painting_recipes;
+
+CREATE INDEX idx_recipes_miniature_type ON painting_recipes(miniature_type);
@@ -0,0 +1,6 @@
+-- Migration: Soft-delete projects
+-- Deleted projects keep their miniatures and photos so they can be restored
+
+ALTER TABLE projects ADD COLUMN deleted_at TIMESTAMP;
+
+CREATE INDEX idx_projects_deleted_at ON projects(deleted_at);
@@ -0,0 +1,4 @@
+-- Migration: Free-form tags on projects
+-- Stored as a JSON array string, like the recipe step and paint lists
+
+ALTER TABLE projects ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,6 @@
+-- Migration: SHA-256 of each photo's bytes, so retried uploads can be recognised
+-- Left NULL for photos uploaded before hashing was introduced
+
+ALTER TABLE photos ADD COLUMN content_hash VARCHAR(64);
+
+CREATE INDEX idx_photos_miniature_hash ON photos(miniature_id, content_hash);
@@ -0,0 +1,4 @@
+-- Migration: Storage path of each photo's generated thumbnail
+-- NULL when the upload couldn't be decoded or predates thumbnails
+
+ALTER TABLE photos ADD COLUMN thumbnail_path VARCHAR(500);
@@ -0,0 +1,6 @@
+-- Migration: Mark one photo per miniature as its primary (featured) image
+-- The partial unique index keeps at most one primary photo per miniature
+
+ALTER TABLE photos ADD COLUMN is_primary BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX idx_photos_primary ON photos(miniature_id) WHERE is_primary;
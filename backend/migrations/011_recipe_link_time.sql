@@ -0,0 +1,6 @@
+-- Migration: Record when each recipe was linked to its miniature
+-- Links made before this migration are stamped with the time it ran
+
+ALTER TABLE miniature_recipes ADD COLUMN linked_at TIMESTAMP;
+
+UPDATE miniature_recipes SET linked_at = CURRENT_TIMESTAMP;
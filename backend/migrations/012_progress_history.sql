@@ -0,0 +1,13 @@
+-- Migration: Log each change of a miniature's progress status
+-- Rows are only written for real transitions, so a miniature's rows form its timeline
+
+CREATE TABLE progress_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    miniature_id INTEGER NOT NULL,
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    changed_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (miniature_id) REFERENCES miniatures(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_progress_history_miniature_id ON progress_history(miniature_id);
@@ -0,0 +1,14 @@
+-- Migration: Paint inventory
+-- Names are free text, like the entries in a recipe's paints_used, and are matched against
+-- them case-insensitively
+
+CREATE TABLE paints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(255) NOT NULL,
+    brand VARCHAR(255),
+    quantity_remaining INTEGER NOT NULL DEFAULT 0 CHECK (quantity_remaining >= 0),
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_paints_name ON paints(name);
@@ -0,0 +1,4 @@
+-- Migration: Points cost per miniature, for army list building
+-- NULL means no value has been entered yet
+
+ALTER TABLE miniatures ADD COLUMN points_value INTEGER CHECK (points_value IS NULL OR points_value >= 0);
@@ -0,0 +1,3 @@
+-- Migration: Index for listing a project's miniatures alphabetically
+
+CREATE INDEX idx_miniatures_project_name ON miniatures(project_id, name);
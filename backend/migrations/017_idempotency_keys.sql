@@ -0,0 +1,13 @@
+-- Migration: Idempotency keys for creation requests
+-- A retried POST carrying the same Idempotency-Key gets the resource the first one created.
+-- Keys are scoped to the route they were sent to and expire after a day.
+
+CREATE TABLE idempotency_keys (
+    scope VARCHAR(255) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    resource_id BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
@@ -0,0 +1,106 @@
+-- MySQL/MariaDB schema, equivalent to ../migrations up to 014_miniature_points.sql
+-- MySQL was added after those migrations, so it starts from their end state in one step.
+-- Later schema changes need a migration here as well as in ../migrations.
+--
+-- Timestamps are DATETIME(6) holding UTC; the app writes them with UTC_TIMESTAMP(6).
+-- Needs MySQL 8.0.16+ or MariaDB 10.4+ for CHECK constraints and TEXT defaults.
+
+CREATE TABLE projects (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    game_system VARCHAR(50) NOT NULL CHECK (game_system IN ('age_of_sigmar', 'horus_heresy', 'kill_team', 'middle_earth', 'warhammer_40k')),
+    army VARCHAR(255) NOT NULL,
+    description TEXT,
+    tags TEXT NOT NULL DEFAULT ('[]'),
+    created_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    updated_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    deleted_at DATETIME(6) NULL
+);
+
+CREATE INDEX idx_projects_game_system ON projects(game_system);
+CREATE INDEX idx_projects_deleted_at ON projects(deleted_at);
+
+CREATE TABLE miniatures (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    project_id BIGINT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    miniature_type VARCHAR(20) NOT NULL CHECK (miniature_type IN ('troop', 'character', 'vehicle', 'monster')),
+    progress_status VARCHAR(50) NOT NULL CHECK (progress_status IN ('unpainted', 'primed', 'basecoated', 'detailed', 'completed', 'varnished')),
+    notes TEXT,
+    points_value INT CHECK (points_value IS NULL OR points_value >= 0),
+    created_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    updated_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_miniatures_project_id ON miniatures(project_id);
+
+CREATE TABLE painting_recipes (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    miniature_type VARCHAR(20) NOT NULL CHECK (miniature_type IN ('troop', 'character', 'vehicle', 'monster')),
+    steps TEXT NOT NULL, -- JSON array of steps
+    paints_used TEXT, -- JSON array of paint names/codes
+    techniques TEXT, -- JSON array of techniques
+    notes TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    updated_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6))
+);
+
+CREATE INDEX idx_recipes_miniature_type ON painting_recipes(miniature_type);
+
+CREATE TABLE photos (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    miniature_id BIGINT NOT NULL,
+    filename VARCHAR(255) NOT NULL,
+    file_path VARCHAR(500) NOT NULL,
+    file_size BIGINT,
+    mime_type VARCHAR(100),
+    uploaded_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    content_hash VARCHAR(64),
+    thumbnail_path VARCHAR(500),
+    is_primary BOOLEAN NOT NULL DEFAULT FALSE,
+    -- MySQL has no partial indexes; this is the miniature id for its primary photo and NULL
+    -- otherwise, and NULLs never collide in a unique index
+    primary_miniature_id BIGINT AS (CASE WHEN is_primary THEN miniature_id END) STORED,
+    FOREIGN KEY (miniature_id) REFERENCES miniatures(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_photos_miniature_id ON photos(miniature_id);
+CREATE INDEX idx_photos_miniature_hash ON photos(miniature_id, content_hash);
+CREATE UNIQUE INDEX idx_photos_primary ON photos(primary_miniature_id);
+
+CREATE TABLE miniature_recipes (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    miniature_id BIGINT NOT NULL,
+    recipe_id BIGINT NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT (UTC_TIMESTAMP(6)),
+    linked_at DATETIME(6),
+    FOREIGN KEY (miniature_id) REFERENCES miniatures(id) ON DELETE CASCADE,
+    FOREIGN KEY (recipe_id) REFERENCES painting_recipes(id) ON DELETE CASCADE,
+    UNIQUE (miniature_id, recipe_id)
+);
+
+CREATE INDEX idx_miniature_recipes_recipe_id ON miniature_recipes(recipe_id);
+
+CREATE TABLE progress_history (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    miniature_id BIGINT NOT NULL,
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    changed_at DATETIME(6) NOT NULL,
+    FOREIGN KEY (miniature_id) REFERENCES miniatures(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_progress_history_miniature_id ON progress_history(miniature_id);
+
+CREATE TABLE paints (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    brand VARCHAR(255),
+    quantity_remaining BIGINT NOT NULL DEFAULT 0 CHECK (quantity_remaining >= 0),
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL
+);
+
+CREATE INDEX idx_paints_name ON paints(name);
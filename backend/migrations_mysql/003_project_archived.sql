@@ -0,0 +1,4 @@
+-- Migration: Archive finished projects
+-- Archived projects are kept out of the default list but, unlike deleted ones, are still live
+
+ALTER TABLE projects ADD COLUMN archived BOOLEAN NOT NULL DEFAULT FALSE;
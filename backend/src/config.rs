@@ -1,19 +1,88 @@
+use crate::services::thumbnail_service;
+use axum::http::HeaderValue;
 use std::env;
+use std::fs;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
+    /// PostgreSQL read replica that serves repository reads; writes stay on `database_url`
+    pub database_replica_url: Option<String>,
+    pub database_connect_attempts: u32,
     pub port: u16,
     pub storage_type: StorageType,
     pub aws_region: Option<String>,
     pub s3_bucket: Option<String>,
+    /// Public URL (such as a CloudFront distribution) that S3 photo links are built on
+    /// instead of being presigned
+    pub s3_base_url: Option<String>,
     pub local_storage_path: Option<String>,
+    pub local_storage_base_url: Option<String>,
+    pub gcs_bucket: Option<String>,
+    pub gcs_base_url: Option<String>,
+    pub gcs_hmac_access_id: Option<String>,
+    pub gcs_hmac_secret: Option<String>,
+    pub max_image_width: u32,
+    pub max_image_height: u32,
+    /// MIME types photos may be uploaded as, lowercase
+    pub allowed_image_types: Vec<String>,
+    pub max_request_body_bytes: usize,
+    /// Origins allowed to make cross-origin requests; `None` allows any origin (development)
+    pub allowed_origins: Option<Vec<HeaderValue>>,
+    /// Record request metrics and serve them at `/metrics`
+    pub metrics_enabled: bool,
+    /// Where to POST a notification when a miniature is completed
+    pub webhook_url: Option<String>,
+    /// Most photos a single miniature may receive per minute; 0 turns the limit off
+    pub upload_rate_limit_per_minute: u32,
+    /// Format uploads are re-encoded to before storing, when that makes them smaller
+    pub reencode_uploads: Option<ReencodeFormat>,
+    /// WebP quality for re-encoded uploads, from 0 (smallest) to 100 (best)
+    pub reencode_quality: u8,
+    /// How long browsers may cache photo contents, which never change once uploaded
+    pub photo_cache_max_age_seconds: u32,
+    /// How stored photos are laid out in directories
+    pub path_strategy: PathStrategy,
+    /// Bearer token the `/api/admin` endpoints require; they are disabled without one
+    pub admin_token: Option<String>,
+    /// Add demo data at startup when there are no projects yet
+    pub seed_on_start: bool,
+    /// Refuse to create, clone, or import a project with the same name, game system, and army
+    /// as a live one
+    pub enforce_unique_project_names: bool,
+    /// Refuse status updates that skip ahead more than one step, unless forced
+    pub strict_progress_transitions: bool,
 }
 
+/// Upload types accepted when `ALLOWED_IMAGE_TYPES` is unset
+pub const DEFAULT_ALLOWED_IMAGE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+
 #[derive(Debug, Clone)]
 pub enum StorageType {
     Local,
     S3,
+    Gcs,
+    Memory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReencodeFormat {
+    /// Lossy WebP at `REENCODE_QUALITY`
+    Webp,
+}
+
+/// Directory layout for stored photos, all under `miniatures/` and each named
+/// `<uuid>_<original name>` so that paths stay unique
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PathStrategy {
+    /// `miniatures/<file>`
+    Flat,
+    /// `miniatures/<miniature id>/<file>`
+    #[default]
+    ByMiniature,
+    /// `miniatures/<year>/<month>/<miniature id>/<file>`, dated when stored
+    ByDate,
 }
 
 impl Config {
@@ -23,28 +92,359 @@ impl Config {
         let database_url = env::var("DATABASE_URL")
             .unwrap_or_else(|_| "sqlite:./miniature_tracker.db".to_string());
 
+        let database_replica_url = env::var("DATABASE_REPLICA_URL").ok();
+
+        let database_connect_attempts = env::var("DATABASE_CONNECT_ATTEMPTS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<u32>()?;
+
         let port = env::var("PORT")
             .unwrap_or_else(|_| "3000".to_string())
             .parse::<u16>()?;
 
         let storage_type = match env::var("STORAGE_TYPE").as_deref() {
+            Ok("local") | Err(_) => StorageType::Local,
             Ok("s3") => StorageType::S3,
-            _ => StorageType::Local,
+            Ok("gcs") => StorageType::Gcs,
+            Ok("memory") => StorageType::Memory,
+            Ok(other) => {
+                return Err(format!(
+                    "STORAGE_TYPE {:?} is not one of local, s3, gcs, or memory",
+                    other
+                )
+                .into())
+            }
         };
 
         let aws_region = env::var("AWS_REGION").ok();
         let s3_bucket = env::var("S3_BUCKET").ok();
+        let s3_base_url = env::var("S3_BASE_URL").ok();
         let local_storage_path = env::var("LOCAL_STORAGE_PATH")
             .ok()
             .or_else(|| Some("./uploads".to_string()));
+        let local_storage_base_url = env::var("LOCAL_STORAGE_BASE_URL").ok();
+        let gcs_bucket = env::var("GCS_BUCKET").ok();
+        let gcs_base_url = env::var("GCS_BASE_URL").ok();
+        let gcs_hmac_access_id = env::var("GCS_HMAC_ACCESS_ID").ok();
+        let gcs_hmac_secret = env::var("GCS_HMAC_SECRET").ok();
+
+        let max_image_width = env::var("MAX_IMAGE_WIDTH")
+            .unwrap_or_else(|_| "8000".to_string())
+            .parse::<u32>()?;
+        let max_image_height = env::var("MAX_IMAGE_HEIGHT")
+            .unwrap_or_else(|_| "8000".to_string())
+            .parse::<u32>()?;
+
+        let allowed_image_types = match env::var("ALLOWED_IMAGE_TYPES") {
+            Ok(value) if !value.trim().is_empty() => parse_image_types(&value),
+            _ => DEFAULT_ALLOWED_IMAGE_TYPES
+                .iter()
+                .map(|mime_type| mime_type.to_string())
+                .collect(),
+        };
+
+        let max_request_body_bytes = env::var("MAX_REQUEST_BODY_BYTES")
+            .unwrap_or_else(|_| "1048576".to_string())
+            .parse::<usize>()?;
+
+        let allowed_origins = match env::var("ALLOWED_ORIGINS") {
+            Ok(value) => Some(parse_allowed_origins(&value)?),
+            Err(_) => None,
+        };
+
+        let metrics_enabled = env::var("METRICS_ENABLED")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        let seed_on_start = env::var("SEED_ON_START")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        let enforce_unique_project_names = env::var("ENFORCE_UNIQUE_PROJECT_NAMES")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        let strict_progress_transitions = env::var("STRICT_PROGRESS_TRANSITIONS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        let webhook_url = env::var("WEBHOOK_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+
+        let upload_rate_limit_per_minute = env::var("UPLOAD_RATE_LIMIT_PER_MINUTE")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<u32>()?;
+
+        let photo_cache_max_age_seconds = env::var("PHOTO_CACHE_MAX_AGE_SECONDS")
+            .unwrap_or_else(|_| "31536000".to_string())
+            .parse::<u32>()?;
+
+        let reencode_uploads = match env::var("REENCODE_UPLOADS").as_deref() {
+            Err(_) | Ok("") | Ok("none") => None,
+            Ok("webp") => Some(ReencodeFormat::Webp),
+            Ok(other) => {
+                return Err(format!(
+                    "REENCODE_UPLOADS {:?} is not supported; use webp or none",
+                    other
+                )
+                .into())
+            }
+        };
+
+        let reencode_quality = env::var("REENCODE_QUALITY")
+            .unwrap_or_else(|_| "80".to_string())
+            .parse::<u8>()?;
+
+        let path_strategy = match env::var("PATH_STRATEGY").as_deref() {
+            Err(_) | Ok("") | Ok("by_miniature") => PathStrategy::ByMiniature,
+            Ok("flat") => PathStrategy::Flat,
+            Ok("by_date") => PathStrategy::ByDate,
+            Ok(other) => {
+                return Err(format!(
+                    "PATH_STRATEGY {:?} is not one of flat, by_miniature, or by_date",
+                    other
+                )
+                .into())
+            }
+        };
+
+        let admin_token = env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.trim().is_empty());
 
         Ok(Config {
             database_url,
+            database_replica_url,
+            database_connect_attempts,
             port,
             storage_type,
             aws_region,
             s3_bucket,
+            s3_base_url,
             local_storage_path,
+            local_storage_base_url,
+            gcs_bucket,
+            gcs_base_url,
+            gcs_hmac_access_id,
+            gcs_hmac_secret,
+            max_image_width,
+            max_image_height,
+            allowed_image_types,
+            max_request_body_bytes,
+            allowed_origins,
+            metrics_enabled,
+            webhook_url,
+            upload_rate_limit_per_minute,
+            reencode_uploads,
+            reencode_quality,
+            photo_cache_max_age_seconds,
+            path_strategy,
+            admin_token,
+            seed_on_start,
+            enforce_unique_project_names,
+            strict_progress_transitions,
         })
     }
+
+    /// Check settings that depend on each other, so a missing variable fails at startup
+    /// instead of on the first upload. Every problem found is reported, not just the first.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        if self.database_replica_url.is_some() && !self.database_url.starts_with("postgres") {
+            problems.push("DATABASE_REPLICA_URL requires a PostgreSQL DATABASE_URL".to_string());
+        }
+
+        let mut require = |value: &Option<String>, variable: &str, storage: &str| {
+            if value.as_deref().is_none_or(|value| value.trim().is_empty()) {
+                problems.push(format!(
+                    "{} is required when STORAGE_TYPE={}",
+                    variable, storage
+                ));
+            }
+        };
+
+        match self.storage_type {
+            StorageType::Local => {
+                let path = self.local_storage_path.as_deref().unwrap_or("./uploads");
+                if let Err(e) = check_writable(Path::new(path)) {
+                    problems.push(format!(
+                        "LOCAL_STORAGE_PATH {:?} is not a writable directory: {}",
+                        path, e
+                    ));
+                }
+            }
+            StorageType::S3 => {
+                require(&self.s3_bucket, "S3_BUCKET", "s3");
+                require(&self.aws_region, "AWS_REGION", "s3");
+            }
+            StorageType::Gcs => {
+                require(&self.gcs_bucket, "GCS_BUCKET", "gcs");
+                require(&self.gcs_hmac_access_id, "GCS_HMAC_ACCESS_ID", "gcs");
+                require(&self.gcs_hmac_secret, "GCS_HMAC_SECRET", "gcs");
+            }
+            StorageType::Memory => {}
+        }
+
+        // Thumbnails and re-encoding decode every upload, so each type needs a decoder built in
+        if self.allowed_image_types.is_empty() {
+            problems.push("ALLOWED_IMAGE_TYPES is set but lists no types".to_string());
+        }
+        for mime_type in &self.allowed_image_types {
+            if !thumbnail_service::can_decode(mime_type) {
+                problems.push(format!(
+                    "ALLOWED_IMAGE_TYPES entry {:?} is not an image type this build can decode",
+                    mime_type
+                ));
+            }
+        }
+
+        if self.reencode_quality > 100 {
+            problems.push(format!(
+                "REENCODE_QUALITY {} must be from 0 to 100",
+                self.reencode_quality
+            ));
+        }
+
+        if let Some(url) = &self.webhook_url {
+            let valid = (url.starts_with("http://") || url.starts_with("https://"))
+                && url.parse::<axum::http::Uri>().is_ok();
+            if !valid {
+                problems.push(format!(
+                    "WEBHOOK_URL {:?} must be an http:// or https:// URL",
+                    url
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid configuration: {}", problems.join("; ")))
+        }
+    }
+
+    /// Log which database, storage, and optional features this instance runs with. Database
+    /// URLs are redacted, and secrets such as the admin token, GCS HMAC key, and webhook URL
+    /// are only reported as set or not.
+    pub fn log_summary(&self) {
+        tracing::info!(
+            version = env!("CARGO_PKG_VERSION"),
+            database = %redact_url(&self.database_url),
+            database_replica = ?self.database_replica_url.as_deref().map(redact_url),
+            storage = %self.storage_summary(),
+            path_strategy = ?self.path_strategy,
+            port = self.port,
+            "Starting miniature-painting-tracker"
+        );
+        tracing::info!(
+            allowed_origins = self.allowed_origins.as_ref().map_or(0, Vec::len),
+            metrics_enabled = self.metrics_enabled,
+            admin_endpoints = self.admin_token.is_some(),
+            webhook = self.webhook_url.is_some(),
+            upload_rate_limit_per_minute = self.upload_rate_limit_per_minute,
+            allowed_image_types = %self.allowed_image_types.join(","),
+            reencode_uploads = ?self.reencode_uploads,
+            reencode_quality = self.reencode_quality,
+            seed_on_start = self.seed_on_start,
+            enforce_unique_project_names = self.enforce_unique_project_names,
+            strict_progress_transitions = self.strict_progress_transitions,
+            "Feature flags (allowed_origins = 0 means any origin)"
+        );
+    }
+
+    /// The storage backend and where it keeps photos, e.g. `s3 (bucket in us-east-1)`
+    pub fn storage_summary(&self) -> String {
+        let or_unset = |value: &Option<String>| value.clone().unwrap_or_else(|| "unset".into());
+        match self.storage_type {
+            StorageType::Local => format!(
+                "local ({})",
+                self.local_storage_path.as_deref().unwrap_or("./uploads")
+            ),
+            StorageType::S3 => format!(
+                "s3 ({} in {})",
+                or_unset(&self.s3_bucket),
+                or_unset(&self.aws_region)
+            ),
+            StorageType::Gcs => format!("gcs ({})", or_unset(&self.gcs_bucket)),
+            StorageType::Memory => "memory (lost on restart)".to_string(),
+        }
+    }
+}
+
+/// A connection URL safe to log: any user and password become `***`, and so does the query
+/// string, which may carry them too. An `@` in the query can hide more than needed, never less.
+pub fn redact_url(url: &str) -> String {
+    let scheme_end = url.find("://").map_or(0, |index| index + 3);
+    // Passwords may contain `?`, so the query is only looked for after the credentials
+    let (scheme, rest) = url.split_at(scheme_end);
+    let (credentials, rest) = match rest.rfind('@') {
+        Some(at) => ("***", &rest[at..]),
+        None => ("", rest),
+    };
+    let rest = match rest.split_once('?') {
+        Some((path, _)) => format!("{}?***", path),
+        None => rest.to_string(),
+    };
+    format!("{}{}{}", scheme, credentials, rest)
+}
+
+/// Parse a comma-separated list of MIME types such as `image/jpeg, image/gif`, lowercased
+/// and without duplicates. Whether each is supported is left to `Config::validate`.
+pub fn parse_image_types(value: &str) -> Vec<String> {
+    let mut types: Vec<String> = Vec::new();
+    for mime_type in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let mime_type = mime_type.to_ascii_lowercase();
+        if !types.contains(&mime_type) {
+            types.push(mime_type);
+        }
+    }
+    types
+}
+
+/// Create the directory if needed and prove files can be written to it
+fn check_writable(path: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(path)?;
+    let probe = path.join(".write-check");
+    fs::write(&probe, b"")?;
+    fs::remove_file(probe)
+}
+
+/// Parse a comma-separated list of origins such as `https://paint.example.com,http://localhost:5173`.
+/// Each entry must be a bare `http`/`https` origin, so a typo fails at startup rather than
+/// silently blocking the frontend.
+pub fn parse_allowed_origins(value: &str) -> Result<Vec<HeaderValue>, String> {
+    let origins = value
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            let host = origin
+                .strip_prefix("https://")
+                .or_else(|| origin.strip_prefix("http://"))
+                .ok_or_else(|| {
+                    format!(
+                        "Allowed origin {:?} must start with http:// or https://",
+                        origin
+                    )
+                })?;
+
+            if host.is_empty() || host.contains(['/', '?', '#', ' ', '*']) {
+                return Err(format!(
+                    "Allowed origin {:?} must be a scheme and host with no path or wildcard",
+                    origin
+                ));
+            }
+
+            HeaderValue::from_str(origin)
+                .map_err(|_| format!("Allowed origin {:?} is not a valid header value", origin))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if origins.is_empty() {
+        return Err("ALLOWED_ORIGINS is set but lists no origins".to_string());
+    }
+
+    Ok(origins)
 }
@@ -1,14 +1,25 @@
-use sqlx::{migrate::MigrateDatabase, Pool, Postgres, Row, Sqlite};
+pub mod seed;
+
+use crate::config::redact_url;
+use rand::Rng;
+use sqlx::{migrate::MigrateDatabase, pool::PoolConnection, MySql, Pool, Postgres, Row, Sqlite};
+use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 use tracing::{error, info, warn};
 
 #[derive(Clone)]
 pub enum Database {
     Sqlite(Pool<Sqlite>),
-    Postgres(Pool<Postgres>),
+    /// The primary pool, plus an optional read replica that serves repository reads
+    Postgres(Pool<Postgres>, Option<Pool<Postgres>>),
+    /// MySQL or MariaDB
+    Mysql(Pool<MySql>),
 }
 
-#[derive(Debug)]
+/// Upper bound on the wait between startup connection attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
 pub struct DatabaseConfig {
     pub max_connections: u32,
     pub acquire_timeout: Duration,
@@ -28,6 +39,7 @@ impl Default for DatabaseConfig {
 }
 
 impl Database {
+    #[allow(dead_code)]
     pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
         Self::new_with_config(database_url, DatabaseConfig::default()).await
     }
@@ -38,7 +50,7 @@ impl Database {
     ) -> Result<Self, sqlx::Error> {
         info!(
             "Initializing database connection with URL: {}",
-            database_url.split('@').next().unwrap_or("***")
+            redact_url(database_url)
         );
 
         if database_url.starts_with("sqlite:") {
@@ -47,14 +59,79 @@ impl Database {
             || database_url.starts_with("postgresql://")
         {
             Self::create_postgres_pool(database_url, config).await
+        } else if database_url.starts_with("mysql://") || database_url.starts_with("mariadb://") {
+            Self::create_mysql_pool(database_url, config).await
         } else {
-            error!("Unsupported database URL format: {}", database_url);
+            error!(
+                "Unsupported database URL format: {}",
+                redact_url(database_url)
+            );
             Err(sqlx::Error::Configuration(
-                "Unsupported database URL format. Use 'sqlite:' or 'postgres://'".into(),
+                "Unsupported database URL format. Use 'sqlite:', 'postgres://', or 'mysql://'"
+                    .into(),
             ))
         }
     }
 
+    /// Connect, retrying connection failures with exponential backoff and jitter so the
+    /// service survives starting before its database is ready. Configuration errors such as
+    /// an unsupported URL fail on the first attempt.
+    pub async fn new_with_retry(
+        database_url: &str,
+        config: DatabaseConfig,
+        max_attempts: u32,
+        base_delay: Duration,
+    ) -> Result<Self, sqlx::Error> {
+        let max_attempts = max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            match Self::new_with_config(database_url, config.clone()).await {
+                Ok(database) => return Ok(database),
+                Err(e) if attempt < max_attempts && is_retryable(&e) => {
+                    let delay = retry_delay(base_delay, attempt);
+                    warn!(
+                        "Database connection attempt {}/{} failed: {}; retrying in {:?}",
+                        attempt, max_attempts, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!(
+                        "Database connection failed after {} attempt(s): {}",
+                        attempt, e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Route repository reads to a PostgreSQL read replica; writes, transactions, and
+    /// migrations stay on the primary. Replica lag means a read may briefly miss a write.
+    pub async fn with_read_replica(
+        self,
+        replica_url: &str,
+        config: DatabaseConfig,
+    ) -> Result<Self, sqlx::Error> {
+        let Database::Postgres(primary, _) = self else {
+            return Err(sqlx::Error::Configuration(
+                "A read replica is only supported with PostgreSQL".into(),
+            ));
+        };
+
+        match Self::new_with_config(replica_url, config).await? {
+            Database::Postgres(replica, _) => {
+                info!("Routing reads to the PostgreSQL read replica");
+                Ok(Database::Postgres(primary, Some(replica)))
+            }
+            _ => Err(sqlx::Error::Configuration(
+                "The read replica must be a PostgreSQL database".into(),
+            )),
+        }
+    }
+
     async fn create_sqlite_pool(
         database_url: &str,
         config: DatabaseConfig,
@@ -102,18 +179,67 @@ impl Database {
         let pool = pool_options.connect(database_url).await?;
 
         info!("PostgreSQL connection pool created successfully");
-        Ok(Database::Postgres(pool))
+        Ok(Database::Postgres(pool, None))
+    }
+
+    async fn create_mysql_pool(
+        database_url: &str,
+        config: DatabaseConfig,
+    ) -> Result<Self, sqlx::Error> {
+        let mut pool_options = sqlx::mysql::MySqlPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout);
+
+        if let Some(idle_timeout) = config.idle_timeout {
+            pool_options = pool_options.idle_timeout(idle_timeout);
+        }
+
+        if let Some(max_lifetime) = config.max_lifetime {
+            pool_options = pool_options.max_lifetime(max_lifetime);
+        }
+
+        let pool = pool_options.connect(database_url).await?;
+
+        info!("MySQL connection pool created successfully");
+        Ok(Database::Mysql(pool))
     }
 
     pub async fn migrate(&self) -> Result<(), sqlx::Error> {
         info!("Running database migrations");
         match self {
             Database::Sqlite(pool) => {
-                sqlx::migrate!("./migrations").run(pool).await?;
+                // Table rebuilds would cascade-delete child rows with foreign keys on,
+                // and the pragma is ignored inside the migration's own transaction
+                let mut conn = pool.acquire().await?;
+                sqlx::query("PRAGMA foreign_keys = OFF")
+                    .execute(&mut *conn)
+                    .await?;
+                let result = sqlx::migrate!("./migrations").run(&mut *conn).await;
+                sqlx::query("PRAGMA foreign_keys = ON")
+                    .execute(&mut *conn)
+                    .await?;
+                result?;
+
+                let violations = sqlx::query("PRAGMA foreign_key_check")
+                    .fetch_all(&mut *conn)
+                    .await?;
+                if !violations.is_empty() {
+                    error!(
+                        "Migrations left {} foreign key violations",
+                        violations.len()
+                    );
+                    return Err(sqlx::Error::Protocol(
+                        "foreign key violations after migration".to_string(),
+                    ));
+                }
             }
-            Database::Postgres(pool) => {
+            Database::Postgres(pool, _) => {
                 sqlx::migrate!("./migrations").run(pool).await?;
             }
+            // MySQL's DDL differs too much to share the SQLite/PostgreSQL migrations
+            Database::Mysql(pool) => {
+                sqlx::migrate!("./migrations_mysql").run(pool).await?;
+            }
         }
         info!("Database migrations completed successfully");
         Ok(())
@@ -130,9 +256,19 @@ impl Database {
                     Err(sqlx::Error::RowNotFound)
                 }
             }
-            Database::Postgres(pool) => {
+            Database::Postgres(pool, replica) => {
+                for pool in std::iter::once(pool).chain(replica) {
+                    let row = sqlx::query("SELECT 1 as health").fetch_one(pool).await?;
+                    let health: i32 = row.get("health");
+                    if health != 1 {
+                        return Err(sqlx::Error::RowNotFound);
+                    }
+                }
+                Ok(())
+            }
+            Database::Mysql(pool) => {
                 let row = sqlx::query("SELECT 1 as health").fetch_one(pool).await?;
-                let health: i32 = row.get("health");
+                let health: i64 = row.get("health");
                 if health == 1 {
                     Ok(())
                 } else {
@@ -146,10 +282,41 @@ impl Database {
         info!("Closing database connection pool");
         match self {
             Database::Sqlite(pool) => pool.close().await,
-            Database::Postgres(pool) => pool.close().await,
+            Database::Postgres(pool, replica) => {
+                pool.close().await;
+                if let Some(replica) = replica {
+                    replica.close().await;
+                }
+            }
+            Database::Mysql(pool) => pool.close().await,
+        }
+    }
+
+    /// Connections currently open in the pools, and how many of those are idle
+    pub fn pool_connections(&self) -> (u32, usize) {
+        match self {
+            Database::Sqlite(pool) => (pool.size(), pool.num_idle()),
+            Database::Postgres(pool, replica) => std::iter::once(pool)
+                .chain(replica)
+                .fold((0, 0), |(size, idle), pool| {
+                    (size + pool.size(), idle + pool.num_idle())
+                }),
+            Database::Mysql(pool) => (pool.size(), pool.num_idle()),
         }
     }
 
+    /// Start a transaction. Repository methods given `&mut transaction` run inside it; none of
+    /// their changes are visible elsewhere until `commit`, and dropping it without committing
+    /// rolls them all back.
+    pub async fn begin(&self) -> Result<Transaction, sqlx::Error> {
+        match self {
+            Database::Sqlite(pool) => Ok(Transaction::Sqlite(pool.begin().await?)),
+            Database::Postgres(pool, _) => Ok(Transaction::Postgres(pool.begin().await?)),
+            Database::Mysql(pool) => Ok(Transaction::Mysql(pool.begin().await?)),
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn get_sqlite_pool(&self) -> Option<&Pool<Sqlite>> {
         match self {
             Database::Sqlite(pool) => Some(pool),
@@ -157,10 +324,163 @@ impl Database {
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_postgres_pool(&self) -> Option<&Pool<Postgres>> {
         match self {
-            Database::Postgres(pool) => Some(pool),
+            Database::Postgres(pool, _) => Some(pool),
             _ => None,
         }
     }
 }
+
+/// An open transaction on any backend, from `Database::begin`
+pub enum Transaction {
+    Sqlite(sqlx::Transaction<'static, Sqlite>),
+    Postgres(sqlx::Transaction<'static, Postgres>),
+    Mysql(sqlx::Transaction<'static, MySql>),
+}
+
+impl Transaction {
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        match self {
+            Transaction::Sqlite(tx) => tx.commit().await,
+            Transaction::Postgres(tx) => tx.commit().await,
+            Transaction::Mysql(tx) => tx.commit().await,
+        }
+    }
+
+    /// Discard the transaction's changes; dropping it uncommitted does the same
+    #[allow(dead_code)]
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        match self {
+            Transaction::Sqlite(tx) => tx.rollback().await,
+            Transaction::Postgres(tx) => tx.rollback().await,
+            Transaction::Mysql(tx) => tx.rollback().await,
+        }
+    }
+}
+
+/// What a repository method runs its queries against: the pool, or an open transaction.
+/// Repository methods take `impl Into<Executor>`, so callers pass `&database` or
+/// `&mut transaction` directly.
+pub enum Executor<'a> {
+    Database(&'a Database),
+    /// The database with reads kept off the replica, so they see writes made just before
+    Primary(&'a Database),
+    Transaction(&'a mut Transaction),
+}
+
+impl<'a> From<&'a Database> for Executor<'a> {
+    fn from(database: &'a Database) -> Self {
+        Executor::Database(database)
+    }
+}
+
+impl<'a> From<&'a mut Transaction> for Executor<'a> {
+    fn from(transaction: &'a mut Transaction) -> Self {
+        Executor::Transaction(transaction)
+    }
+}
+
+impl<'a> Executor<'a> {
+    /// Borrow this executor again, for a method that makes more than one repository call.
+    /// Reads through the new borrow stay on the primary so they see the method's own writes.
+    pub fn reborrow(&mut self) -> Executor<'_> {
+        match self {
+            Executor::Database(database) | Executor::Primary(database) => {
+                Executor::Primary(database)
+            }
+            Executor::Transaction(transaction) => Executor::Transaction(transaction),
+        }
+    }
+
+    /// A connection for reads, from the read replica when one is configured
+    pub async fn acquire_read(self) -> Result<Connection<'a>, sqlx::Error> {
+        match self {
+            Executor::Database(Database::Postgres(_, Some(replica))) => Ok(Connection::Postgres(
+                MaybePooled::Pooled(replica.acquire().await?),
+            )),
+            executor => executor.acquire().await,
+        }
+    }
+
+    /// A connection to run queries on: a fresh one from the primary pool, or the
+    /// transaction's own
+    pub async fn acquire(self) -> Result<Connection<'a>, sqlx::Error> {
+        Ok(match self {
+            Executor::Database(Database::Sqlite(pool))
+            | Executor::Primary(Database::Sqlite(pool)) => {
+                Connection::Sqlite(MaybePooled::Pooled(pool.acquire().await?))
+            }
+            Executor::Database(Database::Postgres(pool, _))
+            | Executor::Primary(Database::Postgres(pool, _)) => {
+                Connection::Postgres(MaybePooled::Pooled(pool.acquire().await?))
+            }
+            Executor::Database(Database::Mysql(pool))
+            | Executor::Primary(Database::Mysql(pool)) => {
+                Connection::Mysql(MaybePooled::Pooled(pool.acquire().await?))
+            }
+            Executor::Transaction(Transaction::Sqlite(tx)) => {
+                Connection::Sqlite(MaybePooled::Borrowed(&mut **tx))
+            }
+            Executor::Transaction(Transaction::Postgres(tx)) => {
+                Connection::Postgres(MaybePooled::Borrowed(&mut **tx))
+            }
+            Executor::Transaction(Transaction::Mysql(tx)) => {
+                Connection::Mysql(MaybePooled::Borrowed(&mut **tx))
+            }
+        })
+    }
+}
+
+/// A connection acquired from an `Executor`; run queries on `&mut *conn`
+pub enum Connection<'a> {
+    Sqlite(MaybePooled<'a, Sqlite>),
+    Postgres(MaybePooled<'a, Postgres>),
+    Mysql(MaybePooled<'a, MySql>),
+}
+
+/// A connection either checked out of the pool for one call or borrowed from a transaction
+pub enum MaybePooled<'a, DB: sqlx::Database> {
+    Pooled(PoolConnection<DB>),
+    Borrowed(&'a mut DB::Connection),
+}
+
+impl<DB: sqlx::Database> Deref for MaybePooled<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            MaybePooled::Pooled(conn) => conn,
+            MaybePooled::Borrowed(conn) => conn,
+        }
+    }
+}
+
+impl<DB: sqlx::Database> DerefMut for MaybePooled<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            MaybePooled::Pooled(conn) => conn,
+            MaybePooled::Borrowed(conn) => conn,
+        }
+    }
+}
+
+/// Whether a connection error might clear up on its own, e.g. the server is still starting
+fn is_retryable(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolTimedOut => true,
+        // PostgreSQL's cannot_connect_now: "the database system is starting up"
+        sqlx::Error::Database(e) => e.code().as_deref() == Some("57P03"),
+        _ => false,
+    }
+}
+
+/// Exponential backoff from `base_delay`, capped, plus up to 50% random jitter
+fn retry_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let backoff = base_delay
+        .saturating_mul(2u32.saturating_pow(attempt - 1))
+        .min(MAX_RETRY_DELAY);
+    let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+    backoff.mul_f64(1.0 + jitter)
+}
@@ -0,0 +1,195 @@
+use super::Database;
+use crate::repositories::{
+    project_repository::ProjectFilter, MiniatureRecipeRepository, MiniatureRepository,
+    ProjectRepository, RecipeRepository,
+};
+use shared_types::{
+    CreateMiniatureRequest, CreateProjectRequest, CreateRecipeRequest, GameSystem, MiniatureType,
+    ProgressStatus,
+};
+
+/// What `seed_if_empty` created
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub projects: usize,
+    pub miniatures: usize,
+    pub recipes: usize,
+}
+
+/// A demo miniature: its name, type, the status it is moved to, and which of the demo
+/// recipes (by index) it uses
+type DemoMiniature = (&'static str, MiniatureType, ProgressStatus, Option<usize>);
+
+/// Fill a fresh install with a few demo projects, miniatures, and recipes so there's
+/// something to explore. Does nothing, returning `None`, once any project exists, counting
+/// soft-deleted and archived ones. Everything is written in one transaction.
+pub async fn seed_if_empty(database: &Database) -> Result<Option<SeedSummary>, sqlx::Error> {
+    let mut transaction = database.begin().await?;
+
+    let everything = ProjectFilter {
+        include_deleted: true,
+        include_archived: true,
+        ..Default::default()
+    };
+    if ProjectRepository::count(&mut transaction, &everything).await? > 0 {
+        return Ok(None);
+    }
+
+    let mut summary = SeedSummary::default();
+
+    let mut recipe_ids = Vec::new();
+    for recipe in demo_recipes() {
+        recipe_ids.push(RecipeRepository::create(&mut transaction, recipe).await?.id);
+        summary.recipes += 1;
+    }
+
+    for (project, miniatures) in demo_projects() {
+        let project = ProjectRepository::create(&mut transaction, project).await?;
+        summary.projects += 1;
+
+        for (name, miniature_type, status, recipe) in miniatures {
+            let miniature = MiniatureRepository::create(
+                &mut transaction,
+                project.id,
+                CreateMiniatureRequest {
+                    name: name.to_string(),
+                    miniature_type,
+                    notes: None,
+                    points_value: None,
+                },
+            )
+            .await?;
+            summary.miniatures += 1;
+
+            if status != ProgressStatus::Unpainted {
+                MiniatureRepository::bulk_update_status(
+                    &mut transaction,
+                    project.id,
+                    &[miniature.id],
+                    status,
+                )
+                .await?;
+            }
+            if let Some(recipe) = recipe {
+                MiniatureRecipeRepository::link(&mut transaction, miniature.id, recipe_ids[recipe])
+                    .await?;
+            }
+        }
+    }
+
+    transaction.commit().await?;
+    Ok(Some(summary))
+}
+
+fn demo_recipes() -> Vec<CreateRecipeRequest> {
+    let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+
+    vec![
+        CreateRecipeRequest {
+            name: "Ultramarine Blue Armour".to_string(),
+            miniature_type: MiniatureType::Troop,
+            steps: strings(&[
+                "Prime with Macragge Blue spray",
+                "Shade recesses with Nuln Oil",
+                "Layer Calgar Blue on raised panels",
+                "Edge highlight with Fenrisian Grey",
+            ]),
+            paints_used: strings(&["Macragge Blue", "Nuln Oil", "Calgar Blue", "Fenrisian Grey"]),
+            techniques: strings(&["Recess shading", "Edge highlighting"]),
+            notes: Some("Demo recipe".to_string()),
+        },
+        CreateRecipeRequest {
+            name: "Stormcast Gold".to_string(),
+            miniature_type: MiniatureType::Character,
+            steps: strings(&[
+                "Basecoat Retributor Armour",
+                "Wash with Reikland Fleshshade",
+                "Highlight with Liberator Gold",
+            ]),
+            paints_used: strings(&["Retributor Armour", "Reikland Fleshshade", "Liberator Gold"]),
+            techniques: strings(&["Washing", "Highlighting"]),
+            notes: Some("Demo recipe".to_string()),
+        },
+        CreateRecipeRequest {
+            name: "Weathered Tank Hull".to_string(),
+            miniature_type: MiniatureType::Vehicle,
+            steps: strings(&[
+                "Basecoat Castellan Green",
+                "Sponge chip with Rhinox Hide",
+                "Streak with Typhus Corrosion",
+            ]),
+            paints_used: strings(&["Castellan Green", "Rhinox Hide", "Typhus Corrosion"]),
+            techniques: strings(&["Sponge chipping", "Streaking"]),
+            notes: Some("Demo recipe".to_string()),
+        },
+    ]
+}
+
+fn demo_projects() -> Vec<(CreateProjectRequest, Vec<DemoMiniature>)> {
+    vec![
+        (
+            CreateProjectRequest {
+                name: "Ultramarines Strike Force".to_string(),
+                game_system: GameSystem::Warhammer40k,
+                army: "Space Marines".to_string(),
+                description: Some("Demo project: a small combat patrol".to_string()),
+                tags: vec!["demo".to_string()],
+            },
+            vec![
+                (
+                    "Intercessor Sergeant",
+                    MiniatureType::Character,
+                    ProgressStatus::Completed,
+                    Some(0),
+                ),
+                (
+                    "Intercessor",
+                    MiniatureType::Troop,
+                    ProgressStatus::Detailed,
+                    Some(0),
+                ),
+                (
+                    "Intercessor",
+                    MiniatureType::Troop,
+                    ProgressStatus::Basecoated,
+                    Some(0),
+                ),
+                (
+                    "Redemptor Dreadnought",
+                    MiniatureType::Vehicle,
+                    ProgressStatus::Primed,
+                    Some(2),
+                ),
+            ],
+        ),
+        (
+            CreateProjectRequest {
+                name: "Stormcast Vanguard".to_string(),
+                game_system: GameSystem::AgeOfSigmar,
+                army: "Stormcast Eternals".to_string(),
+                description: Some("Demo project: a Spearhead force".to_string()),
+                tags: vec!["demo".to_string()],
+            },
+            vec![
+                (
+                    "Lord-Vigilant",
+                    MiniatureType::Character,
+                    ProgressStatus::Varnished,
+                    Some(1),
+                ),
+                (
+                    "Vindictor",
+                    MiniatureType::Troop,
+                    ProgressStatus::Unpainted,
+                    None,
+                ),
+                (
+                    "Gryph-hound",
+                    MiniatureType::Monster,
+                    ProgressStatus::Unpainted,
+                    None,
+                ),
+            ],
+        ),
+    ]
+}
@@ -1,18 +1,50 @@
 use axum::{
+    extract::{
+        path::ErrorKind,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use chrono::Utc;
+use serde::Serialize;
 use shared_types::{ErrorDetails, ErrorResponse};
 use std::fmt;
+use utoipa::ToSchema;
+
+/// One invalid field in a request, reported in the error `details`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum AppError {
     DatabaseError(sqlx::Error),
     ValidationError(String),
+    /// Every invalid field in a request, so clients can flag them all at once
+    FieldValidation(Vec<FieldError>),
     NotFound(String),
+    /// The path exists but doesn't accept the request's method
+    MethodNotAllowed(String),
+    /// Missing or wrong credentials for an endpoint that needs them
+    Unauthorized(String),
+    /// A request body over the configured size limit
+    PayloadTooLarge(String),
     Conflict(String),
+    /// A conflict with machine-readable specifics for the error `details`
+    ConflictWithDetails(String, serde_json::Value),
     InternalServerError(String),
 }
 
@@ -21,8 +53,16 @@ impl fmt::Display for AppError {
         match self {
             AppError::DatabaseError(err) => write!(f, "Database error: {}", err),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::FieldValidation(errors) => {
+                write!(f, "Validation error: {}", field_error_summary(errors))
+            }
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
-            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::MethodNotAllowed(msg) => write!(f, "Method not allowed: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::PayloadTooLarge(msg) => write!(f, "Payload too large: {}", msg),
+            AppError::Conflict(msg) | AppError::ConflictWithDetails(msg, _) => {
+                write!(f, "Conflict: {}", msg)
+            }
             AppError::InternalServerError(msg) => write!(f, "Internal server error: {}", msg),
         }
     }
@@ -30,15 +70,87 @@ impl fmt::Display for AppError {
 
 impl std::error::Error for AppError {}
 
+/// The field messages joined into one sentence for clients that only read `message`
+fn field_error_summary(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         match err {
             sqlx::Error::RowNotFound => AppError::NotFound("Resource not found".to_string()),
+            sqlx::Error::InvalidArgument(msg) => AppError::ValidationError(msg),
             _ => AppError::DatabaseError(err),
         }
     }
 }
 
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::ValidationError(rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            AppError::PayloadTooLarge(rejection.body_text())
+        } else {
+            AppError::ValidationError(rejection.body_text())
+        }
+    }
+}
+
+impl From<axum_extra::extract::QueryRejection> for AppError {
+    fn from(rejection: axum_extra::extract::QueryRejection) -> Self {
+        AppError::ValidationError(format!("Failed to deserialize query string: {}", rejection))
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        let PathRejection::FailedToDeserializePathParams(error) = &rejection else {
+            // Only a route registered without the parameters its handler reads gets here
+            return AppError::InternalServerError(rejection.body_text());
+        };
+
+        let expected = |expected_type: &str| match expected_type {
+            "i64" | "i32" | "u64" | "u32" => "integer".to_string(),
+            other => other.to_string(),
+        };
+        match error.kind() {
+            ErrorKind::ParseError {
+                value,
+                expected_type,
+            }
+            | ErrorKind::ParseErrorAtIndex {
+                value,
+                expected_type,
+                ..
+            } => AppError::ValidationError(format!(
+                "Invalid id {:?}: expected {}",
+                value,
+                expected(expected_type)
+            )),
+            ErrorKind::ParseErrorAtKey {
+                key,
+                value,
+                expected_type,
+            } => AppError::ValidationError(format!(
+                "Invalid {} {:?}: expected {}",
+                key,
+                value,
+                expected(expected_type)
+            )),
+            _ => AppError::ValidationError(rejection.body_text()),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, error_type, message, details) = match self {
@@ -48,8 +160,38 @@ impl IntoResponse for AppError {
                 msg,
                 None,
             ),
+            AppError::FieldValidation(errors) => (
+                StatusCode::BAD_REQUEST,
+                "validation_error".to_string(),
+                field_error_summary(&errors),
+                serde_json::to_value(&errors).ok(),
+            ),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found".to_string(), msg, None),
+            AppError::MethodNotAllowed(msg) => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method_not_allowed".to_string(),
+                msg,
+                None,
+            ),
+            AppError::Unauthorized(msg) => (
+                StatusCode::UNAUTHORIZED,
+                "unauthorized".to_string(),
+                msg,
+                None,
+            ),
+            AppError::PayloadTooLarge(msg) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large".to_string(),
+                msg,
+                None,
+            ),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, "conflict".to_string(), msg, None),
+            AppError::ConflictWithDetails(msg, details) => (
+                StatusCode::CONFLICT,
+                "conflict".to_string(),
+                msg,
+                Some(details),
+            ),
             AppError::DatabaseError(err) => {
                 tracing::error!("Database error: {}", err);
                 (
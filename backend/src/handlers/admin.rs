@@ -0,0 +1,168 @@
+use crate::{
+    config::Config,
+    database::Database,
+    error::{AppError, Result},
+    repositories::PhotoRepository,
+    services::storage_service::{SharedStorage, StorageService},
+};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::Json,
+};
+use serde_json::Value;
+use shared_types::ErrorResponse;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tracing::{instrument, Level};
+
+/// Delete stored photo files that no photo record points to, such as those left behind by
+/// failed uploads or rows removed by hand
+#[utoipa::path(
+    post,
+    path = "/api/admin/storage/prune",
+    tag = "admin",
+    params(("Authorization" = String, Header, description = "`Bearer ` followed by the configured ADMIN_TOKEN")),
+    responses(
+        (status = 200, description = "Orphaned files removed", body = crate::openapi::StoragePrune),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 404, description = "Admin endpoints are disabled because no ADMIN_TOKEN is set", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn prune_storage(
+    State(database): State<Database>,
+    State(config): State<Arc<Config>>,
+    State(storage): State<SharedStorage>,
+    headers: HeaderMap,
+) -> Result<Json<Value>> {
+    check_admin_token(config.admin_token.as_deref(), &headers)?;
+
+    let storage_service = storage.get().await.map_err(|e| {
+        AppError::InternalServerError(format!("Storage initialization error: {}", e))
+    })?;
+
+    let deleted = prune_orphaned_files(&database, storage_service).await?;
+    tracing::info!(deleted, "Pruned orphaned storage files");
+
+    Ok(Json(serde_json::json!({ "deleted": deleted })))
+}
+
+/// Report what storage actually holds: total bytes and file count, overall and for each
+/// miniature's directory. Sizes come from the backend, not the recorded `file_size`.
+#[utoipa::path(
+    get,
+    path = "/api/admin/storage/usage",
+    tag = "admin",
+    params(("Authorization" = String, Header, description = "`Bearer ` followed by the configured ADMIN_TOKEN")),
+    responses(
+        (status = 200, description = "Stored bytes and files", body = crate::openapi::StorageUsage),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 404, description = "Admin endpoints are disabled because no ADMIN_TOKEN is set", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn get_storage_usage(
+    State(config): State<Arc<Config>>,
+    State(storage): State<SharedStorage>,
+    headers: HeaderMap,
+) -> Result<Json<Value>> {
+    check_admin_token(config.admin_token.as_deref(), &headers)?;
+
+    let storage_service = storage.get().await.map_err(|e| {
+        AppError::InternalServerError(format!("Storage initialization error: {}", e))
+    })?;
+
+    Ok(Json(storage_usage(storage_service).await?))
+}
+
+/// Totals for every stored photo and thumbnail, grouped by miniature directory in id order
+pub async fn storage_usage(storage_service: &StorageService) -> Result<Value> {
+    let files = storage_service
+        .list_photo_files()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list storage: {}", e)))?;
+
+    // Date-bucketed layouts can spread a miniature over several directories; each gets an entry
+    let mut by_miniature: BTreeMap<(i64, String), (usize, u64)> = BTreeMap::new();
+    for (file, size) in &files {
+        if let Some(directory) = StorageService::miniature_directory(file) {
+            let (count, bytes) = by_miniature.entry(directory).or_default();
+            *count += 1;
+            *bytes += size;
+        }
+    }
+
+    let miniatures: Vec<Value> = by_miniature
+        .into_iter()
+        .map(|((miniature_id, prefix), (file_count, total_bytes))| {
+            serde_json::json!({
+                "miniature_id": miniature_id,
+                "prefix": prefix,
+                "file_count": file_count,
+                "total_bytes": total_bytes
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "file_count": files.len(),
+        "total_bytes": files.iter().map(|(_, size)| size).sum::<u64>(),
+        "miniatures": miniatures
+    }))
+}
+
+/// Delete every stored photo or thumbnail without a photo record, returning how many went.
+/// Files are listed before records are read, but an upload that stores its file before the
+/// listing and its record after the read still loses the file, so prune while uploads are
+/// quiet. Files that fail to delete are logged and skipped.
+pub async fn prune_orphaned_files(
+    database: &Database,
+    storage_service: &StorageService,
+) -> Result<u64> {
+    let files = storage_service
+        .list_photo_files()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list storage: {}", e)))?;
+    let referenced: HashSet<String> = PhotoRepository::stored_paths(database)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut deleted = 0;
+    for (file, _size) in files.iter().filter(|(file, _)| !referenced.contains(file)) {
+        match storage_service.delete_photo(file).await {
+            Ok(()) => deleted += 1,
+            Err(e) => tracing::warn!("Failed to delete orphaned file {}: {}", file, e),
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Require `Authorization: Bearer <token>` matching the configured admin token. Without one
+/// configured the admin endpoints answer as if they didn't exist.
+pub fn check_admin_token(expected: Option<&str>, headers: &HeaderMap) -> Result<()> {
+    let Some(expected) = expected else {
+        return Err(AppError::NotFound(
+            "Admin endpoints are disabled; set ADMIN_TOKEN to enable them".to_string(),
+        ));
+    };
+
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match presented {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(()),
+        _ => Err(AppError::Unauthorized(
+            "A valid admin token is required".to_string(),
+        )),
+    }
+}
+
+/// Compare without stopping at the first difference, so timing doesn't reveal the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
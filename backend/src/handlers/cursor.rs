@@ -0,0 +1,36 @@
+use crate::error::{AppError, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Position in a list ordered by creation time, then id: the last row a client has seen.
+/// Clients get it as an opaque, URL-safe string and send it back as `after`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: i64,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}|{}",
+            self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.id
+        ))
+    }
+
+    /// Read back a cursor from `encode`; anything else is a validation error
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || AppError::ValidationError(format!("Invalid cursor {:?}", cursor));
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (created_at, id) = text.rsplit_once('|').ok_or_else(invalid)?;
+
+        Ok(Cursor {
+            created_at: DateTime::parse_from_rfc3339(created_at)
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
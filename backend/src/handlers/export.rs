@@ -0,0 +1,394 @@
+use crate::{
+    config::Config,
+    database::Database,
+    error::{AppError, Result},
+    handlers::{
+        projects::{check_unique_name, name_conflict},
+        validation::{step_errors, too_long, MAX_DESCRIPTION_CHARS, MAX_NOTES_CHARS},
+        Path,
+    },
+    repositories::{
+        MiniatureRecipeRepository, MiniatureRepository, PhotoRepository, ProjectRepository,
+        RecipeRepository,
+    },
+};
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use shared_types::{
+    ErrorResponse, GameSystem, MiniatureExport, MiniatureType, ProgressStatus, Project,
+    ProjectExport, ProjectImportSummary, PROJECT_EXPORT_VERSION,
+};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use tracing::{instrument, Level};
+
+/// Export a project with its miniatures, recipe links, and the recipes used
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/export",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The project export document", body = ProjectExport),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn export_project(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<ProjectExport>> {
+    let project = ProjectRepository::find_by_id(&database, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    let mut miniatures = Vec::new();
+    let mut recipe_ids = BTreeSet::new();
+    for miniature in MiniatureRepository::find_by_project_id(&database, project_id).await? {
+        let linked =
+            MiniatureRecipeRepository::get_recipe_ids_for_miniature(&database, miniature.id)
+                .await?;
+        recipe_ids.extend(linked.iter().copied());
+
+        // Photos are referenced by metadata only to keep exports small
+        let photos = PhotoRepository::find_by_miniature_id(&database, miniature.id, false).await?;
+
+        miniatures.push(MiniatureExport {
+            miniature,
+            recipe_ids: linked,
+            photos,
+        });
+    }
+
+    let mut recipes = Vec::new();
+    for recipe_id in recipe_ids {
+        if let Some(recipe) = RecipeRepository::find_by_id(&database, recipe_id).await? {
+            recipes.push(recipe);
+        }
+    }
+
+    Ok(Json(ProjectExport {
+        version: PROJECT_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        project,
+        miniatures,
+        recipes,
+    }))
+}
+
+/// Import a project from an export document, assigning new ids throughout. Documents in a
+/// format version this server doesn't know are refused.
+#[utoipa::path(
+    post,
+    path = "/api/projects/import",
+    tag = "projects",
+    request_body = ProjectExport,
+    responses(
+        (status = 200, description = "What was imported", body = ProjectImportSummary),
+        (status = 400, description = "Invalid export document", body = ErrorResponse),
+        (status = 409, description = "With ENFORCE_UNIQUE_PROJECT_NAMES on, a live project already has this name, game system, and army", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn import_project(
+    State(database): State<Database>,
+    State(config): State<Arc<Config>>,
+    body: std::result::Result<Json<Value>, JsonRejection>,
+) -> Result<Json<ProjectImportSummary>> {
+    let Json(document) = body?;
+    let export = read_export(document)?;
+    let problems = validate_export(&export);
+    if !problems.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Invalid project export: {}",
+            problems.join("; ")
+        )));
+    }
+
+    let project = &export.project;
+    if config.enforce_unique_project_names {
+        check_unique_name(
+            &database,
+            &project.name,
+            &project.game_system,
+            &project.army,
+        )
+        .await?;
+    }
+    let summary = match ProjectRepository::import(&database, &export).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            return Err(name_conflict(
+                &database,
+                e,
+                &project.name,
+                &project.game_system,
+                &project.army,
+            )
+            .await)
+        }
+    };
+    tracing::info!(project_id = summary.project_id, "Project imported");
+    Ok(Json(summary))
+}
+
+/// Parse an export document of any supported version. The version is checked before the
+/// rest of the document, so a file from a newer server is refused by its version rather than
+/// by whichever field changed.
+fn read_export(document: Value) -> Result<ProjectExport> {
+    let version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| {
+            AppError::ValidationError(
+                "Invalid project export: version is required and must be a positive integer"
+                    .to_string(),
+            )
+        })?;
+    if version == 0 || version > u64::from(PROJECT_EXPORT_VERSION) {
+        return Err(AppError::ValidationError(format!(
+            "Invalid project export: unsupported export version {}; this server reads versions 1 to {}",
+            version, PROJECT_EXPORT_VERSION
+        )));
+    }
+
+    // Version 1 is the first format. When it changes, bump `PROJECT_EXPORT_VERSION` and
+    // rewrite older documents here, one version at a time, before they are parsed.
+    serde_json::from_value(document)
+        .map_err(|e| AppError::ValidationError(format!("Invalid project export: {}", e)))
+}
+
+/// Collect every problem with an export document rather than stopping at the first
+fn validate_export(export: &ProjectExport) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if export.project.name.trim().is_empty() {
+        problems.push("project.name is required".to_string());
+    }
+    if export.project.army.trim().is_empty() {
+        problems.push("project.army is required".to_string());
+    }
+    problems.extend(
+        too_long(
+            "project.description",
+            export.project.description.as_deref(),
+            MAX_DESCRIPTION_CHARS,
+        )
+        .map(|error| error.message),
+    );
+
+    let mut recipe_ids = HashSet::new();
+    for (index, recipe) in export.recipes.iter().enumerate() {
+        if recipe.name.trim().is_empty() {
+            problems.push(format!("recipes[{}].name is required", index));
+        }
+        if !recipe_ids.insert(recipe.id) {
+            problems.push(format!("recipes[{}].id {} is duplicated", index, recipe.id));
+        }
+        problems.extend(
+            step_errors(&format!("recipes[{}].steps", index), &recipe.steps)
+                .into_iter()
+                .chain(too_long(
+                    &format!("recipes[{}].notes", index),
+                    recipe.notes.as_deref(),
+                    MAX_NOTES_CHARS,
+                ))
+                .map(|error| error.message),
+        );
+    }
+
+    for (index, entry) in export.miniatures.iter().enumerate() {
+        if entry.miniature.name.trim().is_empty() {
+            problems.push(format!("miniatures[{}].name is required", index));
+        }
+        problems.extend(
+            too_long(
+                &format!("miniatures[{}].notes", index),
+                entry.miniature.notes.as_deref(),
+                MAX_NOTES_CHARS,
+            )
+            .map(|error| error.message),
+        );
+        if entry
+            .miniature
+            .points_value
+            .is_some_and(|points| points < 0)
+        {
+            problems.push(format!(
+                "miniatures[{}].points_value cannot be negative",
+                index
+            ));
+        }
+        for recipe_id in &entry.recipe_ids {
+            if !recipe_ids.contains(recipe_id) {
+                problems.push(format!(
+                    "miniatures[{}].recipe_ids references recipe {} which is not in recipes",
+                    index, recipe_id
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// One line of the miniatures CSV; column order follows field order
+#[derive(Serialize)]
+struct MiniatureCsvRow<'a> {
+    id: i64,
+    name: &'a str,
+    miniature_type: &'a MiniatureType,
+    progress_status: &'a ProgressStatus,
+    notes: Option<&'a str>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Export a project's miniatures as an RFC 4180 CSV attachment
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/miniatures.csv",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The project's miniatures as a CSV attachment", body = String, content_type = "text/csv"),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn export_miniatures_csv(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+) -> Result<Response> {
+    let project = ProjectRepository::find_by_id(&database, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    let miniatures = MiniatureRepository::find_by_project_id(&database, project_id).await?;
+
+    // The csv writer quotes fields containing commas, quotes, or newlines
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for miniature in &miniatures {
+        writer
+            .serialize(MiniatureCsvRow {
+                id: miniature.id,
+                name: &miniature.name,
+                miniature_type: &miniature.miniature_type,
+                progress_status: &miniature.progress_status,
+                notes: miniature.notes.as_deref(),
+                created_at: miniature.created_at,
+                updated_at: miniature.updated_at,
+            })
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+    }
+    if miniatures.is_empty() {
+        writer
+            .write_record([
+                "id",
+                "name",
+                "miniature_type",
+                "progress_status",
+                "notes",
+                "created_at",
+                "updated_at",
+            ])
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+    }
+    let body = writer
+        .into_inner()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}-miniatures.csv\"",
+        csv_file_stem(&project.name)
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// One line of the projects CSV; column order follows field order
+#[derive(Serialize)]
+struct ProjectCsvRow<'a> {
+    id: i64,
+    name: &'a str,
+    game_system: &'a GameSystem,
+    army: &'a str,
+    description: Option<&'a str>,
+    /// Tags joined with `;`
+    tags: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    archived: bool,
+}
+
+/// Render projects as RFC 4180 CSV with a header line, even when there are none
+pub fn projects_to_csv(projects: &[Project]) -> Result<Vec<u8>> {
+    // The csv writer quotes fields containing commas, quotes, or newlines
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for project in projects {
+        writer
+            .serialize(ProjectCsvRow {
+                id: project.id,
+                name: &project.name,
+                game_system: &project.game_system,
+                army: &project.army,
+                description: project.description.as_deref(),
+                tags: project.tags.join(";"),
+                created_at: project.created_at,
+                updated_at: project.updated_at,
+                deleted_at: project.deleted_at,
+                archived: project.archived,
+            })
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+    }
+    if projects.is_empty() {
+        writer
+            .write_record([
+                "id",
+                "name",
+                "game_system",
+                "army",
+                "description",
+                "tags",
+                "created_at",
+                "updated_at",
+                "deleted_at",
+                "archived",
+            ])
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))
+}
+
+/// Reduce a project name to a safe, lowercase file name stem
+fn csv_file_stem(name: &str) -> String {
+    let stem = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_ascii_lowercase();
+
+    if stem.is_empty() {
+        "project".to_string()
+    } else {
+        stem
+    }
+}
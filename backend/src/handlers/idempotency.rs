@@ -0,0 +1,90 @@
+use crate::{
+    database::Transaction,
+    error::{AppError, Result},
+    repositories::IdempotencyKeyRepository,
+};
+use axum::http::HeaderMap;
+
+/// Header a client sets on a creation request it may retry
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// How long a key keeps answering with the resource it created: a day
+pub const IDEMPOTENCY_KEY_TTL_SECONDS: i64 = 24 * 60 * 60;
+/// Longest key accepted, in characters; UUIDs and similar tokens fit easily
+const MAX_KEY_CHARS: usize = 255;
+
+/// The request's `Idempotency-Key`, if it sent one
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| invalid_key("must be visible ASCII"))?;
+    if key.is_empty() {
+        return Err(invalid_key("cannot be blank"));
+    }
+    if key.chars().count() > MAX_KEY_CHARS {
+        return Err(invalid_key(&format!(
+            "must be at most {} characters",
+            MAX_KEY_CHARS
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
+
+fn invalid_key(problem: &str) -> AppError {
+    AppError::ValidationError(format!("Idempotency-Key {}", problem))
+}
+
+/// The id of the resource an earlier request with `key` created in `scope`, the route it was
+/// sent to, if that was within the last day
+pub async fn original_id(
+    transaction: &mut Transaction,
+    scope: &str,
+    key: Option<&str>,
+) -> Result<Option<i64>> {
+    let Some(key) = key else {
+        return Ok(None);
+    };
+    Ok(IdempotencyKeyRepository::find_resource_id(
+        transaction,
+        scope,
+        key,
+        IDEMPOTENCY_KEY_TTL_SECONDS,
+    )
+    .await?)
+}
+
+/// Remember that `key` created `resource_id`, clearing out expired keys first. A request
+/// that raced another with the same key is refused so only one of them creates anything.
+pub async fn remember(
+    transaction: &mut Transaction,
+    scope: &str,
+    key: Option<&str>,
+    resource_id: i64,
+) -> Result<()> {
+    let Some(key) = key else {
+        return Ok(());
+    };
+    IdempotencyKeyRepository::delete_expired(&mut *transaction, IDEMPOTENCY_KEY_TTL_SECONDS)
+        .await?;
+    match IdempotencyKeyRepository::record(transaction, scope, key, resource_id).await {
+        Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+            Err(AppError::Conflict(format!(
+                "Another request with Idempotency-Key {:?} is already in progress",
+                key
+            )))
+        }
+        result => Ok(result?),
+    }
+}
+
+/// The error for a key whose resource has been deleted since; creating it again would
+/// defeat the key, so the client has to choose a new one
+pub fn original_gone(key: &str, resource: &str, id: i64) -> AppError {
+    AppError::Conflict(format!(
+        "Idempotency-Key {:?} already created {} {}, which no longer exists",
+        key, resource, id
+    ))
+}
@@ -1,20 +1,44 @@
 use crate::{
     database::Database,
     error::{AppError, Result},
-    repositories::{
-        miniature_recipe_repository::MiniatureRecipeRepository,
-        miniature_repository::MiniatureRepository,
-        recipe_repository::RecipeRepository,
-    },
+    handlers::Path,
+    repositories::{MiniatureRecipeRepository, MiniatureRepository, RecipeRepository},
 };
 use axum::{
-    extract::{Path, State},
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Query, State,
+    },
     http::StatusCode,
     response::Json,
 };
+use serde::Deserialize;
 use serde_json::Value;
+use shared_types::{BulkLinkRecipeRequest, ErrorResponse, Miniature};
+use std::collections::HashMap;
+use tracing::{instrument, Level};
+use utoipa::IntoParams;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LinkQueryParams {
+    /// Link even when the recipe is meant for a different miniature type
+    #[serde(default)]
+    pub force: bool,
+}
 
 /// Get all recipes linked to a miniature
+#[utoipa::path(
+    get,
+    path = "/api/miniatures/{id}/recipes",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id")),
+    responses(
+        (status = 200, description = "Recipes linked to the miniature, with when each was linked", body = crate::openapi::LinkedRecipeList),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id), err(level = Level::INFO))]
 pub async fn get_miniature_recipes(
     State(database): State<Database>,
     Path(miniature_id): Path<i64>,
@@ -22,36 +46,163 @@ pub async fn get_miniature_recipes(
     // Verify miniature exists
     MiniatureRepository::find_by_id(&database, miniature_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", miniature_id)))?;
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Miniature with id {} not found", miniature_id))
+        })?;
 
-    let recipes = MiniatureRecipeRepository::find_recipes_for_miniature(&database, miniature_id).await?;
+    let recipes =
+        MiniatureRecipeRepository::find_recipes_for_miniature(&database, miniature_id).await?;
 
     Ok(Json(serde_json::json!({
+        "count": recipes.len(),
         "recipes": recipes
     })))
 }
 
 /// Link a recipe to a miniature
+#[utoipa::path(
+    post,
+    path = "/api/miniatures/{id}/recipes/{recipe_id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id"), ("recipe_id" = i64, Path, description = "Recipe id"), LinkQueryParams),
+    responses(
+        (status = 201, description = "Recipe linked; linking again is a no-op"),
+        (status = 400, description = "Recipe is for a different miniature type", body = ErrorResponse),
+        (status = 404, description = "Miniature or recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id, recipe_id = recipe_id), err(level = Level::INFO))]
 pub async fn link_recipe_to_miniature(
     State(database): State<Database>,
     Path((miniature_id, recipe_id)): Path<(i64, i64)>,
+    params: std::result::Result<Query<LinkQueryParams>, QueryRejection>,
 ) -> Result<StatusCode> {
+    let Query(params) = params?;
+
     // Verify miniature exists
-    MiniatureRepository::find_by_id(&database, miniature_id)
+    let miniature = MiniatureRepository::find_by_id(&database, miniature_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", miniature_id)))?;
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Miniature with id {} not found", miniature_id))
+        })?;
 
     // Verify recipe exists
-    RecipeRepository::find_by_id(&database, recipe_id)
+    let recipe = RecipeRepository::find_by_id(&database, recipe_id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", recipe_id)))?;
 
+    // A recipe for a different kind of model is almost always a mistake
+    if recipe.miniature_type != miniature.miniature_type && !params.force {
+        return Err(AppError::ValidationError(format!(
+            "Recipe {} is for {:?} miniatures but miniature {} is a {:?}; pass force=true to link anyway",
+            recipe_id, recipe.miniature_type, miniature_id, miniature.miniature_type
+        )));
+    }
+
     MiniatureRecipeRepository::link(&database, miniature_id, recipe_id).await?;
 
     Ok(StatusCode::CREATED)
 }
 
+/// Link a recipe to several miniatures at once. Each id is reported on separately: missing
+/// miniatures and type mismatches are errors that don't stop the others from linking.
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/link",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id"), LinkQueryParams),
+    request_body = BulkLinkRecipeRequest,
+    responses(
+        (status = 200, description = "What happened to each miniature", body = crate::openapi::BulkLinkResult),
+        (status = 400, description = "No miniature ids", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = recipe_id), err(level = Level::INFO))]
+pub async fn bulk_link_recipe(
+    State(database): State<Database>,
+    Path(recipe_id): Path<i64>,
+    params: std::result::Result<Query<LinkQueryParams>, QueryRejection>,
+    body: std::result::Result<Json<BulkLinkRecipeRequest>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Query(params) = params?;
+    let Json(request) = body?;
+    if request.miniature_ids.is_empty() {
+        return Err(AppError::ValidationError(
+            "At least one miniature id is required".to_string(),
+        ));
+    }
+
+    // Check and link together so nothing checked can be deleted before it is linked
+    let mut transaction = database.begin().await?;
+    let recipe = RecipeRepository::find_by_id(&mut transaction, recipe_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", recipe_id)))?;
+    let miniatures: HashMap<i64, Miniature> =
+        MiniatureRepository::find_by_ids(&mut transaction, &request.miniature_ids)
+            .await?
+            .into_iter()
+            .map(|miniature| (miniature.id, miniature))
+            .collect();
+
+    let mut linked = 0;
+    let mut results = Vec::with_capacity(request.miniature_ids.len());
+    for miniature_id in request.miniature_ids {
+        let (status, message) = match miniatures.get(&miniature_id) {
+            None => (
+                "error",
+                Some(format!("Miniature with id {} not found", miniature_id)),
+            ),
+            Some(miniature)
+                if miniature.miniature_type != recipe.miniature_type && !params.force =>
+            {
+                (
+                    "error",
+                    Some(format!(
+                        "Recipe {} is for {:?} miniatures but miniature {} is a {:?}; pass force=true to link anyway",
+                        recipe_id, recipe.miniature_type, miniature_id, miniature.miniature_type
+                    )),
+                )
+            }
+            Some(_) => {
+                if MiniatureRecipeRepository::link(&mut transaction, miniature_id, recipe_id)
+                    .await?
+                {
+                    linked += 1;
+                    ("linked", None)
+                } else {
+                    ("skipped", Some("Already linked".to_string()))
+                }
+            }
+        };
+        results.push(serde_json::json!({
+            "miniature_id": miniature_id,
+            "status": status,
+            "message": message
+        }));
+    }
+    transaction.commit().await?;
+
+    tracing::info!(recipe_id, linked, "Recipe linked to miniatures");
+    Ok(Json(serde_json::json!({
+        "recipe_id": recipe_id,
+        "linked": linked,
+        "results": results
+    })))
+}
+
 /// Unlink a recipe from a miniature
+#[utoipa::path(
+    delete,
+    path = "/api/miniatures/{id}/recipes/{recipe_id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id"), ("recipe_id" = i64, Path, description = "Recipe id")),
+    responses(
+        (status = 204, description = "Recipe unlinked"),
+        (status = 404, description = "Recipe not linked to the miniature", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id, recipe_id = recipe_id), err(level = Level::INFO))]
 pub async fn unlink_recipe_from_miniature(
     State(database): State<Database>,
     Path((miniature_id, recipe_id)): Path<(i64, i64)>,
@@ -69,6 +220,17 @@ pub async fn unlink_recipe_from_miniature(
 }
 
 /// Get count of miniatures using a recipe
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/usage",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id")),
+    responses(
+        (status = 200, description = "How many miniatures use the recipe", body = crate::openapi::RecipeUsage),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = recipe_id), err(level = Level::INFO))]
 pub async fn get_recipe_usage_count(
     State(database): State<Database>,
     Path(recipe_id): Path<i64>,
@@ -78,10 +240,41 @@ pub async fn get_recipe_usage_count(
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", recipe_id)))?;
 
-    let count = MiniatureRecipeRepository::count_miniatures_for_recipe(&database, recipe_id).await?;
+    let count =
+        MiniatureRecipeRepository::count_miniatures_for_recipe(&database, recipe_id).await?;
 
     Ok(Json(serde_json::json!({
         "recipe_id": recipe_id,
         "miniature_count": count
     })))
 }
+
+/// List the projects whose miniatures use a recipe, with how many miniatures each
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/projects",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id")),
+    responses(
+        (status = 200, description = "Projects using the recipe, by name; deleted projects are left out", body = crate::openapi::RecipeProjectList),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = recipe_id), err(level = Level::INFO))]
+pub async fn get_recipe_projects(
+    State(database): State<Database>,
+    Path(recipe_id): Path<i64>,
+) -> Result<Json<Value>> {
+    RecipeRepository::find_by_id(&database, recipe_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", recipe_id)))?;
+
+    let projects =
+        MiniatureRecipeRepository::find_projects_for_recipe(&database, recipe_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "recipe_id": recipe_id,
+        "count": projects.len(),
+        "projects": projects
+    })))
+}
@@ -1,76 +1,704 @@
 use crate::{
-    database::Database,
-    error::{AppError, Result},
+    config::Config,
+    database::{Database, Transaction},
+    error::{AppError, FieldError, Result},
+    handlers::{
+        conditional, created,
+        cursor::Cursor,
+        idempotency::{idempotency_key, original_gone, original_id, remember},
+        page_bounds,
+        validation::{collapse_whitespace, too_long, trim_ends, MAX_NOTES_CHARS},
+        Created, Path,
+    },
     repositories::{
-        miniature_repository::MiniatureRepository, project_repository::ProjectRepository,
+        miniature_repository::{MiniatureRepository, MiniatureSearch, MiniatureSortField},
+        project_repository::ProjectRepository,
+        MiniatureRecipeRepository, PhotoRepository, ProgressHistoryRepository,
+    },
+    services::{
+        storage_service::{SharedStorage, StorageService},
+        webhook_service::{self, MiniatureCompleted},
     },
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{rejection::JsonRejection, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use axum_extra::extract::{Query, QueryRejection};
+use serde::Deserialize;
 use serde_json::Value;
-use shared_types::{CreateMiniatureRequest, Miniature, UpdateMiniatureRequest};
+use shared_types::{
+    BulkCreateMiniaturesRequest, BulkDeleteMiniaturesRequest, BulkUpdateStatusRequest,
+    CreateMiniatureRequest, ErrorResponse, Miniature, MiniatureDetail, MoveMiniatureRequest,
+    PatchMiniatureRequest, ProgressStatus, UpdateMiniatureRequest,
+};
+use std::sync::Arc;
+use tracing::{instrument, Level};
+use utoipa::{IntoParams, ToSchema};
+
+/// Most miniatures one batch may create; a large army still fits in a few requests
+const MAX_BULK_CREATE: usize = 100;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MiniatureQueryParams {
+    /// Repeatable status filter, e.g. `?status=unpainted&status=primed`
+    #[serde(default)]
+    pub status: Vec<ProgressStatus>,
+    /// Extra data to add to each miniature
+    pub include: Option<MiniatureInclude>,
+    /// Order of the list; defaults to `created_at`, oldest first
+    pub sort: Option<MiniatureSortField>,
+    /// Page size; with this or `after`, the list is paged in creation order and carries a
+    /// `next_cursor` while more remain
+    pub limit: Option<i64>,
+    /// The `next_cursor` of the previous page, to continue after it
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MiniatureUpdateParams {
+    /// Skip the `STRICT_PROGRESS_TRANSITIONS` check, e.g. to record work done off-app
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MiniatureDetailParams {
+    /// Comma-separated nested data to add: `photos`, `recipes`, or both
+    pub expand: Option<String>,
+}
+
+/// Nested data a single miniature can be expanded with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MiniatureExpand {
+    Photos,
+    Recipes,
+}
+
+impl MiniatureExpand {
+    /// Parse an `expand` list such as `photos,recipes`; blank entries are ignored
+    fn parse_list(expand: Option<&str>) -> Result<Vec<Self>> {
+        expand
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| match part {
+                "photos" => Ok(MiniatureExpand::Photos),
+                "recipes" => Ok(MiniatureExpand::Recipes),
+                other => Err(AppError::ValidationError(format!(
+                    "Unknown expand value {:?}; expected photos or recipes",
+                    other
+                ))),
+            })
+            .collect()
+    }
+}
+
+/// Extras a miniature list can carry
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MiniatureInclude {
+    /// The miniature's number of photos, as `photo_count`
+    PhotoCount,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MiniatureSearchParams {
+    /// Case-insensitive search within miniature names
+    pub q: Option<String>,
+    /// Repeatable status filter, e.g. `?status=unpainted&status=primed`
+    #[serde(default)]
+    pub status: Vec<ProgressStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Search miniatures across every project; each result carries its `project_id`
+#[utoipa::path(
+    get,
+    path = "/api/miniatures",
+    tag = "miniatures",
+    params(MiniatureSearchParams),
+    responses(
+        (status = 200, description = "A page of matching miniatures", body = crate::openapi::MiniaturePage),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn search_miniatures(
+    State(database): State<Database>,
+    params: std::result::Result<Query<MiniatureSearchParams>, QueryRejection>,
+) -> Result<Json<Value>> {
+    let Query(params) = params?;
+    let (limit, offset) = page_bounds(params.limit, params.offset)?;
+
+    let search = MiniatureSearch {
+        // A blank search is the same as no search
+        query: params.q.filter(|q| !q.trim().is_empty()),
+        statuses: params.status,
+    };
 
+    let miniatures = MiniatureRepository::search(&database, &search, limit, offset).await?;
+    let total = MiniatureRepository::count_search(&database, &search).await?;
+
+    Ok(Json(serde_json::json!({
+        "count": miniatures.len(),
+        "miniatures": miniatures,
+        "total": total,
+        "limit": limit,
+        "offset": offset
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/miniatures",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id"), MiniatureQueryParams),
+    responses(
+        (status = 200, description = "The project's miniatures; with `include=photo_count` each is a `MiniatureWithCounts`", body = crate::openapi::MiniatureList),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
 pub async fn list_miniatures(
     State(database): State<Database>,
     Path(project_id): Path<i64>,
+    params: std::result::Result<Query<MiniatureQueryParams>, QueryRejection>,
 ) -> Result<Json<Value>> {
+    let Query(params) = params?;
+
     // Verify project exists
     ProjectRepository::find_by_id(&database, project_id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
 
-    let miniatures = MiniatureRepository::find_by_project_id(&database, project_id).await?;
+    if params.limit.is_some() || params.after.is_some() {
+        return miniature_page(&database, project_id, params).await;
+    }
+
+    if params.include == Some(MiniatureInclude::PhotoCount) {
+        let miniatures = MiniatureRepository::find_by_project_with_photo_counts(
+            &database,
+            project_id,
+            &params.status,
+            params.sort.unwrap_or_default(),
+        )
+        .await?;
+
+        return Ok(Json(serde_json::json!({
+            "count": miniatures.len(),
+            "miniatures": miniatures
+        })));
+    }
+
+    let miniatures = MiniatureRepository::find_by_project_id_and_status(
+        &database,
+        project_id,
+        &params.status,
+        params.sort.unwrap_or_default(),
+    )
+    .await?;
 
     Ok(Json(serde_json::json!({
+        "count": miniatures.len(),
         "miniatures": miniatures
     })))
 }
 
+/// A page of `list_miniatures` by keyset rather than offset, so miniatures added while a
+/// client scrolls don't shift the pages after the one it's on
+async fn miniature_page(
+    database: &Database,
+    project_id: i64,
+    params: MiniatureQueryParams,
+) -> Result<Json<Value>> {
+    if params.include.is_some() || params.sort.unwrap_or_default() != MiniatureSortField::CreatedAt
+    {
+        return Err(AppError::ValidationError(
+            "limit and after page by created_at and can't be combined with sort or include"
+                .to_string(),
+        ));
+    }
+    let (limit, _) = page_bounds(params.limit, None)?;
+    let after = params.after.as_deref().map(Cursor::decode).transpose()?;
+
+    // Fetch one extra row to learn whether there's another page
+    let mut miniatures = MiniatureRepository::find_page_by_project(
+        database,
+        project_id,
+        &params.status,
+        after.map(|cursor| (cursor.created_at, cursor.id)),
+        limit + 1,
+    )
+    .await?;
+    let next_cursor = if miniatures.len() as i64 > limit {
+        miniatures.truncate(limit as usize);
+        miniatures.last().map(|last| {
+            Cursor {
+                created_at: last.created_at,
+                id: last.id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    Ok(Json(serde_json::json!({
+        "count": miniatures.len(),
+        "miniatures": miniatures,
+        "next_cursor": next_cursor
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/miniatures",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id"), ("Idempotency-Key" = Option<String>, Header, description = "Retrying with the same key within a day returns the resource the first request created")),
+    request_body = CreateMiniatureRequest,
+    responses(
+        (status = 201, description = "Miniature created", body = Miniature, headers(("Location" = String, description = "URL of the new miniature"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+        (status = 409, description = "The Idempotency-Key is in use by a concurrent request, or its miniature has been deleted", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
 pub async fn create_miniature(
     State(database): State<Database>,
     Path(project_id): Path<i64>,
-    Json(request): Json<CreateMiniatureRequest>,
-) -> Result<Json<Miniature>> {
+    headers: HeaderMap,
+    body: std::result::Result<Json<CreateMiniatureRequest>, JsonRejection>,
+) -> Result<Created<Miniature>> {
+    let idempotency_key = idempotency_key(&headers)?;
+    let Json(mut request) = body?;
+
     // Verify project exists
     ProjectRepository::find_by_id(&database, project_id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
 
-    // Validate required fields
+    let errors = new_miniature_errors(&mut request);
+    if !errors.is_empty() {
+        return Err(AppError::FieldValidation(errors));
+    }
+
+    // Keys are per project, so reusing one elsewhere can't return another project's miniature
+    let scope = format!("POST /api/projects/{}/miniatures", project_id);
+    let key = idempotency_key.as_deref();
+    let mut transaction = database.begin().await?;
+    if let Some(id) = original_id(&mut transaction, &scope, key).await? {
+        let miniature = MiniatureRepository::find_by_id(&mut transaction, id)
+            .await?
+            .ok_or_else(|| original_gone(key.unwrap_or_default(), "miniature", id))?;
+        return Ok(created(format!("/api/miniatures/{}", id), miniature));
+    }
+    let miniature = MiniatureRepository::create(&mut transaction, project_id, request).await?;
+    remember(&mut transaction, &scope, key, miniature.id).await?;
+    transaction.commit().await?;
+
+    tracing::info!(miniature_id = miniature.id, "Miniature created");
+    Ok(created(
+        format!("/api/miniatures/{}", miniature.id),
+        miniature,
+    ))
+}
+
+/// Add several miniatures to a project in one transaction. Every entry is validated first;
+/// if any fails, nothing is created and the errors name the entry, e.g. `miniatures[2].name`.
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/miniatures/batch",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = BulkCreateMiniaturesRequest,
+    responses(
+        (status = 201, description = "The created miniatures, in request order", body = Vec<Miniature>),
+        (status = 400, description = "No miniatures, too many, or an invalid entry", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn bulk_create_miniatures(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+    body: std::result::Result<Json<BulkCreateMiniaturesRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<Vec<Miniature>>)> {
+    let Json(mut request) = body?;
+    if request.miniatures.is_empty() {
+        return Err(AppError::ValidationError(
+            "At least one miniature is required".to_string(),
+        ));
+    }
+    if request.miniatures.len() > MAX_BULK_CREATE {
+        return Err(AppError::ValidationError(format!(
+            "At most {} miniatures can be created at once",
+            MAX_BULK_CREATE
+        )));
+    }
+
+    let errors: Vec<FieldError> = request
+        .miniatures
+        .iter_mut()
+        .enumerate()
+        .flat_map(|(index, miniature)| {
+            new_miniature_errors(miniature)
+                .into_iter()
+                .map(move |error| FieldError {
+                    field: format!("miniatures[{}].{}", index, error.field),
+                    message: error.message,
+                })
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(AppError::FieldValidation(errors));
+    }
+
+    let mut transaction = database.begin().await?;
+    ProjectRepository::find_by_id(&mut transaction, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+    let mut miniatures = Vec::with_capacity(request.miniatures.len());
+    for miniature in request.miniatures {
+        miniatures
+            .push(MiniatureRepository::create(&mut transaction, project_id, miniature).await?);
+    }
+    transaction.commit().await?;
+
+    tracing::info!(count = miniatures.len(), "Miniatures created");
+    Ok((StatusCode::CREATED, Json(miniatures)))
+}
+
+/// Tidy a new miniature's name and notes, then return whatever is still wrong with it
+fn new_miniature_errors(request: &mut CreateMiniatureRequest) -> Vec<FieldError> {
+    collapse_whitespace(&mut request.name);
+    request.notes.iter_mut().for_each(trim_ends);
+
+    let mut errors = Vec::new();
     if request.name.trim().is_empty()
         || !request
             .name
             .chars()
             .any(|c| c.is_alphanumeric() || c.is_ascii_punctuation())
     {
+        errors.push(FieldError::new("name", "Miniature name is required"));
+    }
+    errors.extend(too_long("notes", request.notes.as_deref(), MAX_NOTES_CHARS));
+    errors.extend(points_error(request.points_value));
+    errors
+}
+
+/// An error for a negative points value; leaving the value out is fine
+fn points_error(points_value: Option<i32>) -> Option<FieldError> {
+    points_value
+        .filter(|points| *points < 0)
+        .map(|_| FieldError::new("points_value", "points_value cannot be negative"))
+}
+
+/// Storage for removing the photo files of deleted miniatures. It's set up before anything
+/// is deleted, so a misconfiguration can't leave files with no record pointing to them.
+async fn photo_storage(storage: &SharedStorage) -> Result<&StorageService> {
+    storage
+        .get()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Storage initialization error: {}", e)))
+}
+
+/// Check a bulk request's ids against an existing project, returning them sorted and
+/// deduplicated. Any id outside the project fails the whole request.
+async fn project_miniature_ids(
+    transaction: &mut Transaction,
+    project_id: i64,
+    mut ids: Vec<i64>,
+) -> Result<Vec<i64>> {
+    // Verify project exists
+    ProjectRepository::find_by_id(&mut *transaction, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    if ids.is_empty() {
         return Err(AppError::ValidationError(
-            "Miniature name is required".to_string(),
+            "At least one miniature id is required".to_string(),
         ));
     }
 
-    let miniature = MiniatureRepository::create(&database, project_id, request).await?;
-    Ok(Json(miniature))
+    ids.sort_unstable();
+    ids.dedup();
+
+    let found = MiniatureRepository::find_ids_in_project(transaction, project_id, &ids).await?;
+    let invalid_ids: Vec<i64> = ids
+        .iter()
+        .copied()
+        .filter(|id| !found.contains(id))
+        .collect();
+    if !invalid_ids.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Miniatures {:?} do not belong to project {}",
+            invalid_ids, project_id
+        )));
+    }
+
+    Ok(ids)
 }
 
+/// Set the progress status of several miniatures in a project at once.
+/// All ids must belong to the project; otherwise nothing is updated.
+#[utoipa::path(
+    patch,
+    path = "/api/projects/{id}/miniatures/status",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = BulkUpdateStatusRequest,
+    responses(
+        (status = 200, description = "How many miniatures were updated", body = crate::openapi::BulkUpdateStatusResponse),
+        (status = 400, description = "No ids, or ids outside the project", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn bulk_update_status(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+    body: std::result::Result<Json<BulkUpdateStatusRequest>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Json(request) = body?;
+    // Check and update together so no miniature can move between the two
+    let mut transaction = database.begin().await?;
+    let ids = project_miniature_ids(&mut transaction, project_id, request.miniature_ids).await?;
+
+    let updated = MiniatureRepository::bulk_update_status(
+        &mut transaction,
+        project_id,
+        &ids,
+        request.progress_status,
+    )
+    .await?;
+    transaction.commit().await?;
+
+    Ok(Json(serde_json::json!({
+        "updated": updated
+    })))
+}
+
+/// Delete several miniatures in a project at once, along with their photos and recipe links.
+/// All ids must belong to the project; otherwise nothing is deleted.
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{id}/miniatures",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = BulkDeleteMiniaturesRequest,
+    responses(
+        (status = 200, description = "How many miniatures were deleted", body = crate::openapi::BulkDeleteMiniaturesResponse),
+        (status = 400, description = "No ids, or ids outside the project", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn bulk_delete_miniatures(
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+    Path(project_id): Path<i64>,
+    body: std::result::Result<Json<BulkDeleteMiniaturesRequest>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Json(request) = body?;
+    let storage_service = photo_storage(&storage).await?;
+    let mut transaction = database.begin().await?;
+    let ids = project_miniature_ids(&mut transaction, project_id, request.miniature_ids).await?;
+
+    // The foreign keys drop the photo rows with the miniatures, so note their files first
+    let mut photos = Vec::new();
+    for &id in &ids {
+        photos.extend(PhotoRepository::find_by_miniature_id(&mut transaction, id, false).await?);
+    }
+    let deleted = MiniatureRepository::bulk_delete(&mut transaction, project_id, &ids).await?;
+    transaction.commit().await?;
+    storage_service.delete_photo_files(&photos).await;
+
+    Ok(Json(serde_json::json!({
+        "deleted": deleted
+    })))
+}
+
+/// Get a miniature by id alone, whichever project it is in. Clients that know the project
+/// should prefer `/api/projects/{id}/miniatures/{miniature_id}`, which checks it.
+#[utoipa::path(
+    get,
+    path = "/api/miniatures/{id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id"), MiniatureDetailParams, ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; unchanged resources answer 304")),
+    responses(
+        (status = 200, description = "The miniature, with `photos` and `recipes` when expanded", body = MiniatureDetail, headers(("ETag" = String, description = "Changes whenever the miniature does; only sent without `expand`"))),
+        (status = 304, description = "The miniature is unchanged since the given ETag"),
+        (status = 400, description = "Unknown expand value", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
 pub async fn get_miniature(
     State(database): State<Database>,
     Path(id): Path<i64>,
-) -> Result<Json<Miniature>> {
+    params: std::result::Result<Query<MiniatureDetailParams>, QueryRejection>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Query(params) = params?;
+    let expand = MiniatureExpand::parse_list(params.expand.as_deref())?;
+
     let miniature = MiniatureRepository::find_by_id(&database, id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", id)))?;
 
-    Ok(Json(miniature))
+    miniature_response(&database, miniature, &expand, &headers).await
+}
+
+/// Get a miniature through the project it belongs to; a miniature in another project is
+/// not found here, just as a missing one is
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/miniatures/{miniature_id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Project id"), ("miniature_id" = i64, Path, description = "Miniature id"), MiniatureDetailParams, ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; unchanged resources answer 304")),
+    responses(
+        (status = 200, description = "The miniature, with `photos` and `recipes` when expanded", body = MiniatureDetail, headers(("ETag" = String, description = "Changes whenever the miniature does; only sent without `expand`"))),
+        (status = 304, description = "The miniature is unchanged since the given ETag"),
+        (status = 400, description = "Unknown expand value", body = ErrorResponse),
+        (status = 404, description = "Project not found, or no such miniature in it", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id, miniature_id = miniature_id), err(level = Level::INFO))]
+pub async fn get_project_miniature(
+    State(database): State<Database>,
+    Path((project_id, miniature_id)): Path<(i64, i64)>,
+    params: std::result::Result<Query<MiniatureDetailParams>, QueryRejection>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let Query(params) = params?;
+    let expand = MiniatureExpand::parse_list(params.expand.as_deref())?;
+
+    ProjectRepository::find_by_id(&database, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    let miniature = MiniatureRepository::find_by_id(&database, miniature_id)
+        .await?
+        .filter(|miniature| miniature.project_id == project_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Miniature with id {} not found in project {}",
+                miniature_id, project_id
+            ))
+        })?;
+
+    miniature_response(&database, miniature, &expand, &headers).await
+}
+
+/// A miniature with the expansions asked for; unexpanded, it answers conditionally
+async fn miniature_response(
+    database: &Database,
+    miniature: Miniature,
+    expand: &[MiniatureExpand],
+    headers: &HeaderMap,
+) -> Result<Response> {
+    let id = miniature.id;
+    if expand.is_empty() {
+        return Ok(conditional(headers, miniature.updated_at, miniature).into_response());
+    }
+
+    // Photos and recipe links change without touching the miniature's updated_at, so an
+    // expanded miniature has no ETag to offer
+    let photos = if expand.contains(&MiniatureExpand::Photos) {
+        Some(PhotoRepository::find_by_miniature_id(database, id, true).await?)
+    } else {
+        None
+    };
+    let recipes = if expand.contains(&MiniatureExpand::Recipes) {
+        Some(MiniatureRecipeRepository::find_recipes_for_miniature(database, id).await?)
+    } else {
+        None
+    };
+
+    Ok(Json(MiniatureDetail {
+        miniature,
+        photos,
+        recipes,
+    })
+    .into_response())
 }
 
+/// Replace every editable field of a miniature
+#[utoipa::path(
+    put,
+    path = "/api/miniatures/{id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id"), MiniatureUpdateParams),
+    request_body = UpdateMiniatureRequest,
+    responses(
+        (status = 200, description = "The updated miniature", body = Miniature),
+        (status = 400, description = "Invalid request, or with STRICT_PROGRESS_TRANSITIONS on, a status more than one step ahead", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+        (status = 409, description = "The miniature changed since the given updated_at", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
 pub async fn update_miniature(
     State(database): State<Database>,
+    State(config): State<Arc<Config>>,
     Path(id): Path<i64>,
-    Json(request): Json<UpdateMiniatureRequest>,
+    params: std::result::Result<Query<MiniatureUpdateParams>, QueryRejection>,
+    body: std::result::Result<Json<UpdateMiniatureRequest>, JsonRejection>,
 ) -> Result<Json<Miniature>> {
+    let Query(params) = params?;
+    let Json(request) = body?;
+    apply_miniature_update(&database, &config, id, request.into(), params.force).await
+}
+
+/// Change only the fields present in the request; `"notes": null` clears the notes
+#[utoipa::path(
+    patch,
+    path = "/api/miniatures/{id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id"), MiniatureUpdateParams),
+    request_body = PatchMiniatureRequest,
+    responses(
+        (status = 200, description = "The updated miniature", body = Miniature),
+        (status = 400, description = "Invalid request, or with STRICT_PROGRESS_TRANSITIONS on, a status more than one step ahead", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+        (status = 409, description = "The miniature changed since the given updated_at", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
+pub async fn patch_miniature(
+    State(database): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i64>,
+    params: std::result::Result<Query<MiniatureUpdateParams>, QueryRejection>,
+    body: std::result::Result<Json<PatchMiniatureRequest>, JsonRejection>,
+) -> Result<Json<Miniature>> {
+    let Query(params) = params?;
+    let Json(request) = body?;
+    apply_miniature_update(&database, &config, id, request, params.force).await
+}
+
+/// Validate and apply an update, telling an edit that lost a race apart from a missing miniature
+async fn apply_miniature_update(
+    database: &Database,
+    config: &Config,
+    id: i64,
+    mut request: PatchMiniatureRequest,
+    force: bool,
+) -> Result<Json<Miniature>> {
+    request.name.iter_mut().for_each(collapse_whitespace);
+    request.notes.iter_mut().flatten().for_each(trim_ends);
+
     // Validate fields if provided
     if let Some(ref name) = request.name {
         if name.trim().is_empty()
@@ -84,20 +712,134 @@ pub async fn update_miniature(
         }
     }
 
-    let miniature = MiniatureRepository::update(&database, id, request)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", id)))?;
+    let mut errors = Vec::new();
+    errors.extend(too_long(
+        "notes",
+        request.notes.as_ref().and_then(Option::as_deref),
+        MAX_NOTES_CHARS,
+    ));
+    errors.extend(points_error(request.points_value.flatten()));
+    if !errors.is_empty() {
+        return Err(AppError::FieldValidation(errors));
+    }
+
+    // A status change is checked against, and notifies based on, where the miniature starts
+    let current_status = match request.progress_status {
+        Some(_) => MiniatureRepository::find_by_id(database, id)
+            .await?
+            .map(|current| current.progress_status),
+        None => None,
+    };
+
+    if let (Some(current), Some(next)) = (&current_status, &request.progress_status) {
+        if config.strict_progress_transitions && !force {
+            check_progress_transition(id, current, next)?;
+        }
+    }
+
+    // Only a move into Completed notifies
+    let completing = request.progress_status == Some(ProgressStatus::Completed)
+        && current_status.is_some_and(|current| current != ProgressStatus::Completed);
+
+    let expected_updated_at = request.updated_at;
+    let Some(miniature) = MiniatureRepository::update(database, id, request).await? else {
+        // A guarded update that missed an existing miniature lost to someone else's edit
+        if let Some(expected) = expected_updated_at {
+            if MiniatureRepository::find_by_id(database, id)
+                .await?
+                .is_some()
+            {
+                return Err(AppError::Conflict(format!(
+                    "Miniature with id {} has changed since {}; reload it and try again",
+                    id,
+                    expected.to_rfc3339()
+                )));
+            }
+        }
+        return Err(AppError::NotFound(format!(
+            "Miniature with id {} not found",
+            id
+        )));
+    };
+
+    if completing {
+        notify_completed(database, config, &miniature).await;
+    }
 
     Ok(Json(miniature))
 }
 
+/// The `STRICT_PROGRESS_TRANSITIONS` rule: a status may advance one step, or go back any number
+pub fn check_progress_transition(
+    id: i64,
+    current: &ProgressStatus,
+    next: &ProgressStatus,
+) -> Result<()> {
+    if current.can_move_to(next) {
+        return Ok(());
+    }
+    Err(AppError::ValidationError(format!(
+        "Miniature {} can't go from {} to {} in one step; statuses advance one at a time (pass force=true to skip ahead)",
+        id,
+        current.as_str(),
+        next.as_str()
+    )))
+}
+
+/// Post the completion to `WEBHOOK_URL`, if one is configured. Delivery happens in the
+/// background and problems are only logged; the update has already succeeded.
+async fn notify_completed(database: &Database, config: &Config, miniature: &Miniature) {
+    let Some(webhook_url) = config.webhook_url.clone() else {
+        return;
+    };
+
+    let project_name = match ProjectRepository::find_by_id(database, miniature.project_id).await {
+        Ok(Some(project)) => project.name,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Skipping completion webhook: could not load project: {}", e);
+            return;
+        }
+    };
+
+    webhook_service::spawn_delivery(
+        webhook_url,
+        &MiniatureCompleted {
+            event: "miniature.completed",
+            miniature_id: miniature.id,
+            miniature_name: miniature.name.clone(),
+            project_id: miniature.project_id,
+            project_name,
+            completed_at: miniature.updated_at,
+        },
+    );
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/miniatures/{id}",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id")),
+    responses(
+        (status = 204, description = "Miniature deleted"),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
 pub async fn delete_miniature(
     State(database): State<Database>,
+    State(storage): State<SharedStorage>,
     Path(id): Path<i64>,
 ) -> Result<StatusCode> {
-    let deleted = MiniatureRepository::delete(&database, id).await?;
+    let storage_service = photo_storage(&storage).await?;
+    let mut transaction = database.begin().await?;
+    // The foreign keys drop the photo rows with the miniature, so note their files first
+    let photos = PhotoRepository::find_by_miniature_id(&mut transaction, id, false).await?;
+    let deleted = MiniatureRepository::delete(&mut transaction, id).await?;
+    transaction.commit().await?;
 
     if deleted {
+        storage_service.delete_photo_files(&photos).await;
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err(AppError::NotFound(format!(
@@ -106,3 +848,70 @@ pub async fn delete_miniature(
         )))
     }
 }
+
+/// Move a miniature into another project, for example one filed under the wrong army.
+/// Its photos, recipe links, and history go with it.
+#[utoipa::path(
+    patch,
+    path = "/api/miniatures/{id}/move",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id")),
+    request_body = MoveMiniatureRequest,
+    responses(
+        (status = 200, description = "The moved miniature", body = Miniature),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Miniature or target project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
+pub async fn move_miniature(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<MoveMiniatureRequest>, JsonRejection>,
+) -> Result<Json<Miniature>> {
+    let Json(request) = body?;
+    let target_project_id = request.target_project_id;
+
+    let mut transaction = database.begin().await?;
+    ProjectRepository::find_by_id(&mut transaction, target_project_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Project with id {} not found", target_project_id))
+        })?;
+    let miniature = MiniatureRepository::move_to_project(&mut transaction, id, target_project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", id)))?;
+    transaction.commit().await?;
+
+    tracing::info!(target_project_id, "Miniature moved");
+    Ok(Json(miniature))
+}
+
+/// List a miniature's progress status changes, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/miniatures/{id}/history",
+    tag = "miniatures",
+    params(("id" = i64, Path, description = "Miniature id")),
+    responses(
+        (status = 200, description = "The miniature's status changes", body = crate::openapi::ProgressHistory),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = id), err(level = Level::INFO))]
+pub async fn get_miniature_history(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    // Verify miniature exists
+    MiniatureRepository::find_by_id(&database, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Miniature with id {} not found", id)))?;
+
+    let history = ProgressHistoryRepository::find_by_miniature_id(&database, id).await?;
+
+    Ok(Json(serde_json::json!({
+        "count": history.len(),
+        "history": history
+    })))
+}
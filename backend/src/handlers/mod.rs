@@ -1,5 +1,187 @@
+pub mod admin;
+pub mod cursor;
+pub mod export;
+pub mod idempotency;
 pub mod miniature_recipes;
 pub mod miniatures;
+pub mod paints;
 pub mod photos;
 pub mod projects;
 pub mod recipes;
+pub mod stats;
+pub mod validation;
+
+use crate::error::{AppError, Result};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderName, Method, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+
+const DEFAULT_PAGE_LIMIT: i64 = 50;
+const MAX_PAGE_LIMIT: i64 = 100;
+
+/// Resolve `limit`/`offset` query parameters, applying the default page size and capping
+/// oversized limits
+pub fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64)> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    if limit < 1 {
+        return Err(AppError::ValidationError(
+            "limit must be a positive integer".to_string(),
+        ));
+    }
+
+    let offset = offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(AppError::ValidationError(
+            "offset cannot be negative".to_string(),
+        ));
+    }
+
+    Ok((limit.min(MAX_PAGE_LIMIT), offset))
+}
+
+/// Path parameters, extracted like `axum::extract::Path` but rejected with an `AppError`, so a
+/// malformed id gets the same error body as any other bad request
+#[derive(Debug)]
+pub struct Path<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let axum::extract::Path(value) =
+            axum::extract::Path::<T>::from_request_parts(parts, state).await?;
+        Ok(Path(value))
+    }
+}
+
+/// Router fallback for paths no route matches
+pub async fn route_not_found(method: Method, uri: Uri) -> AppError {
+    AppError::NotFound(format!("No route for {} {}", method, uri.path()))
+}
+
+/// Fallback for a known path requested with a method it doesn't support
+pub async fn method_not_allowed(method: Method, uri: Uri) -> AppError {
+    AppError::MethodNotAllowed(format!("{} is not supported on {}", method, uri.path()))
+}
+
+/// 201 Created with a `Location` header pointing at the new resource
+pub type Created<T> = (StatusCode, [(HeaderName, String); 1], Json<T>);
+
+pub fn created<T>(location: String, resource: T) -> Created<T> {
+    (
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(resource),
+    )
+}
+
+/// A resource served with an `ETag`, or just the tag when the client's copy is current
+#[derive(Debug)]
+pub enum Conditional<T> {
+    Modified(String, Json<T>),
+    /// 304 Not Modified
+    NotModified(String),
+}
+
+impl<T: Serialize> IntoResponse for Conditional<T> {
+    fn into_response(self) -> Response {
+        match self {
+            Conditional::Modified(etag, resource) => {
+                ([(header::ETAG, etag)], resource).into_response()
+            }
+            Conditional::NotModified(etag) => {
+                (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+            }
+        }
+    }
+}
+
+/// A response in the format the request's `Accept` header prefers: JSON, or CSV
+#[derive(Debug)]
+pub enum Negotiated<T> {
+    Json(Json<T>),
+    Csv(Vec<u8>),
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        // Caches must key on Accept, since the same URL has two representations
+        let vary = (header::VARY, "Accept");
+        match self {
+            Negotiated::Json(resource) => ([vary], resource).into_response(),
+            Negotiated::Csv(body) => (
+                [vary, (header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+                body,
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Whether `Accept` ranks `text/csv` above JSON. A missing header, wildcards, and ties
+/// all keep the JSON default.
+pub fn prefers_csv(headers: &HeaderMap) -> bool {
+    let mut csv_quality = 0.0;
+    let mut json_quality = 0.0;
+
+    let ranges = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','));
+    for range in ranges {
+        let mut parts = range.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let quality = parts
+            .filter_map(|param| param.strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        match media_type.as_str() {
+            "text/csv" => csv_quality = f32::max(csv_quality, quality),
+            "application/json" | "application/*" | "*/*" => {
+                json_quality = f32::max(json_quality, quality)
+            }
+            _ => {}
+        }
+    }
+
+    csv_quality > json_quality
+}
+
+/// Tag a resource by its last modification time, which every write bumps, and answer
+/// 304 when the request's `If-None-Match` already holds that tag
+pub fn conditional<T>(
+    headers: &HeaderMap,
+    updated_at: DateTime<Utc>,
+    resource: T,
+) -> Conditional<T> {
+    let etag = format!("\"{}\"", updated_at.timestamp_micros());
+
+    if if_none_match(headers, &etag) {
+        Conditional::NotModified(etag)
+    } else {
+        Conditional::Modified(etag, Json(resource))
+    }
+}
+
+/// Whether any tag in `If-None-Match` matches, using the weak comparison RFC 9110 requires
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
@@ -0,0 +1,229 @@
+use crate::{
+    database::Database,
+    error::{AppError, FieldError, Result},
+    handlers::{
+        conditional, created,
+        idempotency::{idempotency_key, original_gone, original_id, remember},
+        validation::collapse_whitespace,
+        Conditional, Created, Path,
+    },
+    repositories::{PaintRepository, RecipeRepository},
+};
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use serde_json::Value;
+use shared_types::{CreatePaintRequest, ErrorResponse, Paint, UpdatePaintRequest};
+use std::collections::HashSet;
+use tracing::{instrument, Level};
+
+#[utoipa::path(
+    get,
+    path = "/api/paints",
+    tag = "paints",
+    responses(
+        (status = 200, description = "Every paint in the inventory, by name", body = crate::openapi::PaintList),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn list_paints(State(database): State<Database>) -> Result<Json<Value>> {
+    let paints = PaintRepository::find_all(&database).await?;
+
+    Ok(Json(serde_json::json!({
+        "count": paints.len(),
+        "paints": paints
+    })))
+}
+
+/// Idempotency key scope for paint creation
+const PAINTS_SCOPE: &str = "POST /api/paints";
+
+#[utoipa::path(
+    post,
+    path = "/api/paints",
+    tag = "paints",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Retrying with the same key within a day returns the resource the first request created")),
+    request_body = CreatePaintRequest,
+    responses(
+        (status = 201, description = "Paint added", body = Paint, headers(("Location" = String, description = "URL of the new paint"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 409, description = "The Idempotency-Key is in use by a concurrent request, or its paint has been deleted", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn create_paint(
+    State(database): State<Database>,
+    headers: HeaderMap,
+    body: std::result::Result<Json<CreatePaintRequest>, JsonRejection>,
+) -> Result<Created<Paint>> {
+    let idempotency_key = idempotency_key(&headers)?;
+    let Json(mut request) = body?;
+    normalize_paint(&mut request.name, &mut request.brand);
+    validate_paint(&request.name, request.quantity_remaining)?;
+
+    let key = idempotency_key.as_deref();
+    let mut transaction = database.begin().await?;
+    if let Some(id) = original_id(&mut transaction, PAINTS_SCOPE, key).await? {
+        let paint = PaintRepository::find_by_id(&mut transaction, id)
+            .await?
+            .ok_or_else(|| original_gone(key.unwrap_or_default(), "paint", id))?;
+        return Ok(created(format!("/api/paints/{}", id), paint));
+    }
+    let paint = PaintRepository::create(&mut transaction, request).await?;
+    remember(&mut transaction, PAINTS_SCOPE, key, paint.id).await?;
+    transaction.commit().await?;
+
+    tracing::info!(paint_id = paint.id, "Paint created");
+    Ok(created(format!("/api/paints/{}", paint.id), paint))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/paints/{id}",
+    tag = "paints",
+    params(("id" = i64, Path, description = "Paint id"), ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; unchanged resources answer 304")),
+    responses(
+        (status = 200, description = "The paint", body = Paint, headers(("ETag" = String, description = "Changes whenever the paint does"))),
+        (status = 304, description = "The paint is unchanged since the given ETag"),
+        (status = 404, description = "Paint not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(paint_id = id), err(level = Level::INFO))]
+pub async fn get_paint(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Conditional<Paint>> {
+    let paint = PaintRepository::find_by_id(&database, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Paint with id {} not found", id)))?;
+
+    Ok(conditional(&headers, paint.updated_at, paint))
+}
+
+/// Replace a paint's name, brand, and quantity
+#[utoipa::path(
+    put,
+    path = "/api/paints/{id}",
+    tag = "paints",
+    params(("id" = i64, Path, description = "Paint id")),
+    request_body = UpdatePaintRequest,
+    responses(
+        (status = 200, description = "The updated paint", body = Paint),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Paint not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(paint_id = id), err(level = Level::INFO))]
+pub async fn update_paint(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<UpdatePaintRequest>, JsonRejection>,
+) -> Result<Json<Paint>> {
+    let Json(mut request) = body?;
+    normalize_paint(&mut request.name, &mut request.brand);
+    validate_paint(&request.name, request.quantity_remaining)?;
+
+    let paint = PaintRepository::update(&database, id, request)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Paint with id {} not found", id)))?;
+
+    Ok(Json(paint))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/paints/{id}",
+    tag = "paints",
+    params(("id" = i64, Path, description = "Paint id")),
+    responses(
+        (status = 204, description = "Paint deleted"),
+        (status = 404, description = "Paint not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(paint_id = id), err(level = Level::INFO))]
+pub async fn delete_paint(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    if PaintRepository::delete(&database, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::NotFound(format!(
+            "Paint with id {} not found",
+            id
+        )))
+    }
+}
+
+/// List the paints a recipe calls for that aren't in the inventory, or have run out. Names
+/// match case-insensitively, ignoring surrounding whitespace.
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/missing-paints",
+    tag = "paints",
+    params(("id" = i64, Path, description = "Recipe id")),
+    responses(
+        (status = 200, description = "The recipe's paints not on hand, in recipe order", body = crate::openapi::MissingPaints),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
+pub async fn get_missing_paints(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    let recipe = RecipeRepository::find_by_id(&database, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", id)))?;
+
+    let mut seen: HashSet<String> = PaintRepository::owned_names(&database)
+        .await?
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+
+    // Marking each paint as seen also drops repeats within the recipe
+    let missing: Vec<&str> = recipe
+        .paints_used
+        .iter()
+        .map(|paint| paint.trim())
+        .filter(|paint| !paint.is_empty() && seen.insert(paint.to_lowercase()))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "recipe_id": id,
+        "count": missing.len(),
+        "missing": missing
+    })))
+}
+
+/// Tidy a paint's name and brand the way they are stored; a blank brand is no brand
+fn normalize_paint(name: &mut String, brand: &mut Option<String>) {
+    collapse_whitespace(name);
+    brand.iter_mut().for_each(collapse_whitespace);
+    if brand.as_deref() == Some("") {
+        *brand = None;
+    }
+}
+
+fn validate_paint(name: &str, quantity_remaining: i64) -> Result<()> {
+    let mut errors = Vec::new();
+    if name.is_empty() {
+        errors.push(FieldError::new("name", "Paint name is required"));
+    }
+    if quantity_remaining < 0 {
+        errors.push(FieldError::new(
+            "quantity_remaining",
+            "quantity_remaining cannot be negative",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::FieldValidation(errors))
+    }
+}
@@ -1,25 +1,618 @@
-use crate::config::Config;
+use crate::config::{Config, ReencodeFormat};
 use crate::database::Database;
+use crate::handlers::{if_none_match, Path};
 use crate::repositories::MiniatureRepository;
 use crate::repositories::PhotoRepository;
-use crate::services::storage_service::StorageService;
+use crate::services::reencode_service;
+use crate::services::storage_service::{SharedStorage, StorageService};
+use crate::services::thumbnail_service::{self, THUMBNAIL_MIME_TYPE};
+use crate::storage::StorageError;
 use axum::{
-    extract::{Multipart, Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{multipart::MultipartError, rejection::QueryRejection, Multipart, Query, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
-use chrono::Utc;
-use serde_json::json;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use shared_types::{ErrorDetails, ErrorResponse, Photo};
+use std::io::Cursor;
+use std::sync::Arc;
+use tracing::instrument;
+use utoipa::IntoParams;
 
 const MAX_FILE_SIZE: usize = 10 * 1024 * 1024; // 10MB
-const ALLOWED_MIME_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+/// Request body cap for uploads: the largest allowed file plus room for multipart framing
+pub const MAX_UPLOAD_BODY_SIZE: usize = MAX_FILE_SIZE + 64 * 1024;
+/// Most photos stored from one batch upload; later files are reported as errors
+const MAX_BATCH_PHOTOS: usize = 10;
+/// Request body cap for batch uploads: a full batch of the largest allowed files
+pub const MAX_BATCH_BODY_SIZE: usize = MAX_BATCH_PHOTOS * MAX_UPLOAD_BODY_SIZE;
+/// Span over which `UPLOAD_RATE_LIMIT_PER_MINUTE` counts a miniature's uploads
+const UPLOAD_RATE_WINDOW_SECONDS: i64 = 60;
 
+/// Check the leading bytes of a file against the signature for its MIME type
+fn has_matching_signature(mime_type: &str, data: &[u8]) -> bool {
+    match mime_type {
+        "image/jpeg" => data.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/png" => data.starts_with(&[0x89, 0x50, 0x4E, 0x47]),
+        "image/webp" => data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP",
+        // Types opted into with ALLOWED_IMAGE_TYPES, by the image crate's own magic numbers
+        other => image::ImageFormat::from_mime_type(other)
+            .is_some_and(|format| image::guess_format(data).is_ok_and(|found| found == format)),
+    }
+}
+
+/// Map a multipart read failure to a client error, reporting bodies over the size limit as 413
+fn multipart_error(error: MultipartError, error_type: &str, message: &str) -> UploadError {
+    let status = error.status();
+    let error_type = if status == StatusCode::PAYLOAD_TOO_LARGE {
+        "payload_too_large"
+    } else {
+        error_type
+    };
+
+    upload_error(
+        status,
+        error_type,
+        format!("{}: {}", message, error.body_text()),
+    )
+}
+
+/// Read an image's dimensions from its header without decoding the pixel data
+fn probe_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Hex-encoded SHA-256 of the uploaded bytes
+fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Generate and store a thumbnail for an uploaded photo, returning its storage path. This is
+/// best-effort: failures are logged and leave the photo without a thumbnail.
+async fn create_thumbnail(
+    storage_service: &StorageService,
+    file_data: Vec<u8>,
+    file_path: &str,
+) -> Option<String> {
+    // Decoding and resizing are CPU-bound, so keep them off the async workers
+    let generated =
+        tokio::task::spawn_blocking(move || thumbnail_service::generate_thumbnail(&file_data))
+            .await;
+
+    let thumbnail = match generated {
+        Ok(Ok(thumbnail)) => thumbnail,
+        Ok(Err(e)) => {
+            tracing::warn!("Skipping thumbnail for {}: {}", file_path, e);
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("Thumbnail generation for {} failed: {}", file_path, e);
+            return None;
+        }
+    };
+
+    match storage_service.store_thumbnail(&thumbnail, file_path).await {
+        Ok(thumbnail_path) => Some(thumbnail_path),
+        Err(e) => {
+            tracing::warn!("Failed to store thumbnail for {}: {}", file_path, e);
+            None
+        }
+    }
+}
+
+/// Re-encode an upload as WebP when that makes it smaller, renaming it to match. This is
+/// best-effort: files that can't be decoded are stored as uploaded, with a warning.
+async fn reencode_upload(
+    file_data: Vec<u8>,
+    filename: String,
+    mime_type: String,
+    quality: u8,
+) -> Result<(Vec<u8>, String, String), UploadError> {
+    if reencode_service::is_webp(&file_data) {
+        return Ok((file_data, filename, mime_type));
+    }
+
+    // Decoding and encoding are CPU-bound, so keep them off the async workers
+    let (file_data, encoded) = tokio::task::spawn_blocking(move || {
+        let encoded = reencode_service::reencode_as_webp(&file_data, quality);
+        (file_data, encoded)
+    })
+    .await
+    .map_err(|e| {
+        upload_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "reencode_error",
+            format!("Re-encoding {} failed: {}", filename, e),
+        )
+    })?;
+
+    Ok(match encoded {
+        Ok(Some(webp)) => (
+            webp,
+            reencode_service::webp_filename(&filename),
+            reencode_service::WEBP_MIME_TYPE.to_string(),
+        ),
+        Ok(None) => (file_data, filename, mime_type),
+        Err(e) => {
+            tracing::warn!("Storing {} without re-encoding: {}", filename, e);
+            (file_data, filename, mime_type)
+        }
+    })
+}
+
+/// Error response for a rejected upload, with any headers it needs such as `Retry-After`
+type UploadError = (StatusCode, HeaderMap, Json<ErrorResponse>);
+
+fn upload_error(status: StatusCode, error_type: &str, message: String) -> UploadError {
+    (
+        status,
+        HeaderMap::new(),
+        Json(ErrorResponse {
+            error: ErrorDetails {
+                error_type: error_type.to_string(),
+                message,
+                details: None,
+                timestamp: Utc::now(),
+            },
+        }),
+    )
+}
+
+/// The error for an upload whose declared `Content-Length` is already over `limit`, found
+/// before any of the body is buffered. The size checks after reading still catch a missing
+/// or wrong header.
+fn declared_too_large(headers: &HeaderMap, limit: usize) -> Option<UploadError> {
+    let length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|&length| length > limit as u64)?;
+    Some(upload_error(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload_too_large",
+        format!(
+            "Upload of {} bytes exceeds the maximum request size of {} bytes",
+            length, limit
+        ),
+    ))
+}
+
+/// A `photo` field read from the multipart body, not yet checked
+struct UploadedFile {
+    filename: Option<String>,
+    mime_type: Option<String>,
+    data: Vec<u8>,
+}
+
+/// Read the next `photo` field, skipping any others. Failing to read the body is fatal to the
+/// whole request, since the rest of it can't be parsed either.
+async fn next_photo_field(multipart: &mut Multipart) -> Result<Option<UploadedFile>, UploadError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| multipart_error(e, "invalid_multipart", "Invalid multipart data"))?
+    {
+        if field.name() != Some("photo") {
+            continue;
+        }
+
+        let filename = field.file_name().map(|s| s.to_string());
+        let mime_type = field.content_type().map(|s| s.to_string());
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| multipart_error(e, "file_read_error", "Failed to read file data"))?;
+
+        return Ok(Some(UploadedFile {
+            filename,
+            mime_type,
+            data: data.to_vec(),
+        }));
+    }
+
+    Ok(None)
+}
+
+async fn ensure_miniature_exists(
+    database: &Database,
+    miniature_id: i64,
+) -> Result<(), UploadError> {
+    match MiniatureRepository::find_by_id(database, miniature_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(upload_error(
+            StatusCode::NOT_FOUND,
+            "not_found",
+            format!("Miniature with id {} not found", miniature_id),
+        )),
+        Err(e) => Err(upload_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            format!("Database error: {}", e),
+        )),
+    }
+}
+
+/// How many more photos the miniature may receive right now, or a 429 telling the client
+/// when to try again once the per-minute limit is used up. `None` means no limit is set.
+async fn upload_allowance(
+    database: &Database,
+    config: &Config,
+    miniature_id: i64,
+) -> Result<Option<usize>, UploadError> {
+    let limit = config.upload_rate_limit_per_minute;
+    if limit == 0 {
+        return Ok(None);
+    }
+
+    let (recent, oldest) =
+        PhotoRepository::recent_uploads(database, miniature_id, UPLOAD_RATE_WINDOW_SECONDS)
+            .await
+            .map_err(|e| {
+                upload_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database_error",
+                    format!("Database error: {}", e),
+                )
+            })?;
+
+    let remaining = (limit as i64 - recent).max(0) as usize;
+    if remaining > 0 {
+        return Ok(Some(remaining));
+    }
+
+    // The oldest upload in the window is the next to age out of it
+    let retry_after = oldest
+        .map(|oldest| {
+            let age_ms = (Utc::now() - oldest).num_milliseconds();
+            (UPLOAD_RATE_WINDOW_SECONDS * 1000 - age_ms + 999) / 1000
+        })
+        .unwrap_or(UPLOAD_RATE_WINDOW_SECONDS)
+        .clamp(1, UPLOAD_RATE_WINDOW_SECONDS);
+
+    let (status, mut headers, body) = upload_error(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        format!(
+            "Miniature {} has reached its limit of {} photo uploads per minute",
+            miniature_id, limit
+        ),
+    );
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    Err((status, headers, body))
+}
+
+/// The storage backend an upload is stored with
+async fn upload_storage(storage: &SharedStorage) -> Result<&StorageService, UploadError> {
+    storage.get().await.map_err(|e| {
+        upload_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "storage_error",
+            format!("Storage initialization error: {}", e),
+        )
+    })
+}
+
+/// Validate one uploaded file and store it with its thumbnail and database record. A file
+/// already uploaded for the miniature returns the photo stored for it instead.
+async fn save_upload(
+    database: &Database,
+    config: &Config,
+    storage_service: &StorageService,
+    miniature_id: i64,
+    upload: UploadedFile,
+) -> Result<Photo, UploadError> {
+    let UploadedFile {
+        filename,
+        mime_type,
+        data: file_data,
+    } = upload;
+
+    if let Some(ref mt) = mime_type {
+        if !config.allowed_image_types.contains(mt) {
+            return Err(upload_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_file_type",
+                format!(
+                    "Unsupported file type: {}. Allowed types: {}",
+                    mt,
+                    config.allowed_image_types.join(", ")
+                ),
+            ));
+        }
+    }
+
+    if file_data.len() > MAX_FILE_SIZE {
+        return Err(upload_error(
+            StatusCode::BAD_REQUEST,
+            "file_too_large",
+            format!(
+                "File size {} bytes exceeds maximum allowed size of {} bytes",
+                file_data.len(),
+                MAX_FILE_SIZE
+            ),
+        ));
+    }
+
+    let filename = filename.ok_or_else(|| {
+        upload_error(
+            StatusCode::BAD_REQUEST,
+            "missing_filename",
+            "No filename provided".to_string(),
+        )
+    })?;
+
+    let mime_type = mime_type.ok_or_else(|| {
+        upload_error(
+            StatusCode::BAD_REQUEST,
+            "missing_mime_type",
+            "No MIME type provided".to_string(),
+        )
+    })?;
+
+    // Don't trust the declared type; the stored mime_type is served back as Content-Type
+    if !has_matching_signature(&mime_type, &file_data) {
+        return Err(upload_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_file_type",
+            format!("File contents do not match declared type {}", mime_type),
+        ));
+    }
+
+    // Huge dimensions can hide in a small file; check before anything decodes the pixels.
+    // Headers that can't be read are let through, like undecodable images for thumbnails.
+    if let Some((width, height)) = probe_dimensions(&file_data) {
+        if width > config.max_image_width || height > config.max_image_height {
+            return Err(upload_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_file_dimensions",
+                format!(
+                    "Image dimensions {}x{} exceed the maximum of {}x{}",
+                    width, height, config.max_image_width, config.max_image_height
+                ),
+            ));
+        }
+    }
+
+    let (file_data, filename, mime_type) = match config.reencode_uploads {
+        Some(ReencodeFormat::Webp) => {
+            reencode_upload(file_data, filename, mime_type, config.reencode_quality).await?
+        }
+        None => (file_data, filename, mime_type),
+    };
+
+    // A retried upload of the same file returns the photo already stored for it. The hash is
+    // of the bytes actually stored, so it matches what `/content` serves.
+    let content_hash = content_hash(&file_data);
+    let existing =
+        PhotoRepository::find_by_miniature_and_hash(database, miniature_id, &content_hash)
+            .await
+            .map_err(|e| {
+                upload_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database_error",
+                    format!("Database error: {}", e),
+                )
+            })?;
+    if let Some(photo) = existing {
+        return Ok(photo);
+    }
+
+    // Store the file
+    let file_path = storage_service
+        .store_photo(&file_data, &filename, &mime_type, miniature_id)
+        .await
+        .map_err(|e| {
+            upload_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "storage_error",
+                format!("Failed to store photo: {}", e),
+            )
+        })?;
+
+    let file_size = file_data.len() as i64;
+    let thumbnail_path = create_thumbnail(storage_service, file_data, &file_path).await;
+
+    // Save photo record to database
+    let photo = PhotoRepository::create(
+        database,
+        miniature_id,
+        filename,
+        file_path,
+        file_size,
+        mime_type,
+        Some(content_hash),
+        thumbnail_path,
+    )
+    .await
+    .map_err(|e| {
+        upload_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            format!("Failed to save photo record: {}", e),
+        )
+    })?;
+
+    tracing::info!(photo_id = photo.id, "Photo uploaded");
+    Ok(photo)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/miniatures/{id}/photos",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Miniature id")),
+    request_body(content = crate::openapi::PhotoUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The stored photo, or the existing one when the same file was already uploaded", body = Photo),
+        (status = 400, description = "Missing, unsupported, or oversized image", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+        (status = 413, description = "Upload larger than 10MB", body = ErrorResponse),
+        (status = 429, description = "The miniature's per-minute upload limit is used up", body = ErrorResponse, headers(("Retry-After" = u64, description = "Seconds until another upload is allowed"))),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id))]
 pub async fn upload_photo(
     Path(miniature_id): Path<i64>,
     State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
     mut multipart: Multipart,
-) -> Result<Json<Photo>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Photo>, UploadError> {
+    if let Some(error) = declared_too_large(&headers, MAX_UPLOAD_BODY_SIZE) {
+        return Err(error);
+    }
+    ensure_miniature_exists(&database, miniature_id).await?;
+    let storage_service = upload_storage(&storage).await?;
+    upload_allowance(&database, &config, miniature_id).await?;
+
+    // Should the form repeat the field, the last file is the one stored
+    let mut upload = None;
+    while let Some(file) = next_photo_field(&mut multipart).await? {
+        upload = Some(file);
+    }
+    let upload = upload.ok_or_else(|| {
+        upload_error(
+            StatusCode::BAD_REQUEST,
+            "missing_file",
+            "No photo file provided".to_string(),
+        )
+    })?;
+
+    let photo = save_upload(&database, &config, storage_service, miniature_id, upload).await?;
+
+    Ok(Json(photo))
+}
+
+/// Store every `photo` field of the form. Each file is checked on its own, so one bad file
+/// is reported in `errors` without holding back the rest.
+#[utoipa::path(
+    post,
+    path = "/api/miniatures/{id}/photos/batch",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Miniature id")),
+    request_body(content = crate::openapi::PhotoBatchUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The stored photos and the files that were rejected", body = crate::openapi::PhotoBatchResult),
+        (status = 400, description = "No photo files, or a malformed form", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+        (status = 413, description = "Upload larger than the batch limit", body = ErrorResponse),
+        (status = 429, description = "The miniature's per-minute upload limit is used up; files past a partly used limit are reported in `errors` instead", body = ErrorResponse, headers(("Retry-After" = u64, description = "Seconds until another upload is allowed"))),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id))]
+pub async fn upload_photos_batch(
+    Path(miniature_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, UploadError> {
+    if let Some(error) = declared_too_large(&headers, MAX_BATCH_BODY_SIZE) {
+        return Err(error);
+    }
+    ensure_miniature_exists(&database, miniature_id).await?;
+    let storage_service = upload_storage(&storage).await?;
+    let allowance = upload_allowance(&database, &config, miniature_id).await?;
+
+    let mut photos = Vec::new();
+    let mut errors = Vec::new();
+    let mut index = 0;
+    while let Some(upload) = next_photo_field(&mut multipart).await? {
+        let filename = upload.filename.clone();
+        let result = if index >= MAX_BATCH_PHOTOS {
+            Err(upload_error(
+                StatusCode::BAD_REQUEST,
+                "too_many_files",
+                format!("A batch can hold at most {} photos", MAX_BATCH_PHOTOS),
+            ))
+        } else if allowance.is_some_and(|allowance| index >= allowance) {
+            Err(upload_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                format!(
+                    "Miniature {} has reached its limit of {} photo uploads per minute",
+                    miniature_id, config.upload_rate_limit_per_minute
+                ),
+            ))
+        } else {
+            save_upload(&database, &config, storage_service, miniature_id, upload).await
+        };
+
+        match result {
+            Ok(photo) => photos.push(photo),
+            Err((_, _, Json(response))) => errors.push(serde_json::json!({
+                "index": index,
+                "filename": filename,
+                "error_type": response.error.error_type,
+                "message": response.error.message
+            })),
+        }
+        index += 1;
+    }
+
+    if index == 0 {
+        return Err(upload_error(
+            StatusCode::BAD_REQUEST,
+            "missing_file",
+            "No photo file provided".to_string(),
+        ));
+    }
+
+    Ok(Json(serde_json::json!({
+        "count": photos.len(),
+        "photos": photos,
+        "errors": errors
+    })))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListPhotosQueryParams {
+    /// List the primary photo ahead of the rest
+    #[serde(default)]
+    pub primary_first: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/miniatures/{id}/photos",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Miniature id"), ListPhotosQueryParams),
+    responses(
+        (status = 200, description = "The miniature's photos", body = Vec<Photo>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id))]
+pub async fn list_photos(
+    Path(miniature_id): Path<i64>,
+    State(database): State<Database>,
+    params: std::result::Result<Query<ListPhotosQueryParams>, QueryRejection>,
+) -> Result<Json<Vec<Photo>>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|rejection| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "validation_error".to_string(),
+                    message: rejection.body_text(),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
+
     // Check if miniature exists
     match MiniatureRepository::find_by_id(&database, miniature_id).await {
         Ok(Some(_)) => {}
@@ -51,58 +644,16 @@ pub async fn upload_photo(
         }
     }
 
-    // Process multipart form data
-    let mut file_data: Option<Vec<u8>> = None;
-    let mut filename: Option<String> = None;
-    let mut mime_type: Option<String> = None;
-
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: ErrorDetails {
-                    error_type: "invalid_multipart".to_string(),
-                    message: format!("Invalid multipart data: {}", e),
-                    details: None,
-                    timestamp: Utc::now(),
-                },
-            }),
-        )
-    })? {
-        let field_name = field.name().unwrap_or("").to_string();
-
-        if field_name == "photo" {
-            filename = field.file_name().map(|s| s.to_string());
-            mime_type = field.content_type().map(|s| s.to_string());
-
-            // Validate MIME type
-            if let Some(ref mt) = mime_type {
-                if !ALLOWED_MIME_TYPES.contains(&mt.as_str()) {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse {
-                            error: ErrorDetails {
-                                error_type: "invalid_file_type".to_string(),
-                                message: format!(
-                                    "Unsupported file type: {}. Allowed types: {}",
-                                    mt,
-                                    ALLOWED_MIME_TYPES.join(", ")
-                                ),
-                                details: None,
-                                timestamp: Utc::now(),
-                            },
-                        }),
-                    ));
-                }
-            }
-
-            let data = field.bytes().await.map_err(|e| {
+    let photos =
+        PhotoRepository::find_by_miniature_id(&database, miniature_id, params.primary_first)
+            .await
+            .map_err(|e| {
                 (
-                    StatusCode::BAD_REQUEST,
+                    StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ErrorResponse {
                         error: ErrorDetails {
-                            error_type: "file_read_error".to_string(),
-                            message: format!("Failed to read file data: {}", e),
+                            error_type: "database_error".to_string(),
+                            message: format!("Failed to retrieve photos: {}", e),
                             details: None,
                             timestamp: Utc::now(),
                         },
@@ -110,37 +661,47 @@ pub async fn upload_photo(
                 )
             })?;
 
-            // Check file size
-            if data.len() > MAX_FILE_SIZE {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: ErrorDetails {
-                            error_type: "file_too_large".to_string(),
-                            message: format!(
-                                "File size {} bytes exceeds maximum allowed size of {} bytes",
-                                data.len(),
-                                MAX_FILE_SIZE
-                            ),
-                            details: None,
-                            timestamp: Utc::now(),
-                        },
-                    }),
-                ));
-            }
+    Ok(Json(photos))
+}
 
-            file_data = Some(data.to_vec());
-        }
-    }
+#[utoipa::path(
+    get,
+    path = "/api/photos/{id}",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "Photo metadata", body = Photo),
+        (status = 404, description = "Photo not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn get_photo(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+) -> Result<Json<Photo>, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::find_by_id(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "database_error".to_string(),
+                        message: format!("Database error: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            )
+        })?;
 
-    // Validate required fields
-    let file_data = file_data.ok_or_else(|| {
+    let photo = photo.ok_or_else(|| {
         (
-            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
                 error: ErrorDetails {
-                    error_type: "missing_file".to_string(),
-                    message: "No photo file provided".to_string(),
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
                     details: None,
                     timestamp: Utc::now(),
                 },
@@ -148,13 +709,48 @@ pub async fn upload_photo(
         )
     })?;
 
-    let filename = filename.ok_or_else(|| {
+    Ok(Json(photo))
+}
+
+/// Make a photo its miniature's primary photo, clearing the flag on the others
+#[utoipa::path(
+    put,
+    path = "/api/photos/{id}/primary",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "The new primary photo", body = Photo),
+        (status = 404, description = "Photo not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn set_primary_photo(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+) -> Result<Json<Photo>, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::set_primary(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "database_error".to_string(),
+                        message: format!("Database error: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            )
+        })?;
+
+    let photo = photo.ok_or_else(|| {
         (
-            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
                 error: ErrorDetails {
-                    error_type: "missing_filename".to_string(),
-                    message: "No filename provided".to_string(),
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
                     details: None,
                     timestamp: Utc::now(),
                 },
@@ -162,13 +758,89 @@ pub async fn upload_photo(
         )
     })?;
 
-    let mime_type = mime_type.ok_or_else(|| {
+    Ok(Json(photo))
+}
+
+/// Validators and cache lifetime for a photo's contents, which never change once uploaded.
+/// The ETag is the content hash, or the id and size for photos from before hashing.
+fn photo_cache_headers(photo: &Photo, max_age_seconds: u32) -> [(HeaderName, String); 3] {
+    let etag = match &photo.content_hash {
+        Some(hash) => format!("\"{}\"", hash),
+        None => format!("\"{}-{}\"", photo.id, photo.file_size),
+    };
+
+    [
+        (header::ETAG, etag),
         (
-            StatusCode::BAD_REQUEST,
+            header::LAST_MODIFIED,
+            photo
+                .uploaded_at
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        ),
+        (
+            header::CACHE_CONTROL,
+            format!("public, max-age={}, immutable", max_age_seconds),
+        ),
+    ]
+}
+
+/// Whether the client's copy is current. `If-None-Match` decides when present; otherwise
+/// `If-Modified-Since`, compared to the second as HTTP dates are.
+fn photo_not_modified(headers: &HeaderMap, etag: &str, uploaded_at: DateTime<Utc>) -> bool {
+    if headers.contains_key(header::IF_NONE_MATCH) {
+        return if_none_match(headers, etag);
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| uploaded_at.timestamp() <= since.timestamp())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/photos/{id}/content",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "The image file, with its original MIME type", body = crate::openapi::FileContents, content_type = "application/octet-stream", headers(("ETag" = String, description = "The file's content hash"), ("Last-Modified" = String, description = "When the photo was uploaded"), ("Cache-Control" = String, description = "Immutable, for PHOTO_CACHE_MAX_AGE_SECONDS"))),
+        (status = 304, description = "The cached copy named by If-None-Match or If-Modified-Since is current"),
+        (status = 404, description = "Photo or its file not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn get_photo_content(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::find_by_id(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "database_error".to_string(),
+                        message: format!("Database error: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            )
+        })?;
+
+    let photo = photo.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
                 error: ErrorDetails {
-                    error_type: "missing_mime_type".to_string(),
-                    message: "No MIME type provided".to_string(),
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
                     details: None,
                     timestamp: Utc::now(),
                 },
@@ -177,13 +849,104 @@ pub async fn upload_photo(
     })?;
 
     // Initialize storage service
-    let config = Config::from_env().map_err(|e| {
+    let storage_service = storage.get().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
                 error: ErrorDetails {
-                    error_type: "config_error".to_string(),
-                    message: format!("Configuration error: {}", e),
+                    error_type: "storage_error".to_string(),
+                    message: format!("Storage initialization error: {}", e),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
+
+    let cache_headers = photo_cache_headers(&photo, config.photo_cache_max_age_seconds);
+    if photo_not_modified(&headers, &cache_headers[0].1, photo.uploaded_at) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let file_data = storage_service
+        .retrieve_photo(&photo.file_path)
+        .await
+        .map_err(|e| match e {
+            // The record exists but its backing file is gone
+            StorageError::FileNotFound(_) => (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "file_missing".to_string(),
+                        message: format!("File for photo with id {} is missing", photo_id),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            ),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "storage_error".to_string(),
+                        message: format!("Failed to retrieve photo: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            ),
+        })?;
+
+    Ok((
+        cache_headers,
+        [(header::CONTENT_TYPE, photo.mime_type)],
+        file_data,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    head,
+    path = "/api/photos/{id}/content",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "The photo is available; Content-Type, Content-Length, and the caching headers describe it, with no body"),
+        (status = 304, description = "The cached copy named by If-None-Match or If-Modified-Since is current"),
+        (status = 404, description = "Photo or its file not found"),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn head_photo_content(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::find_by_id(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "database_error".to_string(),
+                        message: format!("Database error: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            )
+        })?;
+
+    let photo = photo.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
                     details: None,
                     timestamp: Utc::now(),
                 },
@@ -191,7 +954,8 @@ pub async fn upload_photo(
         )
     })?;
 
-    let storage_service = StorageService::new(&config).await.map_err(|e| {
+    // Initialize storage service
+    let storage_service = storage.get().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -205,9 +969,9 @@ pub async fn upload_photo(
         )
     })?;
 
-    // Store the file
-    let file_path = storage_service
-        .store_photo(&file_data, &filename, miniature_id)
+    // Ask the backend whether the file is there rather than reading it
+    let exists = storage_service
+        .photo_exists(&photo.file_path)
         .await
         .map_err(|e| {
             (
@@ -215,31 +979,82 @@ pub async fn upload_photo(
                 Json(ErrorResponse {
                     error: ErrorDetails {
                         error_type: "storage_error".to_string(),
-                        message: format!("Failed to store photo: {}", e),
+                        message: format!("Failed to check photo: {}", e),
                         details: None,
                         timestamp: Utc::now(),
                     },
                 }),
             )
         })?;
+    if !exists {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "file_missing".to_string(),
+                    message: format!("File for photo with id {} is missing", photo_id),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        ));
+    }
 
-    // Save photo record to database
-    let photo = PhotoRepository::create(
-        &database,
-        miniature_id,
-        filename,
-        file_path,
-        file_data.len() as i64,
-        mime_type,
+    let cache_headers = photo_cache_headers(&photo, config.photo_cache_max_age_seconds);
+    if photo_not_modified(&headers, &cache_headers[0].1, photo.uploaded_at) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        cache_headers,
+        [
+            (header::CONTENT_TYPE, photo.mime_type),
+            (header::CONTENT_LENGTH, photo.file_size.to_string()),
+        ],
+        (),
     )
-    .await
-    .map_err(|e| {
+        .into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/photos/{id}/thumbnail",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "A JPEG thumbnail", body = crate::openapi::FileContents, content_type = "image/jpeg"),
+        (status = 404, description = "Photo or thumbnail not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn get_photo_thumbnail(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::find_by_id(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "database_error".to_string(),
+                        message: format!("Database error: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            )
+        })?;
+
+    let photo = photo.ok_or_else(|| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::NOT_FOUND,
             Json(ErrorResponse {
                 error: ErrorDetails {
-                    error_type: "database_error".to_string(),
-                    message: format!("Failed to save photo record: {}", e),
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
                     details: None,
                     timestamp: Utc::now(),
                 },
@@ -247,31 +1062,87 @@ pub async fn upload_photo(
         )
     })?;
 
-    Ok(Json(photo))
-}
+    let thumbnail_path = photo.thumbnail_path.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} has no thumbnail", photo_id),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
 
-pub async fn list_photos(
-    Path(miniature_id): Path<i64>,
-    State(database): State<Database>,
-) -> Result<Json<Vec<Photo>>, (StatusCode, Json<ErrorResponse>)> {
-    // Check if miniature exists
-    match MiniatureRepository::find_by_id(&database, miniature_id).await {
-        Ok(Some(_)) => {}
-        Ok(None) => {
-            return Err((
+    // Initialize storage service
+    let storage_service = storage.get().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "storage_error".to_string(),
+                    message: format!("Storage initialization error: {}", e),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
+
+    let file_data = storage_service
+        .retrieve_photo(&thumbnail_path)
+        .await
+        .map_err(|e| match e {
+            // The record exists but its backing file is gone
+            StorageError::FileNotFound(_) => (
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
                     error: ErrorDetails {
-                        error_type: "not_found".to_string(),
-                        message: format!("Miniature with id {} not found", miniature_id),
+                        error_type: "file_missing".to_string(),
+                        message: format!("Thumbnail for photo with id {} is missing", photo_id),
                         details: None,
                         timestamp: Utc::now(),
                     },
                 }),
-            ));
-        }
-        Err(e) => {
-            return Err((
+            ),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetails {
+                        error_type: "storage_error".to_string(),
+                        message: format!("Failed to retrieve thumbnail: {}", e),
+                        details: None,
+                        timestamp: Utc::now(),
+                    },
+                }),
+            ),
+        })?;
+
+    Ok(([(header::CONTENT_TYPE, THUMBNAIL_MIME_TYPE)], file_data).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/photos/{id}/url",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 200, description = "A URL for the image file", body = crate::openapi::PhotoUrl),
+        (status = 404, description = "Photo not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
+pub async fn get_photo_url(
+    Path(photo_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let photo = PhotoRepository::find_by_id(&database, photo_id)
+        .await
+        .map_err(|e| {
+            (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
                     error: ErrorDetails {
@@ -281,19 +1152,48 @@ pub async fn list_photos(
                         timestamp: Utc::now(),
                     },
                 }),
-            ));
-        }
-    }
+            )
+        })?;
+
+    let photo = photo.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "not_found".to_string(),
+                    message: format!("Photo with id {} not found", photo_id),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
 
-    let photos = PhotoRepository::find_by_miniature_id(&database, miniature_id)
+    // Initialize storage service
+    let storage_service = storage.get().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetails {
+                    error_type: "storage_error".to_string(),
+                    message: format!("Storage initialization error: {}", e),
+                    details: None,
+                    timestamp: Utc::now(),
+                },
+            }),
+        )
+    })?;
+
+    let url = storage_service
+        .get_photo_url(&photo.file_path)
         .await
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
                     error: ErrorDetails {
-                        error_type: "database_error".to_string(),
-                        message: format!("Failed to retrieve photos: {}", e),
+                        error_type: "storage_error".to_string(),
+                        message: format!("Failed to get photo URL: {}", e),
                         details: None,
                         timestamp: Utc::now(),
                     },
@@ -301,12 +1201,34 @@ pub async fn list_photos(
             )
         })?;
 
-    Ok(Json(photos))
+    let mut response = serde_json::json!({ "url": url });
+
+    // Presigned URLs are only valid for a limited time
+    if let Some(expires_in) = storage_service.photo_url_expires_in() {
+        let expires_at =
+            Utc::now() + chrono::Duration::from_std(expires_in).unwrap_or(chrono::Duration::zero());
+        response["expires_in"] = serde_json::json!(expires_in.as_secs());
+        response["expires_at"] = serde_json::json!(expires_at);
+    }
+
+    Ok(Json(response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/photos/{id}",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Photo id")),
+    responses(
+        (status = 204, description = "Photo and its files deleted"),
+        (status = 404, description = "Photo not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(photo_id = photo_id))]
 pub async fn delete_photo(
     Path(photo_id): Path<i64>,
     State(database): State<Database>,
+    State(storage): State<SharedStorage>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
     // Get photo details before deletion
     let photo = PhotoRepository::delete(&database, photo_id)
@@ -340,21 +1262,7 @@ pub async fn delete_photo(
     })?;
 
     // Initialize storage service and delete the file
-    let config = Config::from_env().map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: ErrorDetails {
-                    error_type: "config_error".to_string(),
-                    message: format!("Configuration error: {}", e),
-                    details: None,
-                    timestamp: Utc::now(),
-                },
-            }),
-        )
-    })?;
-
-    let storage_service = StorageService::new(&config).await.map_err(|e| {
+    let storage_service = storage.get().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -369,9 +1277,49 @@ pub async fn delete_photo(
     })?;
 
     // Delete from storage (log error but don't fail the request if file doesn't exist)
-    if let Err(e) = storage_service.delete_photo(&photo.file_path).await {
-        tracing::warn!("Failed to delete photo file {}: {}", photo.file_path, e);
-    }
+    storage_service
+        .delete_photo_files(std::slice::from_ref(&photo))
+        .await;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    delete,
+    path = "/api/miniatures/{id}/photos",
+    tag = "photos",
+    params(("id" = i64, Path, description = "Miniature id")),
+    responses(
+        (status = 200, description = "Every photo of the miniature and its files deleted", body = crate::openapi::PhotosCleared),
+        (status = 404, description = "Miniature not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(miniature_id = miniature_id))]
+pub async fn delete_miniature_photos(
+    Path(miniature_id): Path<i64>,
+    State(database): State<Database>,
+    State(storage): State<SharedStorage>,
+) -> Result<Json<Value>, UploadError> {
+    ensure_miniature_exists(&database, miniature_id).await?;
+    // Set up storage before touching any rows, so a misconfiguration can't orphan the files
+    let storage_service = upload_storage(&storage).await?;
+
+    let database_error = |e: sqlx::Error| {
+        upload_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            format!("Database error: {}", e),
+        )
+    };
+    let mut transaction = database.begin().await.map_err(database_error)?;
+    let photos = PhotoRepository::delete_by_miniature_id(&mut transaction, miniature_id)
+        .await
+        .map_err(database_error)?;
+    transaction.commit().await.map_err(database_error)?;
+
+    // The rows are gone either way; a file that's already missing only gets a warning
+    storage_service.delete_photo_files(&photos).await;
+    tracing::info!(deleted = photos.len(), "Cleared miniature photos");
+
+    Ok(Json(serde_json::json!({ "deleted": photos.len() })))
+}
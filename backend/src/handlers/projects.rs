@@ -1,38 +1,174 @@
 use crate::{
-    database::Database,
-    error::{AppError, Result},
-    repositories::project_repository::ProjectRepository,
+    config::Config,
+    database::{Database, Executor},
+    error::{AppError, FieldError, Result},
+    handlers::{
+        conditional, created,
+        export::projects_to_csv,
+        idempotency::{idempotency_key, original_gone, original_id, remember},
+        page_bounds, prefers_csv,
+        validation::{collapse_whitespace, too_long, trim_ends, MAX_DESCRIPTION_CHARS},
+        Conditional, Created, Negotiated, Path,
+    },
+    repositories::{
+        project_repository::{ProjectFilter, ProjectSortField},
+        ProjectRepository, SortOrder,
+    },
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Query, State,
+    },
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::Value;
-use shared_types::{CreateProjectRequest, Project, UpdateProjectRequest};
+use shared_types::{
+    CloneProjectRequest, CreateProjectRequest, ErrorResponse, GameSystem, PatchProjectRequest,
+    Project, UpdateProjectRequest,
+};
+use std::sync::Arc;
+use tracing::{instrument, Level};
+use utoipa::IntoParams;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProjectQueryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub sort: Option<ProjectSortField>,
+    pub order: Option<SortOrder>,
+    pub include_deleted: Option<bool>,
+    pub include_archived: Option<bool>,
+    pub tag: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+}
+
+/// Parse an optional RFC 3339 query parameter, naming it in the error
+fn parse_timestamp(name: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|_| {
+                    AppError::ValidationError(format!(
+                        "{} must be an RFC 3339 timestamp such as 2024-01-01T00:00:00Z, got {:?}",
+                        name, value
+                    ))
+                })
+        })
+        .transpose()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/projects",
+    tag = "projects",
+    params(
+        ProjectQueryParams,
+        ("Accept" = Option<String>, Header, description = "`text/csv` for the page as CSV rows instead of JSON"),
+    ),
+    responses(
+        (status = 200, description = "A page of projects, as CSV rows when `Accept` prefers `text/csv`", content(
+            (crate::openapi::ProjectList = "application/json"),
+            (String = "text/csv"),
+        )),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn list_projects(
+    State(database): State<Database>,
+    headers: HeaderMap,
+    params: std::result::Result<Query<ProjectQueryParams>, QueryRejection>,
+) -> Result<Negotiated<Value>> {
+    let Query(params) = params?;
+
+    let (limit, offset) = page_bounds(params.limit, params.offset)?;
+
+    let created_after = parse_timestamp("created_after", params.created_after.as_deref())?;
+    let created_before = parse_timestamp("created_before", params.created_before.as_deref())?;
+    if let (Some(after), Some(before)) = (created_after, created_before) {
+        if after > before {
+            return Err(AppError::ValidationError(
+                "created_after must not be later than created_before".to_string(),
+            ));
+        }
+    }
 
-pub async fn list_projects(State(database): State<Database>) -> Result<Json<Value>> {
-    let projects = ProjectRepository::find_all(&database).await?;
+    let filter = ProjectFilter {
+        include_deleted: params.include_deleted.unwrap_or(false),
+        include_archived: params.include_archived.unwrap_or(false),
+        // A blank tag filter is treated as no filter
+        tag: params.tag.filter(|tag| !tag.trim().is_empty()),
+        created_after,
+        created_before,
+    };
 
-    Ok(Json(serde_json::json!({
-        "projects": projects
-    })))
+    let projects = ProjectRepository::find_page(
+        &database,
+        &filter,
+        limit,
+        offset,
+        params.sort,
+        params.order.unwrap_or_default(),
+    )
+    .await?;
+    if prefers_csv(&headers) {
+        return Ok(Negotiated::Csv(projects_to_csv(&projects)?));
+    }
+    let total = ProjectRepository::count(&database, &filter).await?;
+
+    Ok(Negotiated::Json(Json(serde_json::json!({
+        "count": projects.len(),
+        "projects": projects,
+        "total": total,
+        "limit": limit,
+        "offset": offset
+    }))))
 }
 
+/// Idempotency key scope for project creation
+const PROJECTS_SCOPE: &str = "POST /api/projects";
+
+#[utoipa::path(
+    post,
+    path = "/api/projects",
+    tag = "projects",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Retrying with the same key within a day returns the resource the first request created")),
+    request_body = CreateProjectRequest,
+    responses(
+        (status = 201, description = "Project created", body = Project, headers(("Location" = String, description = "URL of the new project"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 409, description = "With ENFORCE_UNIQUE_PROJECT_NAMES on, a live project already has this name, game system, and army; or the Idempotency-Key is in use by a concurrent request, or its project has been deleted", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
 pub async fn create_project(
     State(database): State<Database>,
-    Json(request): Json<CreateProjectRequest>,
-) -> Result<Json<Project>> {
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    body: std::result::Result<Json<CreateProjectRequest>, JsonRejection>,
+) -> Result<Created<Project>> {
+    let idempotency_key = idempotency_key(&headers)?;
+    let Json(mut request) = body?;
+    collapse_whitespace(&mut request.name);
+    collapse_whitespace(&mut request.army);
+    request.description.iter_mut().for_each(trim_ends);
+
     // Validate required fields - reject empty, whitespace-only, or control-character-only strings
+    let mut errors = Vec::new();
     if request.name.trim().is_empty()
         || !request
             .name
             .chars()
             .any(|c| c.is_alphanumeric() || c.is_ascii_punctuation())
     {
-        return Err(AppError::ValidationError(
-            "Project name is required".to_string(),
-        ));
+        errors.push(FieldError::new("name", "Project name is required"));
     }
 
     if request.army.trim().is_empty()
@@ -41,29 +177,176 @@ pub async fn create_project(
             .chars()
             .any(|c| c.is_alphanumeric() || c.is_ascii_punctuation())
     {
-        return Err(AppError::ValidationError("Army is required".to_string()));
+        errors.push(FieldError::new("army", "Army is required"));
     }
 
-    let project = ProjectRepository::create(&database, request).await?;
-    Ok(Json(project))
+    errors.extend(too_long(
+        "description",
+        request.description.as_deref(),
+        MAX_DESCRIPTION_CHARS,
+    ));
+
+    if !errors.is_empty() {
+        return Err(AppError::FieldValidation(errors));
+    }
+
+    let key = idempotency_key.as_deref();
+    let mut transaction = database.begin().await?;
+    // A retry is answered before the uniqueness check, which its own project would fail
+    if let Some(id) = original_id(&mut transaction, PROJECTS_SCOPE, key).await? {
+        let project = ProjectRepository::find_by_id(&mut transaction, id)
+            .await?
+            .ok_or_else(|| original_gone(key.unwrap_or_default(), "project", id))?;
+        return Ok(created(format!("/api/projects/{}", id), project));
+    }
+    let (name, game_system, army) = (
+        request.name.clone(),
+        request.game_system.clone(),
+        request.army.clone(),
+    );
+    if config.enforce_unique_project_names {
+        check_unique_name(&mut transaction, &name, &game_system, &army).await?;
+    }
+    let project = match ProjectRepository::create(&mut transaction, request).await {
+        Ok(project) => project,
+        Err(e) => {
+            drop(transaction);
+            return Err(name_conflict(&database, e, &name, &game_system, &army).await);
+        }
+    };
+    remember(&mut transaction, PROJECTS_SCOPE, key, project.id).await?;
+    transaction.commit().await?;
+
+    tracing::info!(project_id = project.id, "Project created");
+    Ok(created(format!("/api/projects/{}", project.id), project))
 }
 
+/// Refuse a new project whose name, game system, and army match a live project's, ignoring
+/// case. Installs that already hold duplicates can't get the unique index behind this, and
+/// there concurrent requests can still both pass it.
+pub async fn check_unique_name(
+    executor: impl Into<Executor<'_>>,
+    name: &str,
+    game_system: &GameSystem,
+    army: &str,
+) -> Result<()> {
+    match ProjectRepository::find_duplicate(executor, name, game_system, army).await? {
+        Some(project_id) => Err(AppError::ConflictWithDetails(
+            duplicate_message(name, army),
+            serde_json::json!({ "project_id": project_id }),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The error for a failed project write. One that hit the unique name index, because another
+/// request made the same project after `check_unique_name` passed, gets the same 409.
+pub async fn name_conflict(
+    database: &Database,
+    error: sqlx::Error,
+    name: &str,
+    game_system: &GameSystem,
+    army: &str,
+) -> AppError {
+    if !ProjectRepository::is_duplicate_name(&error) {
+        return error.into();
+    }
+    match check_unique_name(database, name, game_system, army).await {
+        Err(conflict) => conflict,
+        // The other project was deleted again in the meantime
+        Ok(()) => AppError::Conflict(duplicate_message(name, army)),
+    }
+}
+
+fn duplicate_message(name: &str, army: &str) -> String {
+    format!(
+        "Project {:?} already exists for {} in this game system",
+        name, army
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id"), ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; unchanged resources answer 304")),
+    responses(
+        (status = 200, description = "The project", body = Project, headers(("ETag" = String, description = "Changes whenever the project does"))),
+        (status = 304, description = "The project is unchanged since the given ETag"),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
 pub async fn get_project(
     State(database): State<Database>,
     Path(id): Path<i64>,
-) -> Result<Json<Project>> {
+    headers: HeaderMap,
+) -> Result<Conditional<Project>> {
     let project = ProjectRepository::find_by_id(&database, id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", id)))?;
 
-    Ok(Json(project))
+    Ok(conditional(&headers, project.updated_at, project))
 }
 
+/// Replace every editable field of a project
+#[utoipa::path(
+    put,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = UpdateProjectRequest,
+    responses(
+        (status = 200, description = "The updated project", body = Project),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+        (status = 409, description = "The project changed since the given updated_at, or with ENFORCE_UNIQUE_PROJECT_NAMES on, another live project has the resulting name, game system, and army", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
 pub async fn update_project(
     State(database): State<Database>,
     Path(id): Path<i64>,
-    Json(request): Json<UpdateProjectRequest>,
+    body: std::result::Result<Json<UpdateProjectRequest>, JsonRejection>,
 ) -> Result<Json<Project>> {
+    let Json(request) = body?;
+    apply_project_update(&database, id, request.into()).await
+}
+
+/// Change only the fields present in the request; `"description": null` clears the description
+#[utoipa::path(
+    patch,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = PatchProjectRequest,
+    responses(
+        (status = 200, description = "The updated project", body = Project),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+        (status = 409, description = "The project changed since the given updated_at, or with ENFORCE_UNIQUE_PROJECT_NAMES on, another live project has the resulting name, game system, and army", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
+pub async fn patch_project(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<PatchProjectRequest>, JsonRejection>,
+) -> Result<Json<Project>> {
+    let Json(request) = body?;
+    apply_project_update(&database, id, request).await
+}
+
+/// Validate and apply an update, telling an edit that lost a race apart from a missing project
+async fn apply_project_update(
+    database: &Database,
+    id: i64,
+    mut request: PatchProjectRequest,
+) -> Result<Json<Project>> {
+    request.name.iter_mut().for_each(collapse_whitespace);
+    request.army.iter_mut().for_each(collapse_whitespace);
+    request.description.iter_mut().flatten().for_each(trim_ends);
+
     // Validate fields if provided
     if let Some(ref name) = request.name {
         if name.trim().is_empty()
@@ -89,13 +372,147 @@ pub async fn update_project(
         }
     }
 
-    let project = ProjectRepository::update(&database, id, request)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", id)))?;
+    if let Some(error) = too_long(
+        "description",
+        request.description.as_ref().and_then(Option::as_deref),
+        MAX_DESCRIPTION_CHARS,
+    ) {
+        return Err(AppError::FieldValidation(vec![error]));
+    }
 
+    let expected_updated_at = request.updated_at;
+    let (name, game_system, army) = (
+        request.name.clone(),
+        request.game_system.clone(),
+        request.army.clone(),
+    );
+    let updated = match ProjectRepository::update(database, id, request).await {
+        Ok(updated) => updated,
+        Err(e) if ProjectRepository::is_duplicate_name(&e) => {
+            // Renamed onto another live project; report it with the fields it would have had
+            let current = ProjectRepository::find_by_id(database, id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", id)))?;
+            return Err(name_conflict(
+                database,
+                e,
+                name.as_deref().unwrap_or(&current.name),
+                game_system.as_ref().unwrap_or(&current.game_system),
+                army.as_deref().unwrap_or(&current.army),
+            )
+            .await);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let Some(project) = updated else {
+        // A guarded update that missed an existing project lost to someone else's edit
+        if let Some(expected) = expected_updated_at {
+            if ProjectRepository::find_by_id(database, id).await?.is_some() {
+                return Err(AppError::Conflict(format!(
+                    "Project with id {} has changed since {}; reload it and try again",
+                    id,
+                    expected.to_rfc3339()
+                )));
+            }
+        }
+        return Err(AppError::NotFound(format!(
+            "Project with id {} not found",
+            id
+        )));
+    };
+
+    Ok(Json(project))
+}
+
+/// Copy a project and its miniatures as a starting template for a new one
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/clone",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    request_body = Option<CloneProjectRequest>,
+    responses(
+        (status = 200, description = "The new copy", body = Project),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+        (status = 409, description = "With ENFORCE_UNIQUE_PROJECT_NAMES on, a live project already has the copy's name, game system, and army", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
+pub async fn clone_project(
+    State(database): State<Database>,
+    State(config): State<Arc<Config>>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<CloneProjectRequest>, JsonRejection>,
+) -> Result<Json<Project>> {
+    let mut request = match body {
+        Ok(Json(request)) => request,
+        // No body at all means "use the defaults"
+        Err(JsonRejection::MissingJsonContentType(_)) => CloneProjectRequest::default(),
+        Err(rejection) => return Err(rejection.into()),
+    };
+    request.name.iter_mut().for_each(collapse_whitespace);
+
+    if let Some(ref name) = request.name {
+        if name.trim().is_empty()
+            || !name
+                .chars()
+                .any(|c| c.is_alphanumeric() || c.is_ascii_punctuation())
+        {
+            return Err(AppError::ValidationError(
+                "Project name cannot be empty".to_string(),
+            ));
+        }
+    }
+
+    let not_found = || AppError::NotFound(format!("Project with id {} not found", id));
+    // With unique names enforced, the copy is checked like any new project
+    let source = match config.enforce_unique_project_names {
+        true => {
+            let source = ProjectRepository::find_by_id(&database, id)
+                .await?
+                .ok_or_else(not_found)?;
+            let name = request
+                .name
+                .get_or_insert_with(|| format!("{} (Copy)", source.name));
+            check_unique_name(&database, name, &source.game_system, &source.army).await?;
+            Some(source)
+        }
+        false => None,
+    };
+
+    let cloned = ProjectRepository::clone_project(
+        &database,
+        id,
+        request.name.clone(),
+        request.reset_progress.unwrap_or(true),
+    )
+    .await;
+    let project = match (cloned, source, request.name) {
+        (Ok(project), _, _) => project.ok_or_else(not_found)?,
+        (Err(e), Some(source), Some(name)) => {
+            return Err(name_conflict(&database, e, &name, &source.game_system, &source.army).await)
+        }
+        (Err(e), _, _) => return Err(e.into()),
+    };
+
+    tracing::info!(clone_id = project.id, "Project cloned");
     Ok(Json(project))
 }
 
+/// Soft-delete a project. Its miniatures and photos stay in place, files included, so that
+/// `restore_project` can bring them all back.
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 204, description = "Project soft-deleted"),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
 pub async fn delete_project(
     State(database): State<Database>,
     Path(id): Path<i64>,
@@ -111,3 +528,80 @@ pub async fn delete_project(
         )))
     }
 }
+
+/// Bring back a soft-deleted project along with its miniatures and photos
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/restore",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The restored project", body = Project),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+        (status = 409, description = "With ENFORCE_UNIQUE_PROJECT_NAMES on, a live project has the same name, game system, and army", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
+pub async fn restore_project(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Project>> {
+    let project = ProjectRepository::restore(&database, id)
+        .await
+        .map_err(|e| match ProjectRepository::is_duplicate_name(&e) {
+            true => AppError::Conflict(format!(
+                "Project with id {} can't be restored while a live project has its name, game system, and army",
+                id
+            )),
+            false => e.into(),
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", id)))?;
+
+    Ok(Json(project))
+}
+
+/// Move a finished project out of the default projects list without deleting it
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/archive",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The archived project", body = Project),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
+pub async fn archive_project(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Project>> {
+    set_archived(&database, id, true).await
+}
+
+/// Bring an archived project back into the default projects list
+#[utoipa::path(
+    post,
+    path = "/api/projects/{id}/unarchive",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The unarchived project", body = Project),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = id), err(level = Level::INFO))]
+pub async fn unarchive_project(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Project>> {
+    set_archived(&database, id, false).await
+}
+
+async fn set_archived(database: &Database, id: i64, archived: bool) -> Result<Json<Project>> {
+    let project = ProjectRepository::set_archived(database, id, archived)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", id)))?;
+
+    Ok(Json(project))
+}
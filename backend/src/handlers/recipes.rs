@@ -1,68 +1,225 @@
 use crate::{
     database::Database,
-    error::{AppError, Result},
-    repositories::recipe_repository::RecipeRepository,
+    error::{AppError, FieldError, Result},
+    handlers::{
+        conditional, created,
+        idempotency::{idempotency_key, original_gone, original_id, remember},
+        validation::{collapse_whitespace, step_errors, too_long, trim_ends, MAX_NOTES_CHARS},
+        Conditional, Created, Path,
+    },
+    repositories::{MiniatureRecipeRepository, RecipeRepository},
 };
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Query, State,
+    },
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::Value;
-use shared_types::{CreateRecipeRequest, MiniatureType, PaintingRecipe, UpdateRecipeRequest};
+use shared_types::{
+    CreateRecipeRequest, DuplicateRecipeRequest, ErrorResponse, MiniatureType, PaintingRecipe,
+    ReorderRecipeStepsRequest, UpdateRecipeRequest,
+};
+use std::collections::HashSet;
+use tracing::{instrument, Level};
+use utoipa::IntoParams;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct RecipeQueryParams {
     #[serde(rename = "type")]
     pub miniature_type: Option<MiniatureType>,
+    /// Free-text search across names, notes, steps, paints, and techniques
+    pub q: Option<String>,
+    /// Only recipes linked to a miniature in this project
+    pub project_id: Option<i64>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/recipes",
+    tag = "recipes",
+    params(RecipeQueryParams),
+    responses(
+        (status = 200, description = "Matching recipes", body = crate::openapi::RecipeList),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
 pub async fn list_recipes(
     State(database): State<Database>,
     Query(params): Query<RecipeQueryParams>,
 ) -> Result<Json<Value>> {
-    let recipes = match params.miniature_type {
-        Some(miniature_type) => RecipeRepository::find_by_type(&database, miniature_type).await?,
-        None => RecipeRepository::find_all(&database).await?,
+    // A blank search is the same as no search
+    let search = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+
+    let recipes = match (search, params.project_id, params.miniature_type) {
+        (Some(q), None, miniature_type) => {
+            RecipeRepository::search(&database, q, miniature_type).await?
+        }
+        (Some(q), Some(project_id), miniature_type) => {
+            // Search first, then keep the matches the project uses
+            let linked: HashSet<i64> =
+                RecipeRepository::find_by_project(&database, project_id, miniature_type.clone())
+                    .await?
+                    .into_iter()
+                    .map(|recipe| recipe.id)
+                    .collect();
+            let mut recipes = RecipeRepository::search(&database, q, miniature_type).await?;
+            recipes.retain(|recipe| linked.contains(&recipe.id));
+            recipes
+        }
+        (None, Some(project_id), miniature_type) => {
+            RecipeRepository::find_by_project(&database, project_id, miniature_type).await?
+        }
+        (None, None, Some(miniature_type)) => {
+            RecipeRepository::find_by_type(&database, miniature_type).await?
+        }
+        (None, None, None) => RecipeRepository::find_all(&database).await?,
     };
 
     Ok(Json(serde_json::json!({
+        "count": recipes.len(),
         "recipes": recipes
     })))
 }
 
+/// Idempotency key scope for recipe creation
+const RECIPES_SCOPE: &str = "POST /api/recipes";
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes",
+    tag = "recipes",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Retrying with the same key within a day returns the resource the first request created")),
+    request_body = CreateRecipeRequest,
+    responses(
+        (status = 201, description = "Recipe created", body = PaintingRecipe, headers(("Location" = String, description = "URL of the new recipe"))),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 409, description = "The Idempotency-Key is in use by a concurrent request, or its recipe has been deleted", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
 pub async fn create_recipe(
     State(database): State<Database>,
-    Json(request): Json<CreateRecipeRequest>,
-) -> Result<Json<PaintingRecipe>> {
-    // Validate required fields
+    headers: HeaderMap,
+    body: std::result::Result<Json<CreateRecipeRequest>, JsonRejection>,
+) -> Result<Created<PaintingRecipe>> {
+    let idempotency_key = idempotency_key(&headers)?;
+    let Json(mut request) = body?;
+    normalize_create_recipe(&mut request);
+    validate_create_recipe(&request)?;
+
+    let key = idempotency_key.as_deref();
+    let mut transaction = database.begin().await?;
+    if let Some(id) = original_id(&mut transaction, RECIPES_SCOPE, key).await? {
+        let recipe = RecipeRepository::find_by_id(&mut transaction, id)
+            .await?
+            .ok_or_else(|| original_gone(key.unwrap_or_default(), "recipe", id))?;
+        return Ok(created(format!("/api/recipes/{}", id), recipe));
+    }
+    let recipe = RecipeRepository::create(&mut transaction, request).await?;
+    remember(&mut transaction, RECIPES_SCOPE, key, recipe.id).await?;
+    transaction.commit().await?;
+
+    tracing::info!(recipe_id = recipe.id, "Recipe created");
+    Ok(created(format!("/api/recipes/{}", recipe.id), recipe))
+}
+
+/// Check a recipe against the rules for creating one without saving anything, so forms can
+/// show problems as they are typed
+#[utoipa::path(
+    post,
+    path = "/api/recipes/validate",
+    tag = "recipes",
+    request_body = CreateRecipeRequest,
+    responses(
+        (status = 200, description = "The recipe would be accepted", body = crate::openapi::RecipeValidation),
+        (status = 400, description = "Every field that would be rejected", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn validate_recipe(
+    body: std::result::Result<Json<CreateRecipeRequest>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Json(mut request) = body?;
+    normalize_create_recipe(&mut request);
+    validate_create_recipe(&request)?;
+
+    Ok(Json(serde_json::json!({ "valid": true })))
+}
+
+/// Tidy whitespace the way a new recipe is stored, before it is validated
+fn normalize_create_recipe(request: &mut CreateRecipeRequest) {
+    collapse_whitespace(&mut request.name);
+    request.notes.iter_mut().for_each(trim_ends);
+}
+
+/// Check a new recipe against every rule, reporting all the invalid fields together
+pub fn validate_create_recipe(request: &CreateRecipeRequest) -> Result<()> {
+    let mut errors = Vec::new();
     if request.name.trim().is_empty() {
-        return Err(AppError::ValidationError(
-            "Recipe name is required".to_string(),
-        ));
+        errors.push(FieldError::new("name", "Recipe name is required"));
     }
+    errors.extend(step_errors("steps", &request.steps));
+    errors.extend(too_long("notes", request.notes.as_deref(), MAX_NOTES_CHARS));
 
-    let recipe = RecipeRepository::create(&database, request).await?;
-    Ok(Json(recipe))
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::FieldValidation(errors))
+    }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id"), ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; unchanged resources answer 304")),
+    responses(
+        (status = 200, description = "The recipe", body = PaintingRecipe, headers(("ETag" = String, description = "Changes whenever the recipe does"))),
+        (status = 304, description = "The recipe is unchanged since the given ETag"),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
 pub async fn get_recipe(
     State(database): State<Database>,
     Path(id): Path<i64>,
-) -> Result<Json<PaintingRecipe>> {
+    headers: HeaderMap,
+) -> Result<Conditional<PaintingRecipe>> {
     let recipe = RecipeRepository::find_by_id(&database, id)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", id)))?;
 
-    Ok(Json(recipe))
+    Ok(conditional(&headers, recipe.updated_at, recipe))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/recipes/{id}",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id")),
+    request_body = UpdateRecipeRequest,
+    responses(
+        (status = 200, description = "The updated recipe", body = PaintingRecipe),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
 pub async fn update_recipe(
     State(database): State<Database>,
     Path(id): Path<i64>,
-    Json(request): Json<UpdateRecipeRequest>,
+    body: std::result::Result<Json<UpdateRecipeRequest>, JsonRejection>,
 ) -> Result<Json<PaintingRecipe>> {
+    let Json(mut request) = body?;
+    request.name.iter_mut().for_each(collapse_whitespace);
+    request.notes.iter_mut().for_each(trim_ends);
+
     // Validate fields if provided
     if let Some(ref name) = request.name {
         if name.trim().is_empty() {
@@ -72,6 +229,15 @@ pub async fn update_recipe(
         }
     }
 
+    let mut errors = Vec::new();
+    if let Some(ref steps) = request.steps {
+        errors.extend(step_errors("steps", steps));
+    }
+    errors.extend(too_long("notes", request.notes.as_deref(), MAX_NOTES_CHARS));
+    if !errors.is_empty() {
+        return Err(AppError::FieldValidation(errors));
+    }
+
     let recipe = RecipeRepository::update(&database, id, request)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", id)))?;
@@ -79,11 +245,138 @@ pub async fn update_recipe(
     Ok(Json(recipe))
 }
 
+/// Create a new recipe from an existing one, optionally under a new name
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/duplicate",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id")),
+    request_body = Option<DuplicateRecipeRequest>,
+    responses(
+        (status = 200, description = "The new copy", body = PaintingRecipe),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
+pub async fn duplicate_recipe(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<DuplicateRecipeRequest>, JsonRejection>,
+) -> Result<Json<PaintingRecipe>> {
+    let mut request = match body {
+        Ok(Json(request)) => request,
+        // No body at all means "use the defaults"
+        Err(JsonRejection::MissingJsonContentType(_)) => DuplicateRecipeRequest::default(),
+        Err(rejection) => return Err(rejection.into()),
+    };
+    request.name.iter_mut().for_each(collapse_whitespace);
+
+    let source = RecipeRepository::find_by_id(&database, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", id)))?;
+
+    let name = match request.name {
+        Some(name) if name.is_empty() => {
+            return Err(AppError::ValidationError(
+                "Recipe name cannot be empty".to_string(),
+            ));
+        }
+        Some(name) => name,
+        None => format!("{} (Copy)", source.name),
+    };
+
+    let recipe = RecipeRepository::create(
+        &database,
+        CreateRecipeRequest {
+            name,
+            miniature_type: source.miniature_type,
+            steps: source.steps,
+            paints_used: source.paints_used,
+            techniques: source.techniques,
+            notes: source.notes,
+        },
+    )
+    .await?;
+
+    tracing::info!(duplicate_id = recipe.id, "Recipe duplicated");
+    Ok(Json(recipe))
+}
+
+/// Move a recipe's steps into a new order given as indices of the current steps
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/steps/reorder",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id")),
+    request_body = ReorderRecipeStepsRequest,
+    responses(
+        (status = 200, description = "The recipe with its steps reordered", body = PaintingRecipe),
+        (status = 400, description = "Order is not a permutation of the steps", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
+pub async fn reorder_recipe_steps(
+    State(database): State<Database>,
+    Path(id): Path<i64>,
+    body: std::result::Result<Json<ReorderRecipeStepsRequest>, JsonRejection>,
+) -> Result<Json<PaintingRecipe>> {
+    let Json(request) = body?;
+    let recipe = RecipeRepository::reorder_steps(&database, id, &request.order)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Recipe with id {} not found", id)))?;
+
+    Ok(Json(recipe))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteRecipeParams {
+    /// Delete the recipe even while miniatures use it, unlinking them
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Delete a recipe. A recipe still linked to miniatures is kept, with a 409 giving the
+/// `miniature_count` in its details, unless `force` is set.
+#[utoipa::path(
+    delete,
+    path = "/api/recipes/{id}",
+    tag = "recipes",
+    params(("id" = i64, Path, description = "Recipe id"), DeleteRecipeParams),
+    responses(
+        (status = 204, description = "Recipe deleted"),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 409, description = "Miniatures still use the recipe", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(recipe_id = id), err(level = Level::INFO))]
 pub async fn delete_recipe(
     State(database): State<Database>,
     Path(id): Path<i64>,
+    params: std::result::Result<Query<DeleteRecipeParams>, QueryRejection>,
 ) -> Result<StatusCode> {
-    let deleted = RecipeRepository::delete(&database, id).await?;
+    let Query(params) = params?;
+
+    // Count and delete together so a link made in between can't be dropped unwarned
+    let mut transaction = database.begin().await?;
+    let miniature_count =
+        MiniatureRecipeRepository::count_miniatures_for_recipe(&mut transaction, id).await?;
+    if miniature_count > 0 && !params.force {
+        return Err(AppError::ConflictWithDetails(
+            format!(
+                "Recipe {} is used by {} miniature(s); pass force=true to delete it anyway",
+                id, miniature_count
+            ),
+            serde_json::json!({ "miniature_count": miniature_count }),
+        ));
+    }
+
+    // Links go with the recipe through the foreign key cascade
+    let deleted = RecipeRepository::delete(&mut transaction, id).await?;
+    transaction.commit().await?;
 
     if deleted {
         Ok(StatusCode::NO_CONTENT)
@@ -0,0 +1,176 @@
+use crate::{
+    database::Database,
+    error::{AppError, Result},
+    handlers::Path,
+    repositories::{MiniatureRepository, ProjectRepository, RecipeRepository},
+};
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use shared_types::{ErrorResponse, GameSystem, MiniatureType, ProgressStatus};
+use tracing::{instrument, Level};
+use utoipa::IntoParams;
+
+/// Get completion statistics for a project's miniatures
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/stats",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "Completion statistics", body = crate::openapi::ProjectStats),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn get_project_stats(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>> {
+    // Verify project exists
+    ProjectRepository::find_by_id(&database, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    let mut status_counts = MiniatureRepository::count_by_status(&database, project_id).await?;
+
+    // Report every status, including those with no miniatures
+    for status in ProgressStatus::all() {
+        status_counts.entry(status.clone()).or_insert(0);
+    }
+
+    let total_points: i64 = MiniatureRepository::points_by_type(&database, project_id)
+        .await?
+        .values()
+        .sum();
+
+    let total: i64 = status_counts.values().sum();
+    let completed: i64 = status_counts
+        .iter()
+        .filter(|(status, _)| status.is_done())
+        .map(|(_, count)| count)
+        .sum();
+    let completion_percentage = if total == 0 {
+        0.0
+    } else {
+        completed as f64 / total as f64 * 100.0
+    };
+
+    Ok(Json(serde_json::json!({
+        "project_id": project_id,
+        "total_miniatures": total,
+        "status_counts": status_counts,
+        "completion_percentage": completion_percentage,
+        "total_points": total_points
+    })))
+}
+
+/// Get a project's total points, broken down by miniature type
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/points",
+    tag = "projects",
+    params(("id" = i64, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "Points totals", body = crate::openapi::ProjectPoints),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(project_id = project_id), err(level = Level::INFO))]
+pub async fn get_project_points(
+    State(database): State<Database>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<Value>> {
+    ProjectRepository::find_by_id(&database, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with id {} not found", project_id)))?;
+
+    let mut points_by_type = MiniatureRepository::points_by_type(&database, project_id).await?;
+
+    // Report every type, including those with no miniatures
+    for miniature_type in MiniatureType::all() {
+        points_by_type.entry(miniature_type.clone()).or_insert(0);
+    }
+
+    Ok(Json(serde_json::json!({
+        "project_id": project_id,
+        "total_points": points_by_type.values().sum::<i64>(),
+        "points_by_type": points_by_type
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaintStatsQueryParams {
+    pub project_id: Option<i64>,
+}
+
+/// Get how many recipes use each paint, optionally limited to one project's recipes
+#[utoipa::path(
+    get,
+    path = "/api/stats/paints",
+    tag = "recipes",
+    params(PaintStatsQueryParams),
+    responses(
+        (status = 200, description = "Recipe counts per paint", body = crate::openapi::PaintStats),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 404, description = "Project not found", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn get_paint_stats(
+    State(database): State<Database>,
+    params: std::result::Result<Query<PaintStatsQueryParams>, QueryRejection>,
+) -> Result<Json<Value>> {
+    let Query(params) = params?;
+
+    if let Some(project_id) = params.project_id {
+        ProjectRepository::find_by_id(&database, project_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Project with id {} not found", project_id))
+            })?;
+    }
+
+    let paints = RecipeRepository::aggregate_paints(&database, params.project_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "project_id": params.project_id,
+        "paints": paints
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ArmyQueryParams {
+    pub game_system: Option<GameSystem>,
+}
+
+/// List the army names projects already use, for autocompleting new projects consistently
+#[utoipa::path(
+    get,
+    path = "/api/armies",
+    tag = "projects",
+    params(ArmyQueryParams),
+    responses(
+        (status = 200, description = "Army names in use, alphabetically, with project counts", body = crate::openapi::ArmyCatalogue),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, err(level = Level::INFO))]
+pub async fn list_armies(
+    State(database): State<Database>,
+    params: std::result::Result<Query<ArmyQueryParams>, QueryRejection>,
+) -> Result<Json<Value>> {
+    let Query(params) = params?;
+
+    let armies = ProjectRepository::army_usage(&database, params.game_system.as_ref()).await?;
+
+    Ok(Json(serde_json::json!({
+        "game_system": params.game_system,
+        "armies": armies
+    })))
+}
@@ -0,0 +1,65 @@
+use crate::error::FieldError;
+
+/// Longest `notes` on a miniature or recipe, in characters
+pub const MAX_NOTES_CHARS: usize = 2000;
+/// Longest project `description`, in characters
+pub const MAX_DESCRIPTION_CHARS: usize = 5000;
+/// Longest single recipe step, in characters
+pub const MAX_STEP_CHARS: usize = 500;
+/// Most steps a recipe may have
+pub const MAX_RECIPE_STEPS: usize = 100;
+
+/// An error for `field` when `value` is longer than `max` characters. The message names the
+/// field and the limit so it reads on its own.
+pub fn too_long(field: &str, value: Option<&str>, max: usize) -> Option<FieldError> {
+    let length = value?.chars().count();
+    (length > max).then(|| {
+        FieldError::new(
+            field,
+            &format!(
+                "{} must be at most {} characters (got {})",
+                field, max, length
+            ),
+        )
+    })
+}
+
+/// Errors for a recipe's steps: too many of them, and each one that is too long
+pub fn step_errors(field: &str, steps: &[String]) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if steps.len() > MAX_RECIPE_STEPS {
+        errors.push(FieldError::new(
+            field,
+            &format!(
+                "{} can have at most {} entries (got {})",
+                field,
+                MAX_RECIPE_STEPS,
+                steps.len()
+            ),
+        ));
+    }
+    for (index, step) in steps.iter().enumerate() {
+        errors.extend(too_long(
+            &format!("{}[{}]", field, index),
+            Some(step),
+            MAX_STEP_CHARS,
+        ));
+    }
+
+    errors
+}
+
+/// Trim a single-line field such as a name and collapse every inner run of whitespace, line
+/// breaks included, to one space
+pub fn collapse_whitespace(value: &mut String) {
+    *value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+}
+
+/// Trim the ends of free text such as notes, keeping its inner line breaks and spacing
+pub fn trim_ends(value: &mut String) {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+}
@@ -1,26 +1,36 @@
 use axum::{
-    http::StatusCode,
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, Method, StatusCode},
     middleware,
-    response::Json,
-    routing::{delete, get, post, put},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::net::SocketAddr;
+use std::time::Duration;
 use tower::ServiceBuilder;
 use tower_http::{
     cors::CorsLayer,
+    limit::RequestBodyLimitLayer,
     request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
-    trace::TraceLayer,
+    services::ServeDir,
+    trace::{DefaultOnResponse, TraceLayer},
 };
+use tracing::Level;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 mod config;
 mod database;
 mod error;
 mod handlers;
+mod openapi;
 mod repositories;
 mod services;
+mod state;
 mod storage;
+mod telemetry;
 
 #[cfg(test)]
 mod tests;
@@ -29,7 +39,16 @@ mod tests;
 mod integration_tests;
 
 use config::Config;
-use database::Database;
+use database::{Database, DatabaseConfig};
+use services::storage_service::{SharedStorage, StorageService};
+use state::AppState;
+use std::sync::Arc;
+
+/// Request id header set and propagated by the middleware stack
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// First wait between database connection attempts at startup; doubles each retry
+const DATABASE_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -45,13 +64,56 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = Config::from_env()?;
+    config.validate()?;
+    config.log_summary();
 
-    // Initialize database
-    let database = Database::new(&config.database_url).await?;
+    // Initialize database, waiting for it to come up if the service started first
+    let database = Database::new_with_retry(
+        &config.database_url,
+        DatabaseConfig::default(),
+        config.database_connect_attempts,
+        DATABASE_RETRY_BASE_DELAY,
+    )
+    .await?;
+    let database = match &config.database_replica_url {
+        Some(replica_url) => {
+            database
+                .with_read_replica(replica_url, DatabaseConfig::default())
+                .await?
+        }
+        None => database,
+    };
 
     // Run migrations
     database.migrate().await?;
 
+    // Back ENFORCE_UNIQUE_PROJECT_NAMES with a unique index, so concurrent creates can't both
+    // pass the check. Existing duplicates keep it from being added; the check still runs.
+    if let Err(e) = repositories::ProjectRepository::set_unique_name_index(
+        &database,
+        config.enforce_unique_project_names,
+    )
+    .await
+    {
+        tracing::warn!(
+            "Could not update the unique project name index; resolve duplicate projects and restart: {}",
+            e
+        );
+    }
+
+    // Seed demo data into a fresh install when asked to, by config or `--seed`
+    if config.seed_on_start || std::env::args().any(|arg| arg == "--seed") {
+        match database::seed::seed_if_empty(&database).await? {
+            Some(summary) => tracing::info!(
+                projects = summary.projects,
+                miniatures = summary.miniatures,
+                recipes = summary.recipes,
+                "Seeded demo data"
+            ),
+            None => tracing::info!("Projects already exist; skipping demo data"),
+        }
+    }
+
     // Perform initial health check
     database.health_check().await.map_err(|e| {
         tracing::error!("Database health check failed: {}", e);
@@ -59,17 +121,130 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     })?;
     tracing::info!("Database health check passed");
 
+    // Set up storage once; requests and readiness probes all use this client
+    let storage = StorageService::new(&config).await.map_err(|e| {
+        tracing::error!("Storage initialization failed: {}", e);
+        e.to_string()
+    })?;
+    let storage = SharedStorage::with_service(Arc::new(config.clone()), storage);
+
     // Build our application with routes and middleware
-    let app = Router::new()
-        .route("/", get(health_check))
+    let mut app = create_router_with_storage(database.clone(), &config, storage);
+
+    // Serve locally stored files so URLs from /api/photos/:id/url resolve
+    if let config::StorageType::Local = config.storage_type {
+        let uploads_path = config
+            .local_storage_path
+            .clone()
+            .unwrap_or_else(|| "./uploads".to_string());
+        app = app.nest_service("/uploads", ServeDir::new(uploads_path));
+    }
+
+    // Run the server
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    tracing::info!("Server listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    // Stop accepting connections on SIGINT/SIGTERM and let in-flight requests drain
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    tracing::info!("All requests drained");
+    database.close().await;
+
+    Ok(())
+}
+
+/// Resolve once the process is asked to stop, either by Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received SIGINT, shutting down"),
+        _ = terminate => tracing::info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// The application router with storage set up on first use, for tests
+#[cfg(test)]
+fn create_router(database: Database, config: &Config) -> Router {
+    let storage = SharedStorage::new(Arc::new(config.clone()));
+    create_router_with_storage(database, config, storage)
+}
+
+/// Build the application router with all API routes and middleware
+fn create_router_with_storage(
+    database: Database,
+    config: &Config,
+    storage: SharedStorage,
+) -> Router {
+    let router = Router::new()
+        // `/` predates the liveness/readiness split and keeps answering as readiness
+        .route("/", get(readiness))
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
         .route("/api/projects", get(handlers::projects::list_projects))
         .route("/api/projects", post(handlers::projects::create_project))
+        .route(
+            "/api/projects/import",
+            post(handlers::export::import_project),
+        )
         .route("/api/projects/:id", get(handlers::projects::get_project))
         .route("/api/projects/:id", put(handlers::projects::update_project))
+        .route(
+            "/api/projects/:id",
+            patch(handlers::projects::patch_project),
+        )
         .route(
             "/api/projects/:id",
             delete(handlers::projects::delete_project),
         )
+        .route(
+            "/api/projects/:id/clone",
+            post(handlers::projects::clone_project),
+        )
+        .route(
+            "/api/projects/:id/restore",
+            post(handlers::projects::restore_project),
+        )
+        .route(
+            "/api/projects/:id/archive",
+            post(handlers::projects::archive_project),
+        )
+        .route(
+            "/api/projects/:id/unarchive",
+            post(handlers::projects::unarchive_project),
+        )
+        .route(
+            "/api/projects/:id/export",
+            get(handlers::export::export_project),
+        )
+        .route("/api/stats/paints", get(handlers::stats::get_paint_stats))
+        .route("/api/armies", get(handlers::stats::list_armies))
+        .route(
+            "/api/projects/:id/stats",
+            get(handlers::stats::get_project_stats),
+        )
+        .route(
+            "/api/projects/:id/points",
+            get(handlers::stats::get_project_points),
+        )
         .route(
             "/api/projects/:id/miniatures",
             get(handlers::miniatures::list_miniatures),
@@ -78,6 +253,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/projects/:id/miniatures",
             post(handlers::miniatures::create_miniature),
         )
+        .route(
+            "/api/projects/:id/miniatures",
+            delete(handlers::miniatures::bulk_delete_miniatures),
+        )
+        .route(
+            "/api/projects/:id/miniatures/batch",
+            post(handlers::miniatures::bulk_create_miniatures),
+        )
+        .route(
+            "/api/projects/:id/miniatures/:miniature_id",
+            get(handlers::miniatures::get_project_miniature),
+        )
+        .route(
+            "/api/projects/:id/miniatures/status",
+            patch(handlers::miniatures::bulk_update_status),
+        )
+        .route(
+            "/api/projects/:id/miniatures.csv",
+            get(handlers::export::export_miniatures_csv),
+        )
+        .route(
+            "/api/miniatures",
+            get(handlers::miniatures::search_miniatures),
+        )
         .route(
             "/api/miniatures/:id",
             get(handlers::miniatures::get_miniature),
@@ -86,75 +285,247 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api/miniatures/:id",
             put(handlers::miniatures::update_miniature),
         )
+        .route(
+            "/api/miniatures/:id",
+            patch(handlers::miniatures::patch_miniature),
+        )
         .route(
             "/api/miniatures/:id",
             delete(handlers::miniatures::delete_miniature),
         )
+        .route(
+            "/api/miniatures/:id/history",
+            get(handlers::miniatures::get_miniature_history),
+        )
+        .route(
+            "/api/miniatures/:id/move",
+            patch(handlers::miniatures::move_miniature),
+        )
         .route("/api/recipes", get(handlers::recipes::list_recipes))
         .route("/api/recipes", post(handlers::recipes::create_recipe))
+        .route(
+            "/api/recipes/validate",
+            post(handlers::recipes::validate_recipe),
+        )
         .route("/api/recipes/:id", get(handlers::recipes::get_recipe))
         .route("/api/recipes/:id", put(handlers::recipes::update_recipe))
         .route("/api/recipes/:id", delete(handlers::recipes::delete_recipe))
         .route(
-            "/api/miniatures/:id/photos",
-            post(handlers::photos::upload_photo),
+            "/api/recipes/:id/duplicate",
+            post(handlers::recipes::duplicate_recipe),
+        )
+        .route(
+            "/api/recipes/:id/steps/reorder",
+            post(handlers::recipes::reorder_recipe_steps),
+        )
+        .route(
+            "/api/recipes/:id/missing-paints",
+            get(handlers::paints::get_missing_paints),
         )
+        .route("/api/paints", get(handlers::paints::list_paints))
+        .route("/api/paints", post(handlers::paints::create_paint))
+        .route("/api/paints/:id", get(handlers::paints::get_paint))
+        .route("/api/paints/:id", put(handlers::paints::update_paint))
+        .route("/api/paints/:id", delete(handlers::paints::delete_paint))
         .route(
             "/api/miniatures/:id/photos",
-            get(handlers::photos::list_photos),
+            get(handlers::photos::list_photos).delete(handlers::photos::delete_miniature_photos),
         )
+        .route("/api/photos/:id", get(handlers::photos::get_photo))
         .route("/api/photos/:id", delete(handlers::photos::delete_photo))
+        .route(
+            "/api/photos/:id/content",
+            get(handlers::photos::get_photo_content).head(handlers::photos::head_photo_content),
+        )
+        .route(
+            "/api/photos/:id/primary",
+            put(handlers::photos::set_primary_photo),
+        )
+        .route(
+            "/api/photos/:id/thumbnail",
+            get(handlers::photos::get_photo_thumbnail),
+        )
+        .route("/api/photos/:id/url", get(handlers::photos::get_photo_url))
         // Miniature-Recipe linking routes
         .route(
             "/api/miniatures/:id/recipes",
             get(handlers::miniature_recipes::get_miniature_recipes),
         )
         .route(
-            "/api/miniatures/:miniature_id/recipes/:recipe_id",
+            "/api/miniatures/:id/recipes/:recipe_id",
             post(handlers::miniature_recipes::link_recipe_to_miniature),
         )
         .route(
-            "/api/miniatures/:miniature_id/recipes/:recipe_id",
+            "/api/miniatures/:id/recipes/:recipe_id",
             delete(handlers::miniature_recipes::unlink_recipe_from_miniature),
         )
         .route(
             "/api/recipes/:id/usage",
             get(handlers::miniature_recipes::get_recipe_usage_count),
         )
+        .route(
+            "/api/recipes/:id/projects",
+            get(handlers::miniature_recipes::get_recipe_projects),
+        )
+        .route(
+            "/api/recipes/:id/link",
+            post(handlers::miniature_recipes::bulk_link_recipe),
+        )
+        .route(
+            "/api/admin/storage/prune",
+            post(handlers::admin::prune_storage),
+        )
+        .route(
+            "/api/admin/storage/usage",
+            get(handlers::admin::get_storage_usage),
+        )
+        // Cap request bodies; routes added after this keep their own limits
+        .layer(RequestBodyLimitLayer::new(config.max_request_body_bytes))
+        .route(
+            "/api/miniatures/:id/photos",
+            post(handlers::photos::upload_photo).layer(RequestBodyLimitLayer::new(
+                handlers::photos::MAX_UPLOAD_BODY_SIZE,
+            )),
+        )
+        .route(
+            "/api/miniatures/:id/photos/batch",
+            post(handlers::photos::upload_photos_batch).layer(RequestBodyLimitLayer::new(
+                handlers::photos::MAX_BATCH_BODY_SIZE,
+            )),
+        );
+
+    // Routes added after the tracking layer, like /metrics itself, aren't measured
+    let router = if config.metrics_enabled {
+        // Install the recorder up front so the first requests are counted
+        telemetry::prometheus_handle();
+        router
+            .layer(middleware::from_fn(telemetry::track_metrics))
+            .route("/metrics", get(telemetry::render_metrics))
+    } else {
+        router
+    };
+
+    router
+        .merge(
+            SwaggerUi::new("/swagger-ui")
+                .url(openapi::OPENAPI_JSON_PATH, openapi::ApiDoc::openapi()),
+        )
+        // Unknown paths and methods get the same `ErrorResponse` body as every other error;
+        // the method fallback only covers routes added above
+        .fallback(handlers::route_not_found)
+        .method_not_allowed_fallback(handlers::method_not_allowed)
         .layer(
             ServiceBuilder::new()
+                // The limits above replace axum's built-in 2MB extractor cap
+                .layer(DefaultBodyLimit::disable())
                 // Add request ID for tracing
                 .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
                 .layer(PropagateRequestIdLayer::x_request_id())
-                // Add tracing
-                .layer(TraceLayer::new_for_http())
+                // Add tracing, logging each response's status and latency under the request's span
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(telemetry::request_span)
+                        .on_response(DefaultOnResponse::new().level(Level::INFO)),
+                )
                 // Add CORS
-                .layer(CorsLayer::permissive()),
+                .layer(cors_layer(config)),
         )
-        .with_state(database);
+        .with_state(AppState {
+            database,
+            config: Arc::new(config.clone()),
+            storage,
+        })
+}
 
-    // Run the server
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
-    tracing::info!("Server listening on {}", addr);
+/// Restrict cross-origin requests to the configured origins, or allow any origin when none
+/// are configured (development)
+fn cors_layer(config: &Config) -> CorsLayer {
+    let Some(origins) = &config.allowed_origins else {
+        return CorsLayer::permissive();
+    };
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    CorsLayer::new()
+        .allow_origin(origins.clone())
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            header::IF_NONE_MATCH,
+            HeaderName::from_static(handlers::idempotency::IDEMPOTENCY_KEY_HEADER),
+            X_REQUEST_ID,
+        ])
+        // Response headers the frontend reads: tracing ids, created resources, download names,
+        // and cache validators
+        .expose_headers([
+            X_REQUEST_ID,
+            header::LOCATION,
+            header::CONTENT_DISPOSITION,
+            header::ETAG,
+        ])
+        .max_age(Duration::from_secs(3600))
+}
 
-    Ok(())
+/// Liveness: the process is up and serving requests. Dependencies aren't checked, so an
+/// orchestrator doesn't restart the server over a database blip.
+async fn liveness() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "alive",
+        "service": "miniature-painting-tracker"
+    }))
 }
 
-async fn health_check(
+/// Readiness: the database and storage can be reached, or 503 when either can't
+async fn readiness(
     axum::extract::State(database): axum::extract::State<Database>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    match database.health_check().await {
-        Ok(_) => Ok(Json(serde_json::json!({
-            "status": "healthy",
-            "service": "miniature-painting-tracker",
-            "database": "connected"
-        }))),
-        Err(_) => {
-            tracing::error!("Health check failed: database connection error");
-            Err(StatusCode::SERVICE_UNAVAILABLE)
-        }
+    axum::extract::State(storage): axum::extract::State<SharedStorage>,
+) -> Response {
+    if database.health_check().await.is_err() {
+        tracing::error!("Health check failed: database connection error");
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+
+    if let Err(e) = storage_health_check(&storage).await {
+        tracing::error!("Health check failed: storage error: {}", e);
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "status": "degraded",
+                "service": "miniature-painting-tracker",
+                "database": "connected",
+                "storage": "unavailable",
+                "details": {
+                    "subsystem": "storage",
+                    "message": e
+                }
+            })),
+        )
+            .into_response();
     }
+
+    Json(serde_json::json!({
+        "status": "healthy",
+        "service": "miniature-painting-tracker",
+        "database": "connected",
+        "storage": "connected"
+    }))
+    .into_response()
+}
+
+/// Build the configured storage backend and check it can be reached
+async fn storage_health_check(storage: &SharedStorage) -> Result<(), String> {
+    let storage_service = storage
+        .get()
+        .await
+        .map_err(|e| format!("Storage initialization error: {}", e))?;
+    storage_service
+        .health_check()
+        .await
+        .map_err(|e| e.to_string())
 }
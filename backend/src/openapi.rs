@@ -0,0 +1,369 @@
+use crate::{error::FieldError, handlers};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared_types::{
+    ArmyUsage, GameSystem, LinkedRecipe, Miniature, MiniatureType, MiniatureWithCounts, Paint,
+    PaintUsage, PaintingRecipe, Photo, ProgressChange, ProgressStatus, Project, RecipeProjectUsage,
+};
+use std::collections::HashMap;
+use utoipa::{OpenApi, ToSchema};
+
+/// Where the generated spec is served; Swagger UI loads it from here
+pub const OPENAPI_JSON_PATH: &str = "/api-docs/openapi.json";
+
+/// OpenAPI document assembled from the `#[utoipa::path]` annotations on the handlers.
+/// Schemas reachable from a path are collected automatically.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Miniature Painting Tracker API",
+        description = "Track miniature painting projects, their miniatures, painting recipes, and photos. \
+            Every error response has the `ErrorResponse` shape; field validation errors list each \
+            invalid field in `details`."
+    ),
+    paths(
+        handlers::projects::list_projects,
+        handlers::projects::create_project,
+        handlers::projects::get_project,
+        handlers::projects::update_project,
+        handlers::projects::patch_project,
+        handlers::projects::delete_project,
+        handlers::projects::clone_project,
+        handlers::projects::restore_project,
+        handlers::projects::archive_project,
+        handlers::projects::unarchive_project,
+        handlers::export::export_project,
+        handlers::export::import_project,
+        handlers::export::export_miniatures_csv,
+        handlers::stats::get_project_stats,
+        handlers::stats::get_project_points,
+        handlers::stats::get_paint_stats,
+        handlers::stats::list_armies,
+        handlers::miniatures::search_miniatures,
+        handlers::miniatures::list_miniatures,
+        handlers::miniatures::create_miniature,
+        handlers::miniatures::bulk_create_miniatures,
+        handlers::miniatures::bulk_update_status,
+        handlers::miniatures::bulk_delete_miniatures,
+        handlers::miniatures::get_miniature,
+        handlers::miniatures::get_project_miniature,
+        handlers::miniatures::update_miniature,
+        handlers::miniatures::patch_miniature,
+        handlers::miniatures::delete_miniature,
+        handlers::miniatures::get_miniature_history,
+        handlers::miniatures::move_miniature,
+        handlers::miniature_recipes::get_miniature_recipes,
+        handlers::miniature_recipes::link_recipe_to_miniature,
+        handlers::miniature_recipes::unlink_recipe_from_miniature,
+        handlers::miniature_recipes::get_recipe_usage_count,
+        handlers::miniature_recipes::get_recipe_projects,
+        handlers::miniature_recipes::bulk_link_recipe,
+        handlers::recipes::list_recipes,
+        handlers::recipes::create_recipe,
+        handlers::recipes::validate_recipe,
+        handlers::recipes::get_recipe,
+        handlers::recipes::update_recipe,
+        handlers::recipes::delete_recipe,
+        handlers::recipes::duplicate_recipe,
+        handlers::recipes::reorder_recipe_steps,
+        handlers::paints::list_paints,
+        handlers::paints::create_paint,
+        handlers::paints::get_paint,
+        handlers::paints::update_paint,
+        handlers::paints::delete_paint,
+        handlers::paints::get_missing_paints,
+        handlers::photos::upload_photo,
+        handlers::photos::upload_photos_batch,
+        handlers::photos::list_photos,
+        handlers::photos::get_photo,
+        handlers::photos::delete_photo,
+        handlers::photos::delete_miniature_photos,
+        handlers::photos::get_photo_content,
+        handlers::photos::head_photo_content,
+        handlers::photos::get_photo_thumbnail,
+        handlers::photos::get_photo_url,
+        handlers::photos::set_primary_photo,
+        handlers::admin::prune_storage,
+        handlers::admin::get_storage_usage,
+    ),
+    components(schemas(FieldError, MiniatureWithCounts)),
+    tags(
+        (name = "projects", description = "Painting projects, their exports, and statistics"),
+        (name = "miniatures", description = "Miniatures within a project and their recipe links"),
+        (name = "recipes", description = "Reusable painting recipes"),
+        (name = "paints", description = "The paint inventory and what recipes still need"),
+        (name = "photos", description = "Photos of miniatures and their stored files"),
+        (name = "admin", description = "Maintenance; needs the configured admin token"),
+    )
+)]
+pub struct ApiDoc;
+
+// The handlers below build these bodies with `serde_json::json!`; the structs only describe
+// them in the spec, so keep them in step with the handlers.
+
+/// A page of projects
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct ProjectList {
+    /// Length of `projects`
+    pub count: usize,
+    pub projects: Vec<Project>,
+    /// Projects matching the filters across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct MiniatureList {
+    /// Length of `miniatures`
+    pub count: usize,
+    pub miniatures: Vec<Miniature>,
+    /// With `limit` or `after`: pass as `after` for the next page; null on the last one
+    pub next_cursor: Option<String>,
+}
+
+/// A page of miniatures from across all projects
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct MiniaturePage {
+    /// Length of `miniatures`
+    pub count: usize,
+    pub miniatures: Vec<Miniature>,
+    /// Miniatures matching the search across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct ProgressHistory {
+    /// Length of `history`
+    pub count: usize,
+    pub history: Vec<ProgressChange>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct RecipeList {
+    /// Length of `recipes`
+    pub count: usize,
+    pub recipes: Vec<PaintingRecipe>,
+}
+
+/// A recipe that passed validation; failures come back as a 400 error instead
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct RecipeValidation {
+    /// Always true
+    pub valid: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PaintList {
+    /// Length of `paints`
+    pub count: usize,
+    pub paints: Vec<Paint>,
+}
+
+/// Paints a recipe calls for that aren't on hand
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct MissingPaints {
+    pub recipe_id: i64,
+    /// Length of `missing`
+    pub count: usize,
+    /// Paint names as the recipe spells them
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct LinkedRecipeList {
+    /// Length of `recipes`
+    pub count: usize,
+    pub recipes: Vec<LinkedRecipe>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct BulkUpdateStatusResponse {
+    /// Number of miniatures whose status was set
+    pub updated: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct BulkDeleteMiniaturesResponse {
+    /// Number of miniatures deleted
+    pub deleted: u64,
+}
+
+/// Completion statistics for a project's miniatures
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct ProjectStats {
+    pub project_id: i64,
+    pub total_miniatures: i64,
+    /// Miniature count for every status, including those with none
+    pub status_counts: HashMap<ProgressStatus, i64>,
+    pub completion_percentage: f64,
+    /// Sum of the miniatures' points values
+    pub total_points: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct ProjectPoints {
+    pub project_id: i64,
+    /// Sum of the miniatures' points values; miniatures without one count as zero
+    pub total_points: i64,
+    /// Points for every miniature type, including those with none
+    pub points_by_type: HashMap<MiniatureType, i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PaintStats {
+    pub project_id: Option<i64>,
+    pub paints: Vec<PaintUsage>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct ArmyCatalogue {
+    pub game_system: Option<GameSystem>,
+    pub armies: Vec<ArmyUsage>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct RecipeUsage {
+    pub recipe_id: i64,
+    pub miniature_count: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct RecipeProjectList {
+    pub recipe_id: i64,
+    /// Length of `projects`
+    pub count: usize,
+    pub projects: Vec<RecipeProjectUsage>,
+}
+
+/// A link for fetching a photo's file
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PhotoUrl {
+    pub url: String,
+    /// Seconds until a signed URL stops working; absent for URLs that don't expire
+    pub expires_in: Option<u64>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Multipart form for a photo upload
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct PhotoUpload {
+    /// A JPEG, PNG, or WebP image of at most 10MB
+    #[schema(value_type = String, format = Binary)]
+    pub photo: Vec<u8>,
+}
+
+/// Multipart form for a batch photo upload
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct PhotoBatchUpload {
+    /// Repeat the field once per file: JPEG, PNG, or WebP images of at most 10MB each, and at
+    /// most 10 of them
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub photo: Vec<Vec<u8>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PhotoBatchResult {
+    /// Length of `photos`
+    pub count: usize,
+    /// Stored photos, including existing ones returned for repeated files
+    pub photos: Vec<Photo>,
+    pub errors: Vec<PhotoUploadFailure>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct BulkLinkResult {
+    pub recipe_id: i64,
+    /// Number of links newly made
+    pub linked: usize,
+    /// One entry per requested id, in request order
+    pub results: Vec<BulkLinkOutcome>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct BulkLinkOutcome {
+    pub miniature_id: i64,
+    /// `linked`, `skipped` (already linked), or `error`
+    pub status: String,
+    /// Why the miniature was skipped or not linked
+    pub message: Option<String>,
+}
+
+/// A file from a batch upload that was not stored
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PhotoUploadFailure {
+    /// Position of the file among the form's `photo` fields, from zero
+    pub index: usize,
+    pub filename: Option<String>,
+    /// The `error_type` a single upload of the file would have failed with
+    pub error_type: String,
+    pub message: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct PhotosCleared {
+    /// Number of photos deleted along with their files
+    pub deleted: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct StoragePrune {
+    /// Number of orphaned files deleted
+    pub deleted: u64,
+}
+
+/// What storage holds, as reported by the backend itself
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct StorageUsage {
+    /// Every stored photo and thumbnail
+    pub file_count: usize,
+    pub total_bytes: u64,
+    /// One entry per miniature directory with stored files, by miniature id; files stored
+    /// flat count only in the totals
+    pub miniatures: Vec<MiniatureStorageUsage>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[allow(dead_code)]
+pub struct MiniatureStorageUsage {
+    pub miniature_id: i64,
+    /// Storage path the miniature's files sit under
+    pub prefix: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+/// Raw file contents
+#[derive(ToSchema)]
+#[schema(value_type = String, format = Binary)]
+#[allow(dead_code)]
+pub struct FileContents(Vec<u8>);
@@ -0,0 +1,138 @@
+use crate::database::{Connection, Executor};
+use sqlx::Row;
+
+/// Remembers which resource a creation request's `Idempotency-Key` produced, so a retry can
+/// be answered with it instead of creating a duplicate
+pub struct IdempotencyKeyRepository;
+
+impl IdempotencyKeyRepository {
+    /// The resource created under `key` within the last `ttl_seconds`, by the database clock.
+    /// Reads the primary, since a lagging replica could miss a key recorded moments ago.
+    pub async fn find_resource_id(
+        executor: impl Into<Executor<'_>>,
+        scope: &str,
+        key: &str,
+        ttl_seconds: i64,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT resource_id FROM idempotency_keys WHERE scope = ?1 AND idempotency_key = ?2 AND julianday(created_at) > julianday('now', ?3)",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(format!("-{} seconds", ttl_seconds))
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("resource_id")))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT resource_id FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2 AND created_at > CURRENT_TIMESTAMP - $3 * INTERVAL '1 second'",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(ttl_seconds)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("resource_id")))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT resource_id FROM idempotency_keys WHERE scope = ? AND idempotency_key = ? AND created_at > UTC_TIMESTAMP(6) - INTERVAL ? SECOND",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(ttl_seconds)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("resource_id")))
+            }
+        }
+    }
+
+    /// Record the resource a key created. Fails with a unique violation if the key is
+    /// already recorded, as when two requests with it race.
+    pub async fn record(
+        executor: impl Into<Executor<'_>>,
+        scope: &str,
+        key: &str,
+        resource_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                sqlx::query(
+                    "INSERT INTO idempotency_keys (scope, idempotency_key, resource_id, created_at) VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(resource_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+            Connection::Postgres(mut conn) => {
+                sqlx::query(
+                    "INSERT INTO idempotency_keys (scope, idempotency_key, resource_id, created_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP)",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(resource_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+            Connection::Mysql(mut conn) => {
+                sqlx::query(
+                    "INSERT INTO idempotency_keys (scope, idempotency_key, resource_id, created_at) VALUES (?, ?, ?, UTC_TIMESTAMP(6))",
+                )
+                .bind(scope)
+                .bind(key)
+                .bind(resource_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget keys older than `ttl_seconds`, returning how many were removed
+    pub async fn delete_expired(
+        executor: impl Into<Executor<'_>>,
+        ttl_seconds: i64,
+    ) -> Result<u64, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let result = sqlx::query(
+                    "DELETE FROM idempotency_keys WHERE julianday(created_at) <= julianday('now', ?1)",
+                )
+                .bind(format!("-{} seconds", ttl_seconds))
+                .execute(&mut *conn)
+                .await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Postgres(mut conn) => {
+                let result = sqlx::query(
+                    "DELETE FROM idempotency_keys WHERE created_at <= CURRENT_TIMESTAMP - $1 * INTERVAL '1 second'",
+                )
+                .bind(ttl_seconds)
+                .execute(&mut *conn)
+                .await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Mysql(mut conn) => {
+                let result = sqlx::query(
+                    "DELETE FROM idempotency_keys WHERE created_at <= UTC_TIMESTAMP(6) - INTERVAL ? SECOND",
+                )
+                .bind(ttl_seconds)
+                .execute(&mut *conn)
+                .await?;
+
+                Ok(result.rows_affected())
+            }
+        }
+    }
+}
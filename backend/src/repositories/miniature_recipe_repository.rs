@@ -1,80 +1,165 @@
-use crate::database::Database;
-use shared_types::PaintingRecipe;
-use sqlx::Row;
+use crate::database::{Connection, Executor};
+use shared_types::{LinkedRecipe, PaintingRecipe, Project, RecipeProjectUsage};
+use sqlx::{Connection as _, Row};
 
 pub struct MiniatureRecipeRepository;
 
 impl MiniatureRecipeRepository {
-    /// Link a recipe to a miniature
+    /// Link a recipe to a miniature, bumping the miniature's `updated_at` if the link is new.
+    /// Returns whether it was.
     pub async fn link(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
         recipe_id: i64,
-    ) -> Result<(), sqlx::Error> {
-        match database {
-            crate::database::Database::Sqlite(pool) => {
-                sqlx::query(
-                    "INSERT OR IGNORE INTO miniature_recipes (miniature_id, recipe_id) VALUES (?1, ?2)"
+    ) -> Result<bool, sqlx::Error> {
+        let inserted = match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let inserted = sqlx::query(
+                    "INSERT OR IGNORE INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))"
                 )
                 .bind(miniature_id)
                 .bind(recipe_id)
-                .execute(pool)
-                .await?;
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                if inserted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE id = ?1")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                inserted
             }
-            crate::database::Database::Postgres(pool) => {
-                sqlx::query(
-                    "INSERT INTO miniature_recipes (miniature_id, recipe_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let inserted = sqlx::query(
+                    "INSERT INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES ($1, $2, CURRENT_TIMESTAMP) ON CONFLICT DO NOTHING"
                 )
                 .bind(miniature_id)
                 .bind(recipe_id)
-                .execute(pool)
-                .await?;
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                if inserted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                inserted
             }
-        }
-        Ok(())
+            Connection::Mysql(mut conn) => {
+                // Unlike ON CONFLICT, IGNORE also skips foreign key failures; the handler has
+                // already checked that both sides exist
+                let mut tx = conn.begin().await?;
+                let inserted = sqlx::query(
+                    "INSERT IGNORE INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES (?, ?, UTC_TIMESTAMP(6))"
+                )
+                .bind(miniature_id)
+                .bind(recipe_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                if inserted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = UTC_TIMESTAMP(6) WHERE id = ?")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                inserted
+            }
+        };
+        Ok(inserted > 0)
     }
 
-    /// Unlink a recipe from a miniature
+    /// Unlink a recipe from a miniature, bumping the miniature's `updated_at` if a link was removed
     pub async fn unlink(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
         recipe_id: i64,
     ) -> Result<bool, sqlx::Error> {
-        let rows_affected = match database {
-            crate::database::Database::Sqlite(pool) => {
-                sqlx::query(
+        let rows_affected = match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let deleted = sqlx::query(
                     "DELETE FROM miniature_recipes WHERE miniature_id = ?1 AND recipe_id = ?2"
                 )
                 .bind(miniature_id)
                 .bind(recipe_id)
-                .execute(pool)
+                .execute(&mut *tx)
                 .await?
-                .rows_affected()
+                .rows_affected();
+
+                if deleted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE id = ?1")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                deleted
             }
-            crate::database::Database::Postgres(pool) => {
-                sqlx::query(
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let deleted = sqlx::query(
                     "DELETE FROM miniature_recipes WHERE miniature_id = $1 AND recipe_id = $2"
                 )
                 .bind(miniature_id)
                 .bind(recipe_id)
-                .execute(pool)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                if deleted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                deleted
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let deleted = sqlx::query(
+                    "DELETE FROM miniature_recipes WHERE miniature_id = ? AND recipe_id = ?"
+                )
+                .bind(miniature_id)
+                .bind(recipe_id)
+                .execute(&mut *tx)
                 .await?
-                .rows_affected()
+                .rows_affected();
+
+                if deleted > 0 {
+                    sqlx::query("UPDATE miniatures SET updated_at = UTC_TIMESTAMP(6) WHERE id = ?")
+                        .bind(miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+                deleted
             }
         };
         Ok(rows_affected > 0)
     }
 
-    /// Get all recipes linked to a miniature
+    /// Get all recipes linked to a miniature, each with when it was linked
     pub async fn find_recipes_for_miniature(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
-    ) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
-        match database {
-            crate::database::Database::Sqlite(pool) => {
+    ) -> Result<Vec<LinkedRecipe>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let rows = sqlx::query(
                     r#"
-                    SELECT pr.id, pr.name, pr.miniature_type, pr.steps, pr.paints_used, pr.techniques, pr.notes, pr.created_at, pr.updated_at
+                    SELECT pr.id, pr.name, pr.miniature_type, pr.steps, pr.paints_used, pr.techniques, pr.notes, pr.created_at, pr.updated_at, mr.linked_at
                     FROM painting_recipes pr
                     INNER JOIN miniature_recipes mr ON pr.id = mr.recipe_id
                     WHERE mr.miniature_id = ?1
@@ -82,7 +167,7 @@ impl MiniatureRecipeRepository {
                     "#
                 )
                 .bind(miniature_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows.into_iter().map(|r| {
@@ -90,23 +175,26 @@ impl MiniatureRecipeRepository {
                     let paints_used: Vec<String> = serde_json::from_str(r.get("paints_used")).unwrap_or_default();
                     let techniques: Vec<String> = serde_json::from_str(r.get("techniques")).unwrap_or_default();
 
-                    PaintingRecipe {
-                        id: r.get("id"),
-                        name: r.get("name"),
-                        miniature_type: r.get("miniature_type"),
-                        steps,
-                        paints_used,
-                        techniques,
-                        notes: r.get("notes"),
-                        created_at: r.get("created_at"),
-                        updated_at: r.get("updated_at"),
+                    LinkedRecipe {
+                        recipe: PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        linked_at: r.get("linked_at"),
                     }
                 }).collect())
             }
-            crate::database::Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
                     r#"
-                    SELECT pr.id, pr.name, pr.miniature_type, pr.steps, pr.paints_used, pr.techniques, pr.notes, pr.created_at, pr.updated_at
+                    SELECT pr.id, pr.name, pr.miniature_type, pr.steps, pr.paints_used, pr.techniques, pr.notes, pr.created_at, pr.updated_at, mr.linked_at
                     FROM painting_recipes pr
                     INNER JOIN miniature_recipes mr ON pr.id = mr.recipe_id
                     WHERE mr.miniature_id = $1
@@ -114,7 +202,7 @@ impl MiniatureRecipeRepository {
                     "#
                 )
                 .bind(miniature_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows.into_iter().map(|r| {
@@ -122,16 +210,54 @@ impl MiniatureRecipeRepository {
                     let paints_used: Vec<String> = serde_json::from_str(r.get("paints_used")).unwrap_or_default();
                     let techniques: Vec<String> = serde_json::from_str(r.get("techniques")).unwrap_or_default();
 
-                    PaintingRecipe {
-                        id: r.get("id"),
-                        name: r.get("name"),
-                        miniature_type: r.get("miniature_type"),
-                        steps,
-                        paints_used,
-                        techniques,
-                        notes: r.get("notes"),
-                        created_at: r.get("created_at"),
-                        updated_at: r.get("updated_at"),
+                    LinkedRecipe {
+                        recipe: PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        linked_at: r.get("linked_at"),
+                    }
+                }).collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    r#"
+                    SELECT pr.id, pr.name, pr.miniature_type, pr.steps, pr.paints_used, pr.techniques, pr.notes, pr.created_at, pr.updated_at, mr.linked_at
+                    FROM painting_recipes pr
+                    INNER JOIN miniature_recipes mr ON pr.id = mr.recipe_id
+                    WHERE mr.miniature_id = ?
+                    ORDER BY pr.name
+                    "#
+                )
+                .bind(miniature_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows.into_iter().map(|r| {
+                    let steps: Vec<String> = serde_json::from_str(r.get("steps")).unwrap_or_default();
+                    let paints_used: Vec<String> = serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                    let techniques: Vec<String> = serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                    LinkedRecipe {
+                        recipe: PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        linked_at: r.get("linked_at"),
                     }
                 }).collect())
             }
@@ -140,54 +266,190 @@ impl MiniatureRecipeRepository {
 
     /// Get the count of miniatures using a specific recipe
     pub async fn count_miniatures_for_recipe(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         recipe_id: i64,
     ) -> Result<i64, sqlx::Error> {
-        match database {
-            crate::database::Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     "SELECT COUNT(*) as count FROM miniature_recipes WHERE recipe_id = ?1"
                 )
                 .bind(recipe_id)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await?;
                 Ok(row.get("count"))
             }
-            crate::database::Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     "SELECT COUNT(*) as count FROM miniature_recipes WHERE recipe_id = $1"
                 )
                 .bind(recipe_id)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
+                .await?;
+                Ok(row.get("count"))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT COUNT(*) as count FROM miniature_recipes WHERE recipe_id = ?"
+                )
+                .bind(recipe_id)
+                .fetch_one(&mut *conn)
                 .await?;
                 Ok(row.get("count"))
             }
         }
     }
 
+    /// Live projects with miniatures linked to a recipe, by name, each with how many of its
+    /// miniatures are
+    pub async fn find_projects_for_recipe(
+        executor: impl Into<Executor<'_>>,
+        recipe_id: i64,
+    ) -> Result<Vec<RecipeProjectUsage>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    r#"
+                    SELECT p.id, p.name, p.game_system, p.army, p.description, p.created_at, p.updated_at, p.deleted_at, p.tags, p.archived,
+                           COUNT(*) AS miniature_count
+                    FROM miniature_recipes mr
+                    JOIN miniatures m ON m.id = mr.miniature_id
+                    JOIN projects p ON p.id = m.project_id
+                    WHERE mr.recipe_id = ?1 AND p.deleted_at IS NULL
+                    GROUP BY p.id
+                    ORDER BY p.name, p.id
+                    "#,
+                )
+                .bind(recipe_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| RecipeProjectUsage {
+                        project: Project {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            game_system: r.get("game_system"),
+                            army: r.get("army"),
+                            description: r.get("description"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                            deleted_at: r.get("deleted_at"),
+                            tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                            archived: r.get("archived"),
+                        },
+                        miniature_count: r.get("miniature_count"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let rows = sqlx::query(
+                    r#"
+                    SELECT p.id, p.name, p.game_system, p.army, p.description, p.created_at, p.updated_at, p.deleted_at, p.tags, p.archived,
+                           COUNT(*) AS miniature_count
+                    FROM miniature_recipes mr
+                    JOIN miniatures m ON m.id = mr.miniature_id
+                    JOIN projects p ON p.id = m.project_id
+                    WHERE mr.recipe_id = $1 AND p.deleted_at IS NULL
+                    GROUP BY p.id
+                    ORDER BY p.name, p.id
+                    "#,
+                )
+                .bind(recipe_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| RecipeProjectUsage {
+                        project: Project {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            game_system: r.get("game_system"),
+                            army: r.get("army"),
+                            description: r.get("description"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                            deleted_at: r.get("deleted_at"),
+                            tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                            archived: r.get("archived"),
+                        },
+                        miniature_count: r.get("miniature_count"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    r#"
+                    SELECT p.id, p.name, p.game_system, p.army, p.description, p.created_at, p.updated_at, p.deleted_at, p.tags, p.archived,
+                           COUNT(*) AS miniature_count
+                    FROM miniature_recipes mr
+                    JOIN miniatures m ON m.id = mr.miniature_id
+                    JOIN projects p ON p.id = m.project_id
+                    WHERE mr.recipe_id = ? AND p.deleted_at IS NULL
+                    GROUP BY p.id
+                    ORDER BY p.name, p.id
+                    "#,
+                )
+                .bind(recipe_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| RecipeProjectUsage {
+                        project: Project {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            game_system: r.get("game_system"),
+                            army: r.get("army"),
+                            description: r.get("description"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                            deleted_at: r.get("deleted_at"),
+                            tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                            archived: r.get("archived"),
+                        },
+                        miniature_count: r.get("miniature_count"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
     /// Get all recipe IDs linked to a miniature
-    #[allow(dead_code)]
     pub async fn get_recipe_ids_for_miniature(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
     ) -> Result<Vec<i64>, sqlx::Error> {
-        match database {
-            crate::database::Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT recipe_id FROM miniature_recipes WHERE miniature_id = ?1"
                 )
                 .bind(miniature_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows.into_iter().map(|r| r.get("recipe_id")).collect())
             }
-            crate::database::Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT recipe_id FROM miniature_recipes WHERE miniature_id = $1"
                 )
                 .bind(miniature_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows.into_iter().map(|r| r.get("recipe_id")).collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT recipe_id FROM miniature_recipes WHERE miniature_id = ?"
+                )
+                .bind(miniature_id)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows.into_iter().map(|r| r.get("recipe_id")).collect())
@@ -1,27 +1,105 @@
-use crate::database::Database;
-use chrono::Utc;
+use super::{escape_like, placeholders, to_mysql, unnumbered_placeholders};
+use crate::database::{Connection, Executor};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use shared_types::{
-    CreateMiniatureRequest, Miniature, MiniatureType, ProgressStatus, UpdateMiniatureRequest,
+    CreateMiniatureRequest, Miniature, MiniatureType, MiniatureWithCounts, PatchMiniatureRequest,
+    ProgressStatus,
 };
-use sqlx::{Pool, Postgres, Row, Sqlite};
+use sqlx::Connection as _;
+use sqlx::Row;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Orders a project's miniatures may be listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MiniatureSortField {
+    Name,
+    #[default]
+    CreatedAt,
+    /// Painting progression, from unpainted to varnished
+    Status,
+}
+
+impl MiniatureSortField {
+    /// ORDER BY clause from whitelisted columns, with `table` prefixed to each (e.g. `"m."`).
+    /// Statuses are stored as their names, so progression order comes from a CASE over
+    /// `ProgressStatus::all`.
+    fn order_by(&self, table: &str) -> String {
+        match self {
+            MiniatureSortField::Name => format!("{t}name, {t}id", t = table),
+            MiniatureSortField::CreatedAt => format!("{t}created_at, {t}id", t = table),
+            MiniatureSortField::Status => {
+                let ranks: String = ProgressStatus::all()
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, status)| format!(" WHEN '{}' THEN {}", status.as_str(), rank))
+                    .collect();
+                format!(
+                    "CASE {t}progress_status{} END, {t}created_at, {t}id",
+                    ranks,
+                    t = table
+                )
+            }
+        }
+    }
+}
+
+/// Filters for searching miniatures across all projects
+#[derive(Debug, Default)]
+pub struct MiniatureSearch {
+    /// Case-insensitive substring of the name
+    pub query: Option<String>,
+    /// Any of these statuses; empty means every status
+    pub statuses: Vec<ProgressStatus>,
+}
+
+impl MiniatureSearch {
+    /// WHERE conditions for the filters that are set, numbering bind placeholders from `start`.
+    /// Values must be bound in the same order: name pattern, then each status.
+    fn where_clause(&self, prefix: char, start: usize) -> String {
+        // Miniatures of soft-deleted projects stay hidden, as everywhere else
+        let mut conditions =
+            vec!["project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)".to_string()];
+        let mut index = start;
+
+        if self.query.is_some() {
+            conditions.push(format!("LOWER(name) LIKE {}{} ESCAPE '\\'", prefix, index));
+            index += 1;
+        }
+        if !self.statuses.is_empty() {
+            conditions.push(format!(
+                "progress_status IN ({})",
+                placeholders(prefix, index, self.statuses.len())
+            ));
+        }
+
+        conditions.join(" AND ")
+    }
+
+    fn name_pattern(&self) -> Option<String> {
+        self.query
+            .as_ref()
+            .map(|query| format!("%{}%", escape_like(&query.to_lowercase())))
+    }
+}
 
 pub struct MiniatureRepository;
 
 impl MiniatureRepository {
     pub async fn create(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         project_id: i64,
         request: CreateMiniatureRequest,
     ) -> Result<Miniature, sqlx::Error> {
-        let now = Utc::now();
-
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, created_at, updated_at)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-                    RETURNING id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
                     "#
                 )
                 .bind(project_id)
@@ -29,9 +107,8 @@ impl MiniatureRepository {
                 .bind(&request.miniature_type)
                 .bind(ProgressStatus::Unpainted) // Default status
                 .bind(&request.notes)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(request.points_value)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Miniature {
@@ -41,16 +118,46 @@ impl MiniatureRepository {
                     miniature_type: row.get("miniature_type"),
                     progress_status: row.get("progress_status"),
                     notes: row.get("notes"),
+                    points_value: row.get("points_value"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
                 })
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, created_at, updated_at)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7)
-                    RETURNING id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
+                    "#
+                )
+                .bind(project_id)
+                .bind(&request.name)
+                .bind(&request.miniature_type)
+                .bind(ProgressStatus::Unpainted) // Default status
+                .bind(&request.notes)
+                .bind(request.points_value)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Miniature {
+                    id: row.get("id"),
+                    project_id: row.get("project_id"),
+                    name: row.get("name"),
+                    miniature_type: row.get("miniature_type"),
+                    progress_status: row.get("progress_status"),
+                    notes: row.get("notes"),
+                    points_value: row.get("points_value"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            }
+            Connection::Mysql(mut conn) => {
+                // No RETURNING in MySQL, so read the row back by its new id
+                let id = sqlx::query(
+                    r#"
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
                     "#
                 )
                 .bind(project_id)
@@ -58,9 +165,16 @@ impl MiniatureRepository {
                 .bind(&request.miniature_type)
                 .bind(ProgressStatus::Unpainted) // Default status
                 .bind(&request.notes)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(request.points_value)
+                .execute(&mut *conn)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Miniature {
@@ -70,6 +184,7 @@ impl MiniatureRepository {
                     miniature_type: row.get("miniature_type"),
                     progress_status: row.get("progress_status"),
                     notes: row.get("notes"),
+                    points_value: row.get("points_value"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
                 })
@@ -78,16 +193,36 @@ impl MiniatureRepository {
     }
 
     pub async fn find_by_id(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         id: i64,
     ) -> Result<Option<Miniature>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = ?1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)"
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Miniature {
+                    id: r.get("id"),
+                    project_id: r.get("project_id"),
+                    name: r.get("name"),
+                    miniature_type: r.get("miniature_type"),
+                    progress_status: r.get("progress_status"),
+                    notes: r.get("notes"),
+                    points_value: r.get("points_value"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at FROM miniatures WHERE id = ?1"
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = $1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Miniature {
@@ -97,16 +232,17 @@ impl MiniatureRepository {
                     miniature_type: r.get("miniature_type"),
                     progress_status: r.get("progress_status"),
                     notes: r.get("notes"),
+                    points_value: r.get("points_value"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Mysql(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at FROM miniatures WHERE id = $1"
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = ? AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Miniature {
@@ -116,6 +252,7 @@ impl MiniatureRepository {
                     miniature_type: r.get("miniature_type"),
                     progress_status: r.get("progress_status"),
                     notes: r.get("notes"),
+                    points_value: r.get("points_value"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
                 }))
@@ -123,17 +260,108 @@ impl MiniatureRepository {
         }
     }
 
+    /// The miniatures among `ids` that exist in live projects, in no particular order
+    pub async fn find_by_ids(
+        executor: impl Into<Executor<'_>>,
+        ids: &[i64],
+    ) -> Result<Vec<Miniature>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id IN ({}) AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)",
+                    placeholders('?', 1, ids.len())
+                );
+                let mut query = sqlx::query(&sql);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id IN ({}) AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)",
+                    placeholders('$', 1, ids.len())
+                );
+                let mut query = sqlx::query(&sql);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id IN ({}) AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)",
+                    unnumbered_placeholders(ids.len())
+                );
+                let mut query = sqlx::query(&sql);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
     pub async fn find_by_project_id(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         project_id: i64,
     ) -> Result<Vec<Miniature>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let rows = sqlx::query(
-                    "SELECT id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at FROM miniatures WHERE project_id = ?1 ORDER BY created_at"
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ?1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY created_at"
                 )
                 .bind(project_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -145,17 +373,18 @@ impl MiniatureRepository {
                         miniature_type: r.get("miniature_type"),
                         progress_status: r.get("progress_status"),
                         notes: r.get("notes"),
+                        points_value: r.get("points_value"),
                         created_at: r.get("created_at"),
                         updated_at: r.get("updated_at"),
                     })
                     .collect())
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
-                    "SELECT id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at FROM miniatures WHERE project_id = $1 ORDER BY created_at"
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = $1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY created_at"
                 )
                 .bind(project_id)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -167,6 +396,542 @@ impl MiniatureRepository {
                         miniature_type: r.get("miniature_type"),
                         progress_status: r.get("progress_status"),
                         notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ? AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY created_at"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// A project's miniatures, optionally filtered by status, in the given order
+    pub async fn find_by_project_id_and_status(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        statuses: &[ProgressStatus],
+        sort: MiniatureSortField,
+    ) -> Result<Vec<Miniature>, sqlx::Error> {
+        let order_by = sort.order_by("");
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        placeholders('?', 2, statuses.len())
+                    )
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ?1{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY {}",
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        placeholders('$', 2, statuses.len())
+                    )
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = $1{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY {}",
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        unnumbered_placeholders(statuses.len())
+                    )
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ?{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY {}",
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Up to `limit` of a project's miniatures in creation order, starting after the
+    /// `(created_at, id)` of the last one already seen. Unlike an offset, the position holds
+    /// when miniatures are added mid-scroll.
+    pub async fn find_page_by_project(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        statuses: &[ProgressStatus],
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: i64,
+    ) -> Result<Vec<Miniature>, sqlx::Error> {
+        // Statuses are numbered after the cursor, when there is one
+        let first_status = if after.is_some() { 5 } else { 3 };
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        placeholders('?', first_status, statuses.len())
+                    )
+                };
+                // julianday compares the instants whatever text format each was stored in
+                let after_filter = if after.is_some() {
+                    " AND (julianday(created_at), id) > (julianday(?3), ?4)"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ?1{}{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY julianday(created_at), id LIMIT ?2",
+                    after_filter, status_filter
+                );
+                let mut query = sqlx::query(&sql).bind(project_id).bind(limit);
+                if let Some((created_at, id)) = after {
+                    query = query.bind(created_at).bind(id);
+                }
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        placeholders('$', first_status, statuses.len())
+                    )
+                };
+                let after_filter = if after.is_some() {
+                    " AND (created_at, id) > ($3, $4)"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = $1{}{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY created_at, id LIMIT $2",
+                    after_filter, status_filter
+                );
+                let mut query = sqlx::query(&sql).bind(project_id).bind(limit);
+                if let Some((created_at, id)) = after {
+                    query = query.bind(created_at).bind(id);
+                }
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " AND progress_status IN ({})",
+                        unnumbered_placeholders(statuses.len())
+                    )
+                };
+                let after_filter = if after.is_some() {
+                    " AND (created_at, id) > (?, ?)"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE project_id = ?{}{} AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL) ORDER BY created_at, id LIMIT ?",
+                    after_filter, status_filter
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                if let Some((created_at, id)) = after {
+                    query = query.bind(created_at).bind(id);
+                }
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.bind(limit).fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// A project's miniatures, optionally filtered by status, each with its number of photos.
+    /// The photos are counted in the same query rather than once per miniature.
+    pub async fn find_by_project_with_photo_counts(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        statuses: &[ProgressStatus],
+        sort: MiniatureSortField,
+    ) -> Result<Vec<MiniatureWithCounts>, sqlx::Error> {
+        let order_by = sort.order_by("m.");
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "AND m.progress_status IN ({})",
+                        placeholders('?', 2, statuses.len())
+                    )
+                };
+                let sql = format!(
+                    r#"
+                    SELECT m.id, m.project_id, m.name, m.miniature_type, m.progress_status, m.notes, m.points_value, m.created_at, m.updated_at,
+                           COUNT(p.id) AS photo_count
+                    FROM miniatures m
+                    LEFT JOIN photos p ON p.miniature_id = m.id
+                    WHERE m.project_id = ?1 {}
+                      AND m.project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                    GROUP BY m.id
+                    ORDER BY {}
+                    "#,
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| MiniatureWithCounts {
+                        miniature: Miniature {
+                            id: r.get("id"),
+                            project_id: r.get("project_id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            progress_status: r.get("progress_status"),
+                            notes: r.get("notes"),
+                            points_value: r.get("points_value"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        photo_count: r.get("photo_count"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "AND m.progress_status IN ({})",
+                        placeholders('$', 2, statuses.len())
+                    )
+                };
+                let sql = format!(
+                    r#"
+                    SELECT m.id, m.project_id, m.name, m.miniature_type, m.progress_status, m.notes, m.points_value, m.created_at, m.updated_at,
+                           COUNT(p.id) AS photo_count
+                    FROM miniatures m
+                    LEFT JOIN photos p ON p.miniature_id = m.id
+                    WHERE m.project_id = $1 {}
+                      AND m.project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                    GROUP BY m.id
+                    ORDER BY {}
+                    "#,
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| MiniatureWithCounts {
+                        miniature: Miniature {
+                            id: r.get("id"),
+                            project_id: r.get("project_id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            progress_status: r.get("progress_status"),
+                            notes: r.get("notes"),
+                            points_value: r.get("points_value"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        photo_count: r.get("photo_count"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let status_filter = if statuses.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "AND m.progress_status IN ({})",
+                        unnumbered_placeholders(statuses.len())
+                    )
+                };
+                let sql = format!(
+                    r#"
+                    SELECT m.id, m.project_id, m.name, m.miniature_type, m.progress_status, m.notes, m.points_value, m.created_at, m.updated_at,
+                           COUNT(p.id) AS photo_count
+                    FROM miniatures m
+                    LEFT JOIN photos p ON p.miniature_id = m.id
+                    WHERE m.project_id = ? {}
+                      AND m.project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                    GROUP BY m.id
+                    ORDER BY {}
+                    "#,
+                    status_filter, order_by
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for status in statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| MiniatureWithCounts {
+                        miniature: Miniature {
+                            id: r.get("id"),
+                            project_id: r.get("project_id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            progress_status: r.get("progress_status"),
+                            notes: r.get("notes"),
+                            points_value: r.get("points_value"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        },
+                        photo_count: r.get("photo_count"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// A page of miniatures from every live project matching the search, ordered by name
+    pub async fn search(
+        executor: impl Into<Executor<'_>>,
+        search: &MiniatureSearch,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Miniature>, sqlx::Error> {
+        let name_pattern = search.name_pattern();
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE {} ORDER BY name, id LIMIT ?1 OFFSET ?2",
+                    search.where_clause('?', 3)
+                );
+                let mut query = sqlx::query(&sql).bind(limit).bind(offset);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE {} ORDER BY name, id LIMIT $1 OFFSET $2",
+                    search.where_clause('$', 3)
+                );
+                let mut query = sqlx::query(&sql).bind(limit).bind(offset);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                // Parameters bind in the order they appear, so the paging values come last
+                let sql = format!(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE {} ORDER BY name, id LIMIT ? OFFSET ?",
+                    to_mysql(&search.where_clause('$', 1))
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let rows = query.bind(limit).bind(offset).fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Miniature {
+                        id: r.get("id"),
+                        project_id: r.get("project_id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        progress_status: r.get("progress_status"),
+                        notes: r.get("notes"),
+                        points_value: r.get("points_value"),
                         created_at: r.get("created_at"),
                         updated_at: r.get("updated_at"),
                     })
@@ -175,67 +940,568 @@ impl MiniatureRepository {
         }
     }
 
+    /// Number of miniatures matching the search across all pages
+    pub async fn count_search(
+        executor: impl Into<Executor<'_>>,
+        search: &MiniatureSearch,
+    ) -> Result<i64, sqlx::Error> {
+        let name_pattern = search.name_pattern();
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM miniatures WHERE {}",
+                    search.where_clause('?', 1)
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM miniatures WHERE {}",
+                    search.where_clause('$', 1)
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM miniatures WHERE {}",
+                    to_mysql(&search.where_clause('$', 1))
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &name_pattern {
+                    query = query.bind(pattern);
+                }
+                for status in &search.statuses {
+                    query = query.bind(status);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+        }
+    }
+
+    /// Count a project's miniatures grouped by progress status
+    pub async fn count_by_status(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+    ) -> Result<HashMap<ProgressStatus, i64>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT progress_status, COUNT(*) as count FROM miniatures WHERE project_id = ?1 GROUP BY progress_status"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("progress_status"), r.get("count")))
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT progress_status, COUNT(*) as count FROM miniatures WHERE project_id = $1 GROUP BY progress_status"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("progress_status"), r.get("count")))
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT progress_status, COUNT(*) as count FROM miniatures WHERE project_id = ? GROUP BY progress_status"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("progress_status"), r.get("count")))
+                    .collect())
+            }
+        }
+    }
+
+    /// Sum a project's points per miniature type; miniatures without a value count as zero
+    pub async fn points_by_type(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+    ) -> Result<HashMap<MiniatureType, i64>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT miniature_type, COALESCE(SUM(points_value), 0) as points FROM miniatures WHERE project_id = ?1 GROUP BY miniature_type"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("miniature_type"), r.get("points")))
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT miniature_type, COALESCE(SUM(points_value), 0) as points FROM miniatures WHERE project_id = $1 GROUP BY miniature_type"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("miniature_type"), r.get("points")))
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                // MySQL sums integers as DECIMAL
+                let rows = sqlx::query(
+                    "SELECT miniature_type, CAST(COALESCE(SUM(points_value), 0) AS SIGNED) as points FROM miniatures WHERE project_id = ? GROUP BY miniature_type"
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| (r.get("miniature_type"), r.get("points")))
+                    .collect())
+            }
+        }
+    }
+
+    /// Return the subset of `ids` that belong to the given project
+    pub async fn find_ids_in_project(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        ids: &[i64],
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT id FROM miniatures WHERE project_id = ?1 AND id IN ({})",
+                    placeholders('?', 2, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows.into_iter().map(|r| r.get("id")).collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT id FROM miniatures WHERE project_id = $1 AND id IN ({})",
+                    placeholders('$', 2, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows.into_iter().map(|r| r.get("id")).collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "SELECT id FROM miniatures WHERE project_id = ? AND id IN ({})",
+                    unnumbered_placeholders(ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows.into_iter().map(|r| r.get("id")).collect())
+            }
+        }
+    }
+
+    /// Set the progress status of several miniatures in one project with a single UPDATE,
+    /// returning the number of rows changed. Miniatures whose status actually changes get a
+    /// progress history entry.
+    pub async fn bulk_update_status(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        ids: &[i64],
+        progress_status: ProgressStatus,
+    ) -> Result<u64, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let sql = format!(
+                    "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) SELECT id, progress_status, ?1, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') FROM miniatures WHERE progress_status != ?1 AND project_id = ?2 AND id IN ({})",
+                    placeholders('?', 3, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(&progress_status).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                query.execute(&mut *tx).await?;
+
+                let sql = format!(
+                    "UPDATE miniatures SET progress_status = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE project_id = ?2 AND id IN ({})",
+                    placeholders('?', 3, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(&progress_status).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *tx).await?;
+                tx.commit().await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let sql = format!(
+                    "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) SELECT id, progress_status, $1, CURRENT_TIMESTAMP FROM miniatures WHERE progress_status != $1 AND project_id = $2 AND id IN ({})",
+                    placeholders('$', 3, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(&progress_status).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                query.execute(&mut *tx).await?;
+
+                let sql = format!(
+                    "UPDATE miniatures SET progress_status = $1, updated_at = CURRENT_TIMESTAMP WHERE project_id = $2 AND id IN ({})",
+                    placeholders('$', 3, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(&progress_status).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *tx).await?;
+                tx.commit().await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+                let sql = format!(
+                    "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) SELECT id, progress_status, ?, UTC_TIMESTAMP(6) FROM miniatures WHERE progress_status != ? AND project_id = ? AND id IN ({})",
+                    unnumbered_placeholders(ids.len())
+                );
+                let mut query = sqlx::query(&sql)
+                    .bind(&progress_status)
+                    .bind(&progress_status)
+                    .bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                query.execute(&mut *tx).await?;
+
+                let sql = format!(
+                    "UPDATE miniatures SET progress_status = ?, updated_at = UTC_TIMESTAMP(6) WHERE project_id = ? AND id IN ({})",
+                    unnumbered_placeholders(ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(&progress_status).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *tx).await?;
+                tx.commit().await?;
+
+                Ok(result.rows_affected())
+            }
+        }
+    }
+
+    /// Delete several miniatures in one project with a single DELETE, returning the number of
+    /// rows removed. Their photos and recipe links go with them through the foreign keys.
+    pub async fn bulk_delete(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        ids: &[i64],
+    ) -> Result<u64, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "DELETE FROM miniatures WHERE project_id = ?1 AND id IN ({})",
+                    placeholders('?', 2, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *conn).await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "DELETE FROM miniatures WHERE project_id = $1 AND id IN ({})",
+                    placeholders('$', 2, ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *conn).await?;
+
+                Ok(result.rows_affected())
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "DELETE FROM miniatures WHERE project_id = ? AND id IN ({})",
+                    unnumbered_placeholders(ids.len())
+                );
+                let mut query = sqlx::query(&sql).bind(project_id);
+                for id in ids {
+                    query = query.bind(id);
+                }
+                let result = query.execute(&mut *conn).await?;
+
+                Ok(result.rows_affected())
+            }
+        }
+    }
+
+    /// Apply a partial update. When the request carries the `updated_at` the client last saw,
+    /// the row only changes if it still matches, so `None` can also mean a concurrent edit won.
+    /// A change of status is logged to the progress history along with the update.
     pub async fn update(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         id: i64,
-        request: UpdateMiniatureRequest,
+        request: PatchMiniatureRequest,
     ) -> Result<Option<Miniature>, sqlx::Error> {
-        let now = Utc::now();
+        let mut executor = executor.into();
 
         // First, get the current miniature to merge with updates
-        let current = Self::find_by_id(database, id).await?;
+        let current = Self::find_by_id(executor.reborrow(), id).await?;
         let Some(current) = current else {
             return Ok(None);
         };
 
         let name = request.name.unwrap_or(current.name);
-        let progress_status = request.progress_status.unwrap_or(current.progress_status);
-        let notes = request.notes.or(current.notes);
+        let progress_status = request
+            .progress_status
+            .unwrap_or_else(|| current.progress_status.clone());
+        let notes = request.notes.unwrap_or(current.notes);
+        let points_value = request.points_value.unwrap_or(current.points_value);
 
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+                // Timestamps are stored as text, so compare instants rather than strings
                 let row = sqlx::query(
                     r#"
                     UPDATE miniatures 
-                    SET name = ?1, progress_status = ?2, notes = ?3, updated_at = ?4
-                    WHERE id = ?5
-                    RETURNING id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at
+                    SET name = ?1, progress_status = ?2, notes = ?3, points_value = ?4, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?5 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND (?6 IS NULL OR julianday(updated_at) = julianday(?6))
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
                     "#
                 )
                 .bind(&name)
                 .bind(&progress_status)
                 .bind(&notes)
-                .bind(now)
+                .bind(points_value)
                 .bind(id)
-                .fetch_optional(pool)
+                .bind(request.updated_at)
+                .fetch_optional(&mut *tx)
                 .await?;
 
-                Ok(row.map(|r| Miniature {
+                let miniature = row.map(|r| Miniature {
                     id: r.get("id"),
                     project_id: r.get("project_id"),
                     name: r.get("name"),
                     miniature_type: r.get("miniature_type"),
                     progress_status: r.get("progress_status"),
                     notes: r.get("notes"),
+                    points_value: r.get("points_value"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
-                }))
+                });
+
+                if let Some(miniature) = &miniature {
+                    if miniature.progress_status != current.progress_status {
+                        sqlx::query(
+                            "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))"
+                        )
+                        .bind(id)
+                        .bind(&current.progress_status)
+                        .bind(&miniature.progress_status)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+                tx.commit().await?;
+
+                Ok(miniature)
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
                 let row = sqlx::query(
                     r#"
                     UPDATE miniatures 
-                    SET name = $1, progress_status = $2, notes = $3, updated_at = $4
-                    WHERE id = $5
-                    RETURNING id, project_id, name, miniature_type, progress_status, notes, created_at, updated_at
+                    SET name = $1, progress_status = $2, notes = $3, points_value = $4, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $5 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND ($6 IS NULL OR updated_at = $6)
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
                     "#
                 )
                 .bind(&name)
                 .bind(&progress_status)
                 .bind(&notes)
-                .bind(now)
+                .bind(points_value)
+                .bind(id)
+                .bind(request.updated_at)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                let miniature = row.map(|r| Miniature {
+                    id: r.get("id"),
+                    project_id: r.get("project_id"),
+                    name: r.get("name"),
+                    miniature_type: r.get("miniature_type"),
+                    progress_status: r.get("progress_status"),
+                    notes: r.get("notes"),
+                    points_value: r.get("points_value"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                });
+
+                if let Some(miniature) = &miniature {
+                    if miniature.progress_status != current.progress_status {
+                        sqlx::query(
+                            "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP)"
+                        )
+                        .bind(id)
+                        .bind(&current.progress_status)
+                        .bind(&miniature.progress_status)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+                tx.commit().await?;
+
+                Ok(miniature)
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+                // Rows affected counts matched rows, so zero means missing or edited meanwhile
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE miniatures
+                    SET name = ?, progress_status = ?, notes = ?, points_value = ?, updated_at = UTC_TIMESTAMP(6)
+                    WHERE id = ? AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND (? IS NULL OR updated_at = ?)
+                    "#
+                )
+                .bind(&name)
+                .bind(&progress_status)
+                .bind(&notes)
+                .bind(points_value)
+                .bind(id)
+                .bind(request.updated_at)
+                .bind(request.updated_at)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+                let row = if updated > 0 {
+                    sqlx::query(
+                        "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = ?"
+                    )
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                } else {
+                    None
+                };
+
+                let miniature = row.map(|r| Miniature {
+                    id: r.get("id"),
+                    project_id: r.get("project_id"),
+                    name: r.get("name"),
+                    miniature_type: r.get("miniature_type"),
+                    progress_status: r.get("progress_status"),
+                    notes: r.get("notes"),
+                    points_value: r.get("points_value"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                });
+
+                if let Some(miniature) = &miniature {
+                    if miniature.progress_status != current.progress_status {
+                        sqlx::query(
+                            "INSERT INTO progress_history (miniature_id, from_status, to_status, changed_at) VALUES (?, ?, ?, UTC_TIMESTAMP(6))"
+                        )
+                        .bind(id)
+                        .bind(&current.progress_status)
+                        .bind(&miniature.progress_status)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+                tx.commit().await?;
+
+                Ok(miniature)
+            }
+        }
+    }
+
+    /// Move a miniature into another live project. Its photos, recipe links, and history
+    /// reference the miniature rather than the project, so they go with it. `None` means the
+    /// miniature or the target project is missing or deleted.
+    pub async fn move_to_project(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+        target_project_id: i64,
+    ) -> Result<Option<Miniature>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE miniatures
+                    SET project_id = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?2 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND EXISTS (SELECT 1 FROM projects WHERE id = ?1 AND deleted_at IS NULL)
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
+                    "#,
+                )
+                .bind(target_project_id)
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Miniature {
@@ -245,6 +1511,72 @@ impl MiniatureRepository {
                     miniature_type: r.get("miniature_type"),
                     progress_status: r.get("progress_status"),
                     notes: r.get("notes"),
+                    points_value: r.get("points_value"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE miniatures
+                    SET project_id = $1, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $2 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND EXISTS (SELECT 1 FROM projects WHERE id = $1 AND deleted_at IS NULL)
+                    RETURNING id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at
+                    "#,
+                )
+                .bind(target_project_id)
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Miniature {
+                    id: r.get("id"),
+                    project_id: r.get("project_id"),
+                    name: r.get("name"),
+                    miniature_type: r.get("miniature_type"),
+                    progress_status: r.get("progress_status"),
+                    notes: r.get("notes"),
+                    points_value: r.get("points_value"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE miniatures
+                    SET project_id = ?, updated_at = UTC_TIMESTAMP(6)
+                    WHERE id = ? AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)
+                        AND EXISTS (SELECT 1 FROM projects WHERE id = ? AND deleted_at IS NULL)
+                    "#,
+                )
+                .bind(target_project_id)
+                .bind(id)
+                .bind(target_project_id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at FROM miniatures WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Miniature {
+                    id: r.get("id"),
+                    project_id: r.get("project_id"),
+                    name: r.get("name"),
+                    miniature_type: r.get("miniature_type"),
+                    progress_status: r.get("progress_status"),
+                    notes: r.get("notes"),
+                    points_value: r.get("points_value"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
                 }))
@@ -252,20 +1584,28 @@ impl MiniatureRepository {
         }
     }
 
-    pub async fn delete(database: &Database, id: i64) -> Result<bool, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
-                let result = sqlx::query("DELETE FROM miniatures WHERE id = ?1")
+    pub async fn delete(executor: impl Into<Executor<'_>>, id: i64) -> Result<bool, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let result = sqlx::query("DELETE FROM miniatures WHERE id = ?1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)")
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Postgres(mut conn) => {
+                let result = sqlx::query("DELETE FROM miniatures WHERE id = $1 AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)")
                     .bind(id)
-                    .execute(pool)
+                    .execute(&mut *conn)
                     .await?;
 
                 Ok(result.rows_affected() > 0)
             }
-            Database::Postgres(pool) => {
-                let result = sqlx::query("DELETE FROM miniatures WHERE id = $1")
+            Connection::Mysql(mut conn) => {
+                let result = sqlx::query("DELETE FROM miniatures WHERE id = ? AND project_id IN (SELECT id FROM projects WHERE deleted_at IS NULL)")
                     .bind(id)
-                    .execute(pool)
+                    .execute(&mut *conn)
                     .await?;
 
                 Ok(result.rows_affected() > 0)
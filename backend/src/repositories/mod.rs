@@ -1,11 +1,84 @@
+pub mod idempotency_key_repository;
 pub mod miniature_recipe_repository;
 pub mod miniature_repository;
+pub mod paint_repository;
 pub mod photo_repository;
+pub mod progress_history_repository;
 pub mod project_repository;
 pub mod recipe_repository;
 
+pub use idempotency_key_repository::IdempotencyKeyRepository;
 pub use miniature_recipe_repository::MiniatureRecipeRepository;
 pub use miniature_repository::MiniatureRepository;
+pub use paint_repository::PaintRepository;
 pub use photo_repository::PhotoRepository;
+pub use progress_history_repository::ProgressHistoryRepository;
 pub use project_repository::ProjectRepository;
 pub use recipe_repository::RecipeRepository;
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+// Timestamps come from the database clock, not the app server's. SQLite writes
+// `strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')`: the RFC 3339 text sqlx uses for
+// `DateTime<Utc>`, to the millisecond, so older rows still sort and decode alongside new ones.
+// PostgreSQL uses `CURRENT_TIMESTAMP`, and MySQL `UTC_TIMESTAMP(6)` into DATETIME(6) columns.
+
+/// Sort direction for list queries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Build a comma-separated list of numbered bind placeholders (e.g. `?2, ?3` or `$2, $3`)
+/// for dynamic `IN (...)` clauses, starting at the given parameter index.
+pub(crate) fn placeholders(prefix: char, start: usize, count: usize) -> String {
+    (start..start + count)
+        .map(|i| format!("{}{}", prefix, i))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The MySQL form of `placeholders`: MySQL only has unnumbered `?` parameters, bound in the
+/// order they appear
+pub(crate) fn unnumbered_placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Adapt a clause built with `$N` placeholders for MySQL: each becomes a bare `?`, so values
+/// must be bound in the order the placeholders appear, and `ESCAPE '\'` is dropped because
+/// backslash is already MySQL's LIKE escape and its string literals treat it as one too
+pub(crate) fn to_mysql(clause: &str) -> String {
+    let clause = clause.replace(" ESCAPE '\\'", "");
+    let mut sql = String::with_capacity(clause.len());
+    let mut chars = clause.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' && chars.peek().is_some_and(char::is_ascii_digit) {
+            while chars.next_if(char::is_ascii_digit).is_some() {}
+            sql.push('?');
+        } else {
+            sql.push(c);
+        }
+    }
+    sql
+}
+
+/// Escape LIKE wildcards so user input only ever matches literally (use with `ESCAPE '\'`)
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,361 @@
+use crate::database::{Connection, Executor};
+use shared_types::{CreatePaintRequest, Paint, UpdatePaintRequest};
+use sqlx::Row;
+
+pub struct PaintRepository;
+
+impl PaintRepository {
+    /// Every paint, by name and then brand
+    pub async fn find_all(executor: impl Into<Executor<'_>>) -> Result<Vec<Paint>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints ORDER BY LOWER(name), LOWER(brand), id",
+                )
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Paint {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        brand: r.get("brand"),
+                        quantity_remaining: r.get("quantity_remaining"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints ORDER BY LOWER(name), LOWER(brand), id",
+                )
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Paint {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        brand: r.get("brand"),
+                        quantity_remaining: r.get("quantity_remaining"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints ORDER BY LOWER(name), LOWER(brand), id",
+                )
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Paint {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        brand: r.get("brand"),
+                        quantity_remaining: r.get("quantity_remaining"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    pub async fn find_by_id(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Paint>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints WHERE id = ?1",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints WHERE id = $1",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+        }
+    }
+
+    pub async fn create(
+        executor: impl Into<Executor<'_>>,
+        request: CreatePaintRequest,
+    ) -> Result<Paint, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO paints (name, brand, quantity_remaining, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+                    RETURNING id, name, brand, quantity_remaining, created_at, updated_at
+                    "#,
+                )
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Paint {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    brand: row.get("brand"),
+                    quantity_remaining: row.get("quantity_remaining"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO paints (name, brand, quantity_remaining, created_at, updated_at)
+                    VALUES ($1, $2, $3, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                    RETURNING id, name, brand, quantity_remaining, created_at, updated_at
+                    "#,
+                )
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Paint {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    brand: row.get("brand"),
+                    quantity_remaining: row.get("quantity_remaining"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            }
+            Connection::Mysql(mut conn) => {
+                // No RETURNING in MySQL, so read the row back by its new id
+                let id = sqlx::query(
+                    r#"
+                    INSERT INTO paints (name, brand, quantity_remaining, created_at, updated_at)
+                    VALUES (?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
+                    "#,
+                )
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .execute(&mut *conn)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Paint {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    brand: row.get("brand"),
+                    quantity_remaining: row.get("quantity_remaining"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            }
+        }
+    }
+
+    /// Replace every field of a paint, or `None` when it doesn't exist
+    pub async fn update(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+        request: UpdatePaintRequest,
+    ) -> Result<Option<Paint>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE paints
+                    SET name = ?2, brand = ?3, quantity_remaining = ?4, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?1
+                    RETURNING id, name, brand, quantity_remaining, created_at, updated_at
+                    "#,
+                )
+                .bind(id)
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE paints
+                    SET name = $2, brand = $3, quantity_remaining = $4, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $1
+                    RETURNING id, name, brand, quantity_remaining, created_at, updated_at
+                    "#,
+                )
+                .bind(id)
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE paints
+                    SET name = ?, brand = ?, quantity_remaining = ?, updated_at = UTC_TIMESTAMP(6)
+                    WHERE id = ?
+                    "#,
+                )
+                .bind(&request.name)
+                .bind(&request.brand)
+                .bind(request.quantity_remaining)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, name, brand, quantity_remaining, created_at, updated_at FROM paints WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Paint {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    brand: r.get("brand"),
+                    quantity_remaining: r.get("quantity_remaining"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                }))
+            }
+        }
+    }
+
+    pub async fn delete(executor: impl Into<Executor<'_>>, id: i64) -> Result<bool, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let result = sqlx::query("DELETE FROM paints WHERE id = ?1")
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Postgres(mut conn) => {
+                let result = sqlx::query("DELETE FROM paints WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Mysql(mut conn) => {
+                let result = sqlx::query("DELETE FROM paints WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+        }
+    }
+
+    /// Names of the paints with any quantity left, as stored
+    pub async fn owned_names(
+        executor: impl Into<Executor<'_>>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                sqlx::query_scalar("SELECT name FROM paints WHERE quantity_remaining > 0")
+                    .fetch_all(&mut *conn)
+                    .await
+            }
+            Connection::Postgres(mut conn) => {
+                sqlx::query_scalar("SELECT name FROM paints WHERE quantity_remaining > 0")
+                    .fetch_all(&mut *conn)
+                    .await
+            }
+            Connection::Mysql(mut conn) => {
+                sqlx::query_scalar("SELECT name FROM paints WHERE quantity_remaining > 0")
+                    .fetch_all(&mut *conn)
+                    .await
+            }
+        }
+    }
+}
@@ -1,28 +1,55 @@
-use crate::database::Database;
-use chrono::Utc;
+use crate::database::{Connection, Executor};
+use chrono::{DateTime, Utc};
 use shared_types::Photo;
-use sqlx::{Pool, Postgres, Row, Sqlite};
+use sqlx::Connection as _;
+use sqlx::Row;
+
+/// Percent-encoded forms of `.`, `/`, `\` and `%` (for double encoding), lowercased
+const ENCODED_TRAVERSAL: &[&str] = &["%2e", "%2f", "%5c", "%25"];
+
+/// Reject stored photo paths that could escape the storage root. Storage backends sanitize
+/// on write too, but the database should never record a dangerous path regardless of caller.
+pub(crate) fn validate_photo_path(file_path: &str) -> Result<(), sqlx::Error> {
+    let lowercase = file_path.to_lowercase();
+
+    if file_path.contains("..")
+        || file_path.contains('\\')
+        || ENCODED_TRAVERSAL.iter().any(|seq| lowercase.contains(seq))
+    {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "Photo path {:?} contains path traversal sequences",
+            file_path
+        )));
+    }
+
+    Ok(())
+}
 
 pub struct PhotoRepository;
 
 impl PhotoRepository {
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
         filename: String,
         file_path: String,
         file_size: i64,
         mime_type: String,
+        content_hash: Option<String>,
+        thumbnail_path: Option<String>,
     ) -> Result<Photo, sqlx::Error> {
-        let now = Utc::now();
-
-        match database {
-            Database::Sqlite(pool) => {
+        validate_photo_path(&file_path)?;
+        if let Some(thumbnail_path) = &thumbnail_path {
+            validate_photo_path(thumbnail_path)?;
+        }
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO photos (miniature_id, filename, file_path, file_size, mime_type, uploaded_at)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                    RETURNING id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at
+                    INSERT INTO photos (miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path)
+                    VALUES (?1, ?2, ?3, ?4, ?5, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), ?6, ?7)
+                    RETURNING id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary
                     "#
                 )
                 .bind(miniature_id)
@@ -30,8 +57,9 @@ impl PhotoRepository {
                 .bind(&file_path)
                 .bind(file_size)
                 .bind(&mime_type)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(&content_hash)
+                .bind(&thumbnail_path)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Photo {
@@ -42,14 +70,17 @@ impl PhotoRepository {
                     file_size: row.get("file_size"),
                     mime_type: row.get("mime_type"),
                     uploaded_at: row.get("uploaded_at"),
+                    content_hash: row.get("content_hash"),
+                    thumbnail_path: row.get("thumbnail_path"),
+                    is_primary: row.get("is_primary"),
                 })
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO photos (miniature_id, filename, file_path, file_size, mime_type, uploaded_at)
-                    VALUES ($1, $2, $3, $4, $5, $6)
-                    RETURNING id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at
+                    INSERT INTO photos (miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path)
+                    VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, $6, $7)
+                    RETURNING id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary
                     "#
                 )
                 .bind(miniature_id)
@@ -57,8 +88,9 @@ impl PhotoRepository {
                 .bind(&file_path)
                 .bind(file_size)
                 .bind(&mime_type)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(&content_hash)
+                .bind(&thumbnail_path)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Photo {
@@ -69,19 +101,85 @@ impl PhotoRepository {
                     file_size: row.get("file_size"),
                     mime_type: row.get("mime_type"),
                     uploaded_at: row.get("uploaded_at"),
+                    content_hash: row.get("content_hash"),
+                    thumbnail_path: row.get("thumbnail_path"),
+                    is_primary: row.get("is_primary"),
+                })
+            }
+            Connection::Mysql(mut conn) => {
+                // No RETURNING in MySQL, so read the row back by its new id
+                let id = sqlx::query(
+                    r#"
+                    INSERT INTO photos (miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path)
+                    VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP(6), ?, ?)
+                    "#
+                )
+                .bind(miniature_id)
+                .bind(&filename)
+                .bind(&file_path)
+                .bind(file_size)
+                .bind(&mime_type)
+                .bind(&content_hash)
+                .bind(&thumbnail_path)
+                .execute(&mut *conn)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Photo {
+                    id: row.get("id"),
+                    miniature_id: row.get("miniature_id"),
+                    filename: row.get("filename"),
+                    file_path: row.get("file_path"),
+                    file_size: row.get("file_size"),
+                    mime_type: row.get("mime_type"),
+                    uploaded_at: row.get("uploaded_at"),
+                    content_hash: row.get("content_hash"),
+                    thumbnail_path: row.get("thumbnail_path"),
+                    is_primary: row.get("is_primary"),
                 })
             }
         }
     }
 
-    pub async fn find_by_id(database: &Database, id: i64) -> Result<Option<Photo>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+    pub async fn find_by_id(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Photo>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE id = ?1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)"
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Photo {
+                    id: r.get("id"),
+                    miniature_id: r.get("miniature_id"),
+                    filename: r.get("filename"),
+                    file_path: r.get("file_path"),
+                    file_size: r.get("file_size"),
+                    mime_type: r.get("mime_type"),
+                    uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at FROM photos WHERE id = ?1"
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE id = $1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Photo {
@@ -92,14 +190,17 @@ impl PhotoRepository {
                     file_size: r.get("file_size"),
                     mime_type: r.get("mime_type"),
                     uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Mysql(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at FROM photos WHERE id = $1"
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE id = ? AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Photo {
@@ -110,23 +211,36 @@ impl PhotoRepository {
                     file_size: r.get("file_size"),
                     mime_type: r.get("mime_type"),
                     uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
                 }))
             }
         }
     }
 
+    /// Photos of a miniature in upload order, optionally with its primary photo first
     pub async fn find_by_miniature_id(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
+        primary_first: bool,
     ) -> Result<Vec<Photo>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
-                let rows = sqlx::query(
-                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at FROM photos WHERE miniature_id = ?1 ORDER BY uploaded_at"
-                )
-                .bind(miniature_id)
-                .fetch_all(pool)
-                .await?;
+        let order_by = if primary_first {
+            "is_primary DESC, uploaded_at"
+        } else {
+            "uploaded_at"
+        };
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = ?1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY {}",
+                    order_by
+                );
+                let rows = sqlx::query(&sql)
+                    .bind(miniature_id)
+                    .fetch_all(&mut *conn)
+                    .await?;
 
                 Ok(rows
                     .into_iter()
@@ -138,16 +252,47 @@ impl PhotoRepository {
                         file_size: r.get("file_size"),
                         mime_type: r.get("mime_type"),
                         uploaded_at: r.get("uploaded_at"),
+                        content_hash: r.get("content_hash"),
+                        thumbnail_path: r.get("thumbnail_path"),
+                        is_primary: r.get("is_primary"),
                     })
                     .collect())
             }
-            Database::Postgres(pool) => {
-                let rows = sqlx::query(
-                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at FROM photos WHERE miniature_id = $1 ORDER BY uploaded_at"
-                )
-                .bind(miniature_id)
-                .fetch_all(pool)
-                .await?;
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = $1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY {}",
+                    order_by
+                );
+                let rows = sqlx::query(&sql)
+                    .bind(miniature_id)
+                    .fetch_all(&mut *conn)
+                    .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Photo {
+                        id: r.get("id"),
+                        miniature_id: r.get("miniature_id"),
+                        filename: r.get("filename"),
+                        file_path: r.get("file_path"),
+                        file_size: r.get("file_size"),
+                        mime_type: r.get("mime_type"),
+                        uploaded_at: r.get("uploaded_at"),
+                        content_hash: r.get("content_hash"),
+                        thumbnail_path: r.get("thumbnail_path"),
+                        is_primary: r.get("is_primary"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = ? AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY {}",
+                    order_by
+                );
+                let rows = sqlx::query(&sql)
+                    .bind(miniature_id)
+                    .fetch_all(&mut *conn)
+                    .await?;
 
                 Ok(rows
                     .into_iter()
@@ -159,67 +304,322 @@ impl PhotoRepository {
                         file_size: r.get("file_size"),
                         mime_type: r.get("mime_type"),
                         uploaded_at: r.get("uploaded_at"),
+                        content_hash: r.get("content_hash"),
+                        thumbnail_path: r.get("thumbnail_path"),
+                        is_primary: r.get("is_primary"),
                     })
                     .collect())
             }
         }
     }
 
-    pub async fn delete(database: &Database, id: i64) -> Result<Option<Photo>, sqlx::Error> {
+    /// Count a miniature's photos uploaded within the last `window_seconds`, by the database
+    /// clock, along with when the oldest of them was uploaded
+    pub async fn recent_uploads(
+        executor: impl Into<Executor<'_>>,
+        miniature_id: i64,
+        window_seconds: i64,
+    ) -> Result<(i64, Option<DateTime<Utc>>), sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT COUNT(*) AS count, MIN(uploaded_at) AS oldest FROM photos WHERE miniature_id = ?1 AND julianday(uploaded_at) > julianday('now', ?2)"
+                )
+                .bind(miniature_id)
+                .bind(format!("-{} seconds", window_seconds))
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok((row.get("count"), row.get("oldest")))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT COUNT(*) AS count, MIN(uploaded_at) AS oldest FROM photos WHERE miniature_id = $1 AND uploaded_at > CURRENT_TIMESTAMP - $2 * INTERVAL '1 second'"
+                )
+                .bind(miniature_id)
+                .bind(window_seconds)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok((row.get("count"), row.get("oldest")))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT COUNT(*) AS count, MIN(uploaded_at) AS oldest FROM photos WHERE miniature_id = ? AND uploaded_at > UTC_TIMESTAMP(6) - INTERVAL ? SECOND"
+                )
+                .bind(miniature_id)
+                .bind(window_seconds)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok((row.get("count"), row.get("oldest")))
+            }
+        }
+    }
+
+    /// Find an existing upload of the same file content to the given miniature
+    pub async fn find_by_miniature_and_hash(
+        executor: impl Into<Executor<'_>>,
+        miniature_id: i64,
+        content_hash: &str,
+    ) -> Result<Option<Photo>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = ?1 AND content_hash = ?2 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY id LIMIT 1"
+                )
+                .bind(miniature_id)
+                .bind(content_hash)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Photo {
+                    id: r.get("id"),
+                    miniature_id: r.get("miniature_id"),
+                    filename: r.get("filename"),
+                    file_path: r.get("file_path"),
+                    file_size: r.get("file_size"),
+                    mime_type: r.get("mime_type"),
+                    uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = $1 AND content_hash = $2 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY id LIMIT 1"
+                )
+                .bind(miniature_id)
+                .bind(content_hash)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Photo {
+                    id: r.get("id"),
+                    miniature_id: r.get("miniature_id"),
+                    filename: r.get("filename"),
+                    file_path: r.get("file_path"),
+                    file_size: r.get("file_size"),
+                    mime_type: r.get("mime_type"),
+                    uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, miniature_id, filename, file_path, file_size, mime_type, uploaded_at, content_hash, thumbnail_path, is_primary FROM photos WHERE miniature_id = ? AND content_hash = ? AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL) ORDER BY id LIMIT 1"
+                )
+                .bind(miniature_id)
+                .bind(content_hash)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Photo {
+                    id: r.get("id"),
+                    miniature_id: r.get("miniature_id"),
+                    filename: r.get("filename"),
+                    file_path: r.get("file_path"),
+                    file_size: r.get("file_size"),
+                    mime_type: r.get("mime_type"),
+                    uploaded_at: r.get("uploaded_at"),
+                    content_hash: r.get("content_hash"),
+                    thumbnail_path: r.get("thumbnail_path"),
+                    is_primary: r.get("is_primary"),
+                }))
+            }
+        }
+    }
+
+    pub async fn delete(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Photo>, sqlx::Error> {
+        let mut executor = executor.into();
         // First get the photo to return its details for cleanup
-        let photo = Self::find_by_id(database, id).await?;
+        let Some(deleted) = Self::find_by_id(executor.reborrow(), id).await? else {
+            return Ok(None);
+        };
 
-        if photo.is_some() {
-            match database {
-                Database::Sqlite(pool) => {
-                    let result = sqlx::query("DELETE FROM photos WHERE id = ?1")
+        match executor.reborrow().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let result = sqlx::query("DELETE FROM photos WHERE id = ?1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)")
                         .bind(id)
-                        .execute(pool)
+                        .execute(&mut *tx)
                         .await?;
+                if result.rows_affected() == 0 {
+                    return Ok(None);
+                }
 
-                    if result.rows_affected() > 0 {
-                        Ok(photo)
-                    } else {
-                        Ok(None)
-                    }
+                // Hand the primary flag to the most recent remaining photo, if any
+                if deleted.is_primary {
+                    sqlx::query(
+                            "UPDATE photos SET is_primary = TRUE WHERE id = (SELECT id FROM photos WHERE miniature_id = ?1 ORDER BY uploaded_at DESC, id DESC LIMIT 1)",
+                        )
+                        .bind(deleted.miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
                 }
-                Database::Postgres(pool) => {
-                    let result = sqlx::query("DELETE FROM photos WHERE id = $1")
+
+                tx.commit().await?;
+                Ok(Some(deleted))
+            }
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let result = sqlx::query("DELETE FROM photos WHERE id = $1 AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)")
                         .bind(id)
-                        .execute(pool)
+                        .execute(&mut *tx)
                         .await?;
+                if result.rows_affected() == 0 {
+                    return Ok(None);
+                }
 
-                    if result.rows_affected() > 0 {
-                        Ok(photo)
-                    } else {
-                        Ok(None)
-                    }
+                // Hand the primary flag to the most recent remaining photo, if any
+                if deleted.is_primary {
+                    sqlx::query(
+                            "UPDATE photos SET is_primary = TRUE WHERE id = (SELECT id FROM photos WHERE miniature_id = $1 ORDER BY uploaded_at DESC, id DESC LIMIT 1)",
+                        )
+                        .bind(deleted.miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
                 }
+
+                tx.commit().await?;
+                Ok(Some(deleted))
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let result = sqlx::query("DELETE FROM photos WHERE id = ? AND miniature_id IN (SELECT m.id FROM miniatures m JOIN projects p ON p.id = m.project_id WHERE p.deleted_at IS NULL)")
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                if result.rows_affected() == 0 {
+                    return Ok(None);
+                }
+
+                // Hand the primary flag to the most recent remaining photo, if any. MySQL can't
+                // select from the table being updated, but can order and limit the update itself.
+                if deleted.is_primary {
+                    sqlx::query(
+                            "UPDATE photos SET is_primary = TRUE WHERE miniature_id = ? ORDER BY uploaded_at DESC, id DESC LIMIT 1",
+                        )
+                        .bind(deleted.miniature_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+
+                tx.commit().await?;
+                Ok(Some(deleted))
+            }
+        }
+    }
+
+    /// Make a photo its miniature's primary photo, clearing the flag on the others
+    pub async fn set_primary(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Photo>, sqlx::Error> {
+        let mut executor = executor.into();
+        let Some(photo) = Self::find_by_id(executor.reborrow(), id).await? else {
+            return Ok(None);
+        };
+
+        match executor.reborrow().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                // Clear first so the one-primary-per-miniature index is never violated
+                sqlx::query(
+                    "UPDATE photos SET is_primary = FALSE WHERE miniature_id = ?1 AND id != ?2",
+                )
+                .bind(photo.miniature_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query("UPDATE photos SET is_primary = TRUE WHERE id = ?1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                tx.commit().await?;
+            }
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                // Clear first so the one-primary-per-miniature index is never violated
+                sqlx::query(
+                    "UPDATE photos SET is_primary = FALSE WHERE miniature_id = $1 AND id != $2",
+                )
+                .bind(photo.miniature_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query("UPDATE photos SET is_primary = TRUE WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                tx.commit().await?;
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                // Clear first so the one-primary-per-miniature index is never violated
+                sqlx::query(
+                    "UPDATE photos SET is_primary = FALSE WHERE miniature_id = ? AND id != ?",
+                )
+                .bind(photo.miniature_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query("UPDATE photos SET is_primary = TRUE WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                tx.commit().await?;
             }
-        } else {
-            Ok(None)
         }
+
+        Ok(Some(Photo {
+            is_primary: true,
+            ..photo
+        }))
     }
 
+    /// Delete every photo of a miniature, returning them so their files can be removed too
     pub async fn delete_by_miniature_id(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_id: i64,
     ) -> Result<Vec<Photo>, sqlx::Error> {
+        let mut executor = executor.into();
         // First get all photos to return their details for cleanup
-        let photos = Self::find_by_miniature_id(database, miniature_id).await?;
+        let photos = Self::find_by_miniature_id(executor.reborrow(), miniature_id, false).await?;
 
         if !photos.is_empty() {
-            match database {
-                Database::Sqlite(pool) => {
+            match executor.reborrow().acquire().await? {
+                Connection::Sqlite(mut conn) => {
                     sqlx::query("DELETE FROM photos WHERE miniature_id = ?1")
                         .bind(miniature_id)
-                        .execute(pool)
+                        .execute(&mut *conn)
                         .await?;
                 }
-                Database::Postgres(pool) => {
+                Connection::Postgres(mut conn) => {
                     sqlx::query("DELETE FROM photos WHERE miniature_id = $1")
                         .bind(miniature_id)
-                        .execute(pool)
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                Connection::Mysql(mut conn) => {
+                    sqlx::query("DELETE FROM photos WHERE miniature_id = ?")
+                        .bind(miniature_id)
+                        .execute(&mut *conn)
                         .await?;
                 }
             }
@@ -227,4 +627,34 @@ impl PhotoRepository {
 
         Ok(photos)
     }
+
+    /// Storage paths of every photo and thumbnail on record, soft-deleted projects included.
+    /// Reads the primary, since a lagging replica could miss a photo just uploaded.
+    pub async fn stored_paths(
+        executor: impl Into<Executor<'_>>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                sqlx::query_scalar(
+                    "SELECT file_path FROM photos UNION SELECT thumbnail_path FROM photos WHERE thumbnail_path IS NOT NULL",
+                )
+                .fetch_all(&mut *conn)
+                .await
+            }
+            Connection::Postgres(mut conn) => {
+                sqlx::query_scalar(
+                    "SELECT file_path FROM photos UNION SELECT thumbnail_path FROM photos WHERE thumbnail_path IS NOT NULL",
+                )
+                .fetch_all(&mut *conn)
+                .await
+            }
+            Connection::Mysql(mut conn) => {
+                sqlx::query_scalar(
+                    "SELECT file_path FROM photos UNION SELECT thumbnail_path FROM photos WHERE thumbnail_path IS NOT NULL",
+                )
+                .fetch_all(&mut *conn)
+                .await
+            }
+        }
+    }
 }
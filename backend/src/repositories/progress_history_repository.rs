@@ -0,0 +1,75 @@
+use crate::database::{Connection, Executor};
+use shared_types::ProgressChange;
+use sqlx::Row;
+
+/// Reads the progress status log. Rows are written by `MiniatureRepository` alongside the
+/// status changes they record.
+pub struct ProgressHistoryRepository;
+
+impl ProgressHistoryRepository {
+    /// A miniature's status changes, oldest first
+    pub async fn find_by_miniature_id(
+        executor: impl Into<Executor<'_>>,
+        miniature_id: i64,
+    ) -> Result<Vec<ProgressChange>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, miniature_id, from_status, to_status, changed_at FROM progress_history WHERE miniature_id = ?1 ORDER BY changed_at, id",
+                )
+                .bind(miniature_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| ProgressChange {
+                        id: r.get("id"),
+                        miniature_id: r.get("miniature_id"),
+                        from_status: r.get("from_status"),
+                        to_status: r.get("to_status"),
+                        changed_at: r.get("changed_at"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, miniature_id, from_status, to_status, changed_at FROM progress_history WHERE miniature_id = $1 ORDER BY changed_at, id",
+                )
+                .bind(miniature_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| ProgressChange {
+                        id: r.get("id"),
+                        miniature_id: r.get("miniature_id"),
+                        from_status: r.get("from_status"),
+                        to_status: r.get("to_status"),
+                        changed_at: r.get("changed_at"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, miniature_id, from_status, to_status, changed_at FROM progress_history WHERE miniature_id = ? ORDER BY changed_at, id",
+                )
+                .bind(miniature_id)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| ProgressChange {
+                        id: r.get("id"),
+                        miniature_id: r.get("miniature_id"),
+                        from_status: r.get("from_status"),
+                        to_status: r.get("to_status"),
+                        changed_at: r.get("changed_at"),
+                    })
+                    .collect())
+            }
+        }
+    }
+}
@@ -1,33 +1,138 @@
-use crate::database::Database;
-use chrono::Utc;
-use shared_types::{CreateProjectRequest, GameSystem, Project, UpdateProjectRequest};
-use sqlx::{Pool, Postgres, Row, Sqlite};
+use super::{escape_like, to_mysql, SortOrder};
+use crate::database::{Connection, Executor};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use shared_types::{
+    ArmyUsage, CreateProjectRequest, GameSystem, PatchProjectRequest, ProgressStatus, Project,
+    ProjectExport, ProjectImportSummary,
+};
+use sqlx::Connection as _;
+use sqlx::Row;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Columns the projects list may be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSortField {
+    Name,
+    CreatedAt,
+    UpdatedAt,
+    Army,
+}
+
+impl ProjectSortField {
+    fn column(&self) -> &'static str {
+        match self {
+            ProjectSortField::Name => "name",
+            ProjectSortField::CreatedAt => "created_at",
+            ProjectSortField::UpdatedAt => "updated_at",
+            ProjectSortField::Army => "army",
+        }
+    }
+}
+
+/// Build an ORDER BY clause from whitelisted columns only.
+/// Without an explicit sort field the list keeps its grouping by system, army, and name;
+/// game systems are stored as their snake_case names, so they group alphabetically.
+fn order_by_clause(sort: Option<ProjectSortField>, order: SortOrder) -> String {
+    let direction = order.as_sql();
+    match sort {
+        Some(field) => format!("{} {}, id {}", field.column(), direction, direction),
+        None => format!(
+            "game_system {}, army {}, name {}",
+            direction, direction, direction
+        ),
+    }
+}
+
+/// Optional filters applied when listing projects
+#[derive(Debug, Default)]
+pub struct ProjectFilter {
+    pub include_deleted: bool,
+    pub include_archived: bool,
+    pub tag: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl ProjectFilter {
+    /// WHERE clause for the filters that are set, numbering bind placeholders from `start`.
+    /// Values must be bound in the same order: tag pattern, created_after, created_before.
+    fn where_clause(&self, prefix: char, start: usize) -> String {
+        let mut conditions = Vec::new();
+        let mut index = start;
+
+        if !self.include_deleted {
+            conditions.push("deleted_at IS NULL".to_string());
+        }
+        if !self.include_archived {
+            conditions.push("archived = FALSE".to_string());
+        }
+        if self.tag.is_some() {
+            conditions.push(format!("tags LIKE {}{} ESCAPE '\\'", prefix, index));
+            index += 1;
+        }
+        if self.created_after.is_some() {
+            conditions.push(format!("created_at >= {}{}", prefix, index));
+            index += 1;
+        }
+        if self.created_before.is_some() {
+            conditions.push(format!("created_at <= {}{}", prefix, index));
+        }
+
+        if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        }
+    }
+
+    /// LIKE pattern finding the tag as a whole element of the JSON-encoded tags array
+    fn tag_pattern(&self) -> Option<String> {
+        self.tag.as_ref().map(|tag| {
+            let quoted = serde_json::to_string(&tag.trim().to_lowercase()).unwrap_or_default();
+            format!("%{}%", escape_like(&quoted))
+        })
+    }
+}
+
+/// Lowercase, trim, and dedupe tags, keeping the order they were first given in
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
 
 pub struct ProjectRepository;
 
 impl ProjectRepository {
     pub async fn create(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         request: CreateProjectRequest,
     ) -> Result<Project, sqlx::Error> {
-        let now = Utc::now();
+        let tags_json = serde_json::to_string(&normalize_tags(&request.tags)).unwrap_or_default();
 
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO projects (name, game_system, army, description, created_at, updated_at)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                    RETURNING id, name, game_system, army, description, created_at, updated_at
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
                     "#
                 )
                 .bind(&request.name)
                 .bind(&request.game_system)
                 .bind(&request.army)
                 .bind(&request.description)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(&tags_json)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Project {
@@ -38,23 +143,25 @@ impl ProjectRepository {
                     description: row.get("description"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
                 })
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
-                    INSERT INTO projects (name, game_system, army, description, created_at, updated_at)
-                    VALUES ($1, $2, $3, $4, $5, $6)
-                    RETURNING id, name, game_system, army, description, created_at, updated_at
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
                     "#
                 )
                 .bind(&request.name)
                 .bind(&request.game_system)
                 .bind(&request.army)
                 .bind(&request.description)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .bind(&tags_json)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 Ok(Project {
@@ -65,19 +172,83 @@ impl ProjectRepository {
                     description: row.get("description"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
+                })
+            }
+            Connection::Mysql(mut conn) => {
+                // No RETURNING in MySQL, so read the row back by its new id
+                let id = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
+                    "#
+                )
+                .bind(&request.name)
+                .bind(&request.game_system)
+                .bind(&request.army)
+                .bind(&request.description)
+                .bind(&tags_json)
+                .execute(&mut *conn)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Ok(Project {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    game_system: row.get("game_system"),
+                    army: row.get("army"),
+                    description: row.get("description"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
                 })
             }
         }
     }
 
-    pub async fn find_by_id(database: &Database, id: i64) -> Result<Option<Project>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+    pub async fn find_by_id(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?1 AND deleted_at IS NULL"
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, name, game_system, army, description, created_at, updated_at FROM projects WHERE id = ?1"
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = $1 AND deleted_at IS NULL"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Project {
@@ -88,14 +259,17 @@ impl ProjectRepository {
                     description: r.get("description"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Mysql(mut conn) => {
                 let row = sqlx::query(
-                    "SELECT id, name, game_system, army, description, created_at, updated_at FROM projects WHERE id = $1"
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ? AND deleted_at IS NULL"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Project {
@@ -106,18 +280,144 @@ impl ProjectRepository {
                     description: r.get("description"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
                 }))
             }
         }
     }
 
-    pub async fn find_all(database: &Database) -> Result<Vec<Project>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+    /// Id of a live project with this name, game system, and army, names and armies compared
+    /// case-insensitively
+    pub async fn find_duplicate(
+        executor: impl Into<Executor<'_>>,
+        name: &str,
+        game_system: &GameSystem,
+        army: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id FROM projects WHERE LOWER(name) = LOWER(?1) AND game_system = ?2 AND LOWER(army) = LOWER(?3) AND deleted_at IS NULL ORDER BY id LIMIT 1"
+                )
+                .bind(name)
+                .bind(game_system)
+                .bind(army)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("id")))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id FROM projects WHERE LOWER(name) = LOWER($1) AND game_system = $2 AND LOWER(army) = LOWER($3) AND deleted_at IS NULL ORDER BY id LIMIT 1"
+                )
+                .bind(name)
+                .bind(game_system)
+                .bind(army)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("id")))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id FROM projects WHERE LOWER(name) = LOWER(?) AND game_system = ? AND LOWER(army) = LOWER(?) AND deleted_at IS NULL ORDER BY id LIMIT 1"
+                )
+                .bind(name)
+                .bind(game_system)
+                .bind(army)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| r.get("id")))
+            }
+        }
+    }
+
+    /// Add or drop the unique index on live projects' name, game system, and army (name and
+    /// army lowercased), so two requests can't both pass `find_duplicate` and insert the same
+    /// project. Adding it fails while duplicates exist.
+    pub async fn set_unique_name_index(
+        executor: impl Into<Executor<'_>>,
+        enabled: bool,
+    ) -> Result<(), sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let statement = if enabled {
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_unique_name ON projects (LOWER(name), game_system, LOWER(army)) WHERE deleted_at IS NULL"
+                } else {
+                    "DROP INDEX IF EXISTS idx_projects_unique_name"
+                };
+                sqlx::query(statement).execute(&mut *conn).await?;
+            }
+            Connection::Postgres(mut conn) => {
+                let statement = if enabled {
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_unique_name ON projects (LOWER(name), game_system, LOWER(army)) WHERE deleted_at IS NULL"
+                } else {
+                    "DROP INDEX IF EXISTS idx_projects_unique_name"
+                };
+                sqlx::query(statement).execute(&mut *conn).await?;
+            }
+            Connection::Mysql(mut conn) => {
+                // No partial indexes or IF NOT EXISTS here. Deleted rows key on NULL instead,
+                // which a unique index never treats as equal.
+                let exists: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM information_schema.statistics WHERE table_schema = DATABASE() AND table_name = 'projects' AND index_name = 'idx_projects_unique_name'",
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+                let statement = match (enabled, exists > 0) {
+                    (true, false) => "CREATE UNIQUE INDEX idx_projects_unique_name ON projects ((LOWER(name)), game_system, (LOWER(army)), (IF(deleted_at IS NULL, 1, NULL)))",
+                    (false, true) => "DROP INDEX idx_projects_unique_name ON projects",
+                    _ => return Ok(()),
+                };
+                sqlx::query(statement).execute(&mut *conn).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a write failed on the unique index added by `set_unique_name_index`
+    pub fn is_duplicate_name(error: &sqlx::Error) -> bool {
+        error.as_database_error().is_some_and(|e| {
+            e.is_unique_violation() && e.message().contains("idx_projects_unique_name")
+        })
+    }
+
+    #[allow(dead_code)]
+    pub async fn find_all(executor: impl Into<Executor<'_>>) -> Result<Vec<Project>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE deleted_at IS NULL ORDER BY game_system, army, name"
+                )
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Project {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        game_system: r.get("game_system"),
+                        army: r.get("army"),
+                        description: r.get("description"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
-                    "SELECT id, name, game_system, army, description, created_at, updated_at FROM projects ORDER BY game_system, army, name"
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE deleted_at IS NULL ORDER BY game_system, army, name"
                 )
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -130,14 +430,17 @@ impl ProjectRepository {
                         description: r.get("description"),
                         created_at: r.get("created_at"),
                         updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
                     })
                     .collect())
             }
-            Database::Postgres(pool) => {
+            Connection::Mysql(mut conn) => {
                 let rows = sqlx::query(
-                    "SELECT id, name, game_system, army, description, created_at, updated_at FROM projects ORDER BY game_system, army, name"
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE deleted_at IS NULL ORDER BY game_system, army, name"
                 )
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -150,21 +453,271 @@ impl ProjectRepository {
                         description: r.get("description"),
                         created_at: r.get("created_at"),
                         updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
                     })
                     .collect())
             }
         }
     }
 
+    pub async fn find_page(
+        executor: impl Into<Executor<'_>>,
+        filter: &ProjectFilter,
+        limit: i64,
+        offset: i64,
+        sort: Option<ProjectSortField>,
+        order: SortOrder,
+    ) -> Result<Vec<Project>, sqlx::Error> {
+        let order_by = order_by_clause(sort, order);
+        let tag_pattern = filter.tag_pattern();
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects{} ORDER BY {} LIMIT ?1 OFFSET ?2",
+                    filter.where_clause('?', 3),
+                    order_by
+                );
+                let mut query = sqlx::query(&sql).bind(limit).bind(offset);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Project {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        game_system: r.get("game_system"),
+                        army: r.get("army"),
+                        description: r.get("description"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects{} ORDER BY {} LIMIT $1 OFFSET $2",
+                    filter.where_clause('$', 3),
+                    order_by
+                );
+                let mut query = sqlx::query(&sql).bind(limit).bind(offset);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Project {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        game_system: r.get("game_system"),
+                        army: r.get("army"),
+                        description: r.get("description"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                // Parameters bind in the order they appear, so the paging values come last
+                let sql = format!(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects{} ORDER BY {} LIMIT ? OFFSET ?",
+                    to_mysql(&filter.where_clause('$', 1)),
+                    order_by
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let rows = query.bind(limit).bind(offset).fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| Project {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        game_system: r.get("game_system"),
+                        army: r.get("army"),
+                        description: r.get("description"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                        deleted_at: r.get("deleted_at"),
+                        tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                        archived: r.get("archived"),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    pub async fn count(
+        executor: impl Into<Executor<'_>>,
+        filter: &ProjectFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let tag_pattern = filter.tag_pattern();
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM projects{}",
+                    filter.where_clause('?', 1)
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+            Connection::Postgres(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM projects{}",
+                    filter.where_clause('$', 1)
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+            Connection::Mysql(mut conn) => {
+                let sql = format!(
+                    "SELECT COUNT(*) as count FROM projects{}",
+                    to_mysql(&filter.where_clause('$', 1))
+                );
+                let mut query = sqlx::query(&sql);
+                if let Some(pattern) = &tag_pattern {
+                    query = query.bind(pattern);
+                }
+                if let Some(created_after) = filter.created_after {
+                    query = query.bind(created_after);
+                }
+                if let Some(created_before) = filter.created_before {
+                    query = query.bind(created_before);
+                }
+                let row = query.fetch_one(&mut *conn).await?;
+                Ok(row.get("count"))
+            }
+        }
+    }
+
+    /// Every army name live projects use, optionally within one game system, with how many
+    /// projects use it. Sorted case-insensitively so differently-cased spellings sit together.
+    pub async fn army_usage(
+        executor: impl Into<Executor<'_>>,
+        game_system: Option<&GameSystem>,
+    ) -> Result<Vec<ArmyUsage>, sqlx::Error> {
+        let rows = match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => sqlx::query(
+                r#"
+                    SELECT army, COUNT(*) AS project_count FROM projects
+                    WHERE deleted_at IS NULL AND (?1 IS NULL OR game_system = ?1)
+                    GROUP BY army
+                    ORDER BY LOWER(army), army
+                    "#,
+            )
+            .bind(game_system)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|r| ArmyUsage {
+                army: r.get("army"),
+                project_count: r.get("project_count"),
+            })
+            .collect(),
+            Connection::Postgres(mut conn) => sqlx::query(
+                r#"
+                    SELECT army, COUNT(*) AS project_count FROM projects
+                    WHERE deleted_at IS NULL AND ($1 IS NULL OR game_system = $1)
+                    GROUP BY army
+                    ORDER BY LOWER(army), army
+                    "#,
+            )
+            .bind(game_system)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|r| ArmyUsage {
+                army: r.get("army"),
+                project_count: r.get("project_count"),
+            })
+            .collect(),
+            Connection::Mysql(mut conn) => sqlx::query(
+                r#"
+                    SELECT army, COUNT(*) AS project_count FROM projects
+                    WHERE deleted_at IS NULL AND (? IS NULL OR game_system = ?)
+                    GROUP BY army
+                    ORDER BY LOWER(army), army
+                    "#,
+            )
+            .bind(game_system)
+            .bind(game_system)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|r| ArmyUsage {
+                army: r.get("army"),
+                project_count: r.get("project_count"),
+            })
+            .collect(),
+        };
+
+        Ok(rows)
+    }
+
+    /// Apply a partial update. When the request carries the `updated_at` the client last saw,
+    /// the row only changes if it still matches, so `None` can also mean a concurrent edit won.
     pub async fn update(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         id: i64,
-        request: UpdateProjectRequest,
+        request: PatchProjectRequest,
     ) -> Result<Option<Project>, sqlx::Error> {
-        let now = Utc::now();
-
+        let mut executor = executor.into();
         // First, get the current project to merge with updates
-        let current = Self::find_by_id(database, id).await?;
+        let current = Self::find_by_id(executor.reborrow(), id).await?;
         let Some(current) = current else {
             return Ok(None);
         };
@@ -172,25 +725,32 @@ impl ProjectRepository {
         let name = request.name.unwrap_or(current.name);
         let game_system = request.game_system.unwrap_or(current.game_system);
         let army = request.army.unwrap_or(current.army);
-        let description = request.description.or(current.description);
+        let description = request.description.unwrap_or(current.description);
+        let tags = request
+            .tags
+            .map(|tags| normalize_tags(&tags))
+            .unwrap_or(current.tags);
+        let tags_json = serde_json::to_string(&tags).unwrap_or_default();
 
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.reborrow().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                // Timestamps are stored as text, so compare instants rather than strings
                 let row = sqlx::query(
                     r#"
                     UPDATE projects 
-                    SET name = ?1, game_system = ?2, army = ?3, description = ?4, updated_at = ?5
-                    WHERE id = ?6
-                    RETURNING id, name, game_system, army, description, created_at, updated_at
+                    SET name = ?1, game_system = ?2, army = ?3, description = ?4, tags = ?5, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?6 AND deleted_at IS NULL AND (?7 IS NULL OR julianday(updated_at) = julianday(?7))
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
                     "#,
                 )
                 .bind(&name)
                 .bind(&game_system)
                 .bind(&army)
                 .bind(&description)
-                .bind(now)
+                .bind(&tags_json)
                 .bind(id)
-                .fetch_optional(pool)
+                .bind(request.updated_at)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Project {
@@ -201,24 +761,28 @@ impl ProjectRepository {
                     description: r.get("description"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
                     UPDATE projects 
-                    SET name = $1, game_system = $2, army = $3, description = $4, updated_at = $5
-                    WHERE id = $6
-                    RETURNING id, name, game_system, army, description, created_at, updated_at
+                    SET name = $1, game_system = $2, army = $3, description = $4, tags = $5, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $6 AND deleted_at IS NULL AND ($7 IS NULL OR updated_at = $7)
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
                     "#,
                 )
                 .bind(&name)
                 .bind(&game_system)
                 .bind(&army)
                 .bind(&description)
-                .bind(now)
+                .bind(&tags_json)
                 .bind(id)
-                .fetch_optional(pool)
+                .bind(request.updated_at)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| Project {
@@ -229,29 +793,779 @@ impl ProjectRepository {
                     description: r.get("description"),
                     created_at: r.get("created_at"),
                     updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                // Rows affected counts matched rows, so zero means missing or edited meanwhile
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE projects
+                    SET name = ?, game_system = ?, army = ?, description = ?, tags = ?, updated_at = UTC_TIMESTAMP(6)
+                    WHERE id = ? AND deleted_at IS NULL AND (? IS NULL OR updated_at = ?)
+                    "#,
+                )
+                .bind(&name)
+                .bind(&game_system)
+                .bind(&army)
+                .bind(&description)
+                .bind(&tags_json)
+                .bind(id)
+                .bind(request.updated_at)
+                .bind(request.updated_at)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
                 }))
             }
         }
     }
 
-    pub async fn delete(database: &Database, id: i64) -> Result<bool, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
-                let result = sqlx::query("DELETE FROM projects WHERE id = ?1")
-                    .bind(id)
-                    .execute(pool)
+    /// Copy a project and all of its miniatures (but not their photos) in one transaction.
+    /// With `reset_progress` every copied miniature starts out unpainted.
+    pub async fn clone_project(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+        name: Option<String>,
+        reset_progress: bool,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        let status_override = reset_progress.then_some(ProgressStatus::Unpainted);
+
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(source) = sqlx::query(
+                    "SELECT name, game_system, army, description, tags FROM projects WHERE id = ?1 AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+                else {
+                    return Ok(None);
+                };
+
+                let name =
+                    name.unwrap_or_else(|| format!("{} (Copy)", source.get::<String, _>("name")));
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#
+                )
+                .bind(&name)
+                .bind(source.get::<GameSystem, _>("game_system"))
+                .bind(source.get::<String, _>("army"))
+                .bind(source.get::<Option<String>, _>("description"))
+                .bind(source.get::<String, _>("tags"))
+                .fetch_one(&mut *tx)
+                .await?;
+
+                let project = Project {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    game_system: row.get("game_system"),
+                    army: row.get("army"),
+                    description: row.get("description"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
+                };
+
+                // A NULL status keeps each miniature's existing progress
+                sqlx::query(
+                    r#"
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    SELECT ?1, name, miniature_type, COALESCE(?2, progress_status), notes, points_value, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    FROM miniatures WHERE project_id = ?3
+                    ORDER BY id
+                    "#
+                )
+                .bind(project.id)
+                .bind(&status_override)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(Some(project))
+            }
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(source) = sqlx::query(
+                    "SELECT name, game_system, army, description, tags FROM projects WHERE id = $1 AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+                else {
+                    return Ok(None);
+                };
+
+                let name =
+                    name.unwrap_or_else(|| format!("{} (Copy)", source.get::<String, _>("name")));
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#
+                )
+                .bind(&name)
+                .bind(source.get::<GameSystem, _>("game_system"))
+                .bind(source.get::<String, _>("army"))
+                .bind(source.get::<Option<String>, _>("description"))
+                .bind(source.get::<String, _>("tags"))
+                .fetch_one(&mut *tx)
+                .await?;
+
+                let project = Project {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    game_system: row.get("game_system"),
+                    army: row.get("army"),
+                    description: row.get("description"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
+                };
+
+                // A NULL status keeps each miniature's existing progress
+                sqlx::query(
+                    r#"
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    SELECT $1, name, miniature_type, COALESCE($2, progress_status), notes, points_value, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+                    FROM miniatures WHERE project_id = $3
+                    ORDER BY id
+                    "#
+                )
+                .bind(project.id)
+                .bind(&status_override)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(Some(project))
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(source) = sqlx::query(
+                    "SELECT name, game_system, army, description, tags FROM projects WHERE id = ? AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+                else {
+                    return Ok(None);
+                };
+
+                let name =
+                    name.unwrap_or_else(|| format!("{} (Copy)", source.get::<String, _>("name")));
+                let project_id = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
+                    "#
+                )
+                .bind(&name)
+                .bind(source.get::<GameSystem, _>("game_system"))
+                .bind(source.get::<String, _>("army"))
+                .bind(source.get::<Option<String>, _>("description"))
+                .bind(source.get::<String, _>("tags"))
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?",
+                )
+                .bind(project_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                let project = Project {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    game_system: row.get("game_system"),
+                    army: row.get("army"),
+                    description: row.get("description"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    deleted_at: row.get("deleted_at"),
+                    tags: serde_json::from_str(row.get("tags")).unwrap_or_default(),
+                    archived: row.get("archived"),
+                };
+
+                // A NULL status keeps each miniature's existing progress
+                sqlx::query(
+                    r#"
+                    INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                    SELECT ?, name, miniature_type, COALESCE(?, progress_status), notes, points_value, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6)
+                    FROM miniatures WHERE project_id = ?
+                    ORDER BY id
+                    "#
+                )
+                .bind(project.id)
+                .bind(&status_override)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                Ok(Some(project))
+            }
+        }
+    }
+
+    /// Recreate an exported project, its miniatures, and their recipe links in one transaction.
+    /// Recipes are reused when one with the same name already exists. Photos are not imported
+    /// because exports only carry their metadata.
+    pub async fn import(
+        executor: impl Into<Executor<'_>>,
+        export: &ProjectExport,
+    ) -> Result<ProjectImportSummary, sqlx::Error> {
+        let mut summary = ProjectImportSummary::default();
+        let tags_json =
+            serde_json::to_string(&normalize_tags(&export.project.tags)).unwrap_or_default();
+
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    RETURNING id
+                    "#
+                )
+                .bind(&export.project.name)
+                .bind(&export.project.game_system)
+                .bind(&export.project.army)
+                .bind(&export.project.description)
+                .bind(&tags_json)
+                .bind(export.project.created_at)
+                .bind(export.project.updated_at)
+                .fetch_one(&mut *tx)
+                .await?;
+                summary.project_id = row.get("id");
+
+                // Map exported recipe ids onto existing recipes by name, creating the rest
+                let mut recipe_ids = HashMap::new();
+                for recipe in &export.recipes {
+                    let existing = sqlx::query(
+                        "SELECT id FROM painting_recipes WHERE name = ?1 ORDER BY id LIMIT 1",
+                    )
+                    .bind(&recipe.name)
+                    .fetch_optional(&mut *tx)
                     .await?;
 
-                Ok(result.rows_affected() > 0)
+                    let new_id: i64 = match existing {
+                        Some(row) => {
+                            summary.recipes_reused += 1;
+                            row.get("id")
+                        }
+                        None => {
+                            let row = sqlx::query(
+                                r#"
+                                INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
+                                VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+                                RETURNING id
+                                "#
+                            )
+                            .bind(&recipe.name)
+                            .bind(&recipe.miniature_type)
+                            .bind(serde_json::to_string(&recipe.steps).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.paints_used).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.techniques).unwrap_or_default())
+                            .bind(&recipe.notes)
+                            .fetch_one(&mut *tx)
+                            .await?;
+                            summary.recipes_created += 1;
+                            row.get("id")
+                        }
+                    };
+                    recipe_ids.insert(recipe.id, new_id);
+                }
+
+                for entry in &export.miniatures {
+                    let miniature = &entry.miniature;
+                    let row = sqlx::query(
+                        r#"
+                        INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                        RETURNING id
+                        "#
+                    )
+                    .bind(summary.project_id)
+                    .bind(&miniature.name)
+                    .bind(&miniature.miniature_type)
+                    .bind(&miniature.progress_status)
+                    .bind(&miniature.notes)
+                    .bind(miniature.points_value)
+                    .bind(miniature.created_at)
+                    .bind(miniature.updated_at)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    let miniature_id: i64 = row.get("id");
+                    summary.miniatures_imported += 1;
+
+                    for recipe_id in entry.recipe_ids.iter().filter_map(|id| recipe_ids.get(id)) {
+                        sqlx::query(
+                            "INSERT OR IGNORE INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))"
+                        )
+                        .bind(miniature_id)
+                        .bind(recipe_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+
+                tx.commit().await?;
             }
-            Database::Postgres(pool) => {
-                let result = sqlx::query("DELETE FROM projects WHERE id = $1")
-                    .bind(id)
-                    .execute(pool)
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING id
+                    "#
+                )
+                .bind(&export.project.name)
+                .bind(&export.project.game_system)
+                .bind(&export.project.army)
+                .bind(&export.project.description)
+                .bind(&tags_json)
+                .bind(export.project.created_at)
+                .bind(export.project.updated_at)
+                .fetch_one(&mut *tx)
+                .await?;
+                summary.project_id = row.get("id");
+
+                // Map exported recipe ids onto existing recipes by name, creating the rest
+                let mut recipe_ids = HashMap::new();
+                for recipe in &export.recipes {
+                    let existing = sqlx::query(
+                        "SELECT id FROM painting_recipes WHERE name = $1 ORDER BY id LIMIT 1",
+                    )
+                    .bind(&recipe.name)
+                    .fetch_optional(&mut *tx)
                     .await?;
 
+                    let new_id: i64 = match existing {
+                        Some(row) => {
+                            summary.recipes_reused += 1;
+                            row.get("id")
+                        }
+                        None => {
+                            let row = sqlx::query(
+                                r#"
+                                INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
+                                VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                                RETURNING id
+                                "#
+                            )
+                            .bind(&recipe.name)
+                            .bind(&recipe.miniature_type)
+                            .bind(serde_json::to_string(&recipe.steps).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.paints_used).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.techniques).unwrap_or_default())
+                            .bind(&recipe.notes)
+                            .fetch_one(&mut *tx)
+                            .await?;
+                            summary.recipes_created += 1;
+                            row.get("id")
+                        }
+                    };
+                    recipe_ids.insert(recipe.id, new_id);
+                }
+
+                for entry in &export.miniatures {
+                    let miniature = &entry.miniature;
+                    let row = sqlx::query(
+                        r#"
+                        INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        RETURNING id
+                        "#
+                    )
+                    .bind(summary.project_id)
+                    .bind(&miniature.name)
+                    .bind(&miniature.miniature_type)
+                    .bind(&miniature.progress_status)
+                    .bind(&miniature.notes)
+                    .bind(miniature.points_value)
+                    .bind(miniature.created_at)
+                    .bind(miniature.updated_at)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    let miniature_id: i64 = row.get("id");
+                    summary.miniatures_imported += 1;
+
+                    for recipe_id in entry.recipe_ids.iter().filter_map(|id| recipe_ids.get(id)) {
+                        sqlx::query(
+                            "INSERT INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES ($1, $2, CURRENT_TIMESTAMP) ON CONFLICT DO NOTHING"
+                        )
+                        .bind(miniature_id)
+                        .bind(recipe_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+
+                tx.commit().await?;
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                // No RETURNING in MySQL; each insert's id comes from last_insert_id
+                let project_id = sqlx::query(
+                    r#"
+                    INSERT INTO projects (name, game_system, army, description, tags, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(&export.project.name)
+                .bind(&export.project.game_system)
+                .bind(&export.project.army)
+                .bind(&export.project.description)
+                .bind(&tags_json)
+                .bind(export.project.created_at)
+                .bind(export.project.updated_at)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+                summary.project_id = project_id as i64;
+
+                // Map exported recipe ids onto existing recipes by name, creating the rest
+                let mut recipe_ids = HashMap::new();
+                for recipe in &export.recipes {
+                    let existing = sqlx::query(
+                        "SELECT id FROM painting_recipes WHERE name = ? ORDER BY id LIMIT 1",
+                    )
+                    .bind(&recipe.name)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+                    let new_id: i64 = match existing {
+                        Some(row) => {
+                            summary.recipes_reused += 1;
+                            row.get("id")
+                        }
+                        None => {
+                            let recipe_id = sqlx::query(
+                                r#"
+                                INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
+                                VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
+                                "#
+                            )
+                            .bind(&recipe.name)
+                            .bind(&recipe.miniature_type)
+                            .bind(serde_json::to_string(&recipe.steps).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.paints_used).unwrap_or_default())
+                            .bind(serde_json::to_string(&recipe.techniques).unwrap_or_default())
+                            .bind(&recipe.notes)
+                            .execute(&mut *tx)
+                            .await?
+                            .last_insert_id();
+                            summary.recipes_created += 1;
+                            recipe_id as i64
+                        }
+                    };
+                    recipe_ids.insert(recipe.id, new_id);
+                }
+
+                for entry in &export.miniatures {
+                    let miniature = &entry.miniature;
+                    let miniature_id = sqlx::query(
+                        r#"
+                        INSERT INTO miniatures (project_id, name, miniature_type, progress_status, notes, points_value, created_at, updated_at)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                        "#
+                    )
+                    .bind(summary.project_id)
+                    .bind(&miniature.name)
+                    .bind(&miniature.miniature_type)
+                    .bind(&miniature.progress_status)
+                    .bind(&miniature.notes)
+                    .bind(miniature.points_value)
+                    .bind(miniature.created_at)
+                    .bind(miniature.updated_at)
+                    .execute(&mut *tx)
+                    .await?
+                    .last_insert_id();
+                    summary.miniatures_imported += 1;
+
+                    for recipe_id in entry.recipe_ids.iter().filter_map(|id| recipe_ids.get(id)) {
+                        sqlx::query(
+                            "INSERT IGNORE INTO miniature_recipes (miniature_id, recipe_id, linked_at) VALUES (?, ?, UTC_TIMESTAMP(6))"
+                        )
+                        .bind(miniature_id)
+                        .bind(recipe_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+
+                tx.commit().await?;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Soft-delete a project; it and its miniatures stay hidden until restored
+    pub async fn delete(executor: impl Into<Executor<'_>>, id: i64) -> Result<bool, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let result = sqlx::query(
+                    "UPDATE projects SET deleted_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE id = ?1 AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Postgres(mut conn) => {
+                let result = sqlx::query(
+                    "UPDATE projects SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1 AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Mysql(mut conn) => {
+                let result = sqlx::query(
+                    "UPDATE projects SET deleted_at = UTC_TIMESTAMP(6) WHERE id = ? AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+
                 Ok(result.rows_affected() > 0)
             }
         }
     }
+
+    /// Clear a project's soft-delete marker. Restoring a live project is a no-op.
+    pub async fn restore(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE projects SET deleted_at = NULL
+                    WHERE id = ?1
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#,
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE projects SET deleted_at = NULL
+                    WHERE id = $1
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#,
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let restored = sqlx::query("UPDATE projects SET deleted_at = NULL WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *conn)
+                    .await?
+                    .rows_affected();
+                if restored == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+        }
+    }
+
+    /// Archive or unarchive a live project. Archiving again is harmless; `None` means the
+    /// project doesn't exist or has been deleted.
+    pub async fn set_archived(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+        archived: bool,
+    ) -> Result<Option<Project>, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE projects SET archived = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?2 AND deleted_at IS NULL
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#,
+                )
+                .bind(archived)
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Postgres(mut conn) => {
+                let row = sqlx::query(
+                    r#"
+                    UPDATE projects SET archived = $1, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $2 AND deleted_at IS NULL
+                    RETURNING id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived
+                    "#,
+                )
+                .bind(archived)
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let updated = sqlx::query(
+                    "UPDATE projects SET archived = ?, updated_at = UTC_TIMESTAMP(6) WHERE id = ? AND deleted_at IS NULL",
+                )
+                .bind(archived)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, name, game_system, army, description, created_at, updated_at, deleted_at, tags, archived FROM projects WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| Project {
+                    id: r.get("id"),
+                    name: r.get("name"),
+                    game_system: r.get("game_system"),
+                    army: r.get("army"),
+                    description: r.get("description"),
+                    created_at: r.get("created_at"),
+                    updated_at: r.get("updated_at"),
+                    deleted_at: r.get("deleted_at"),
+                    tags: serde_json::from_str(r.get("tags")).unwrap_or_default(),
+                    archived: r.get("archived"),
+                }))
+            }
+        }
+    }
 }
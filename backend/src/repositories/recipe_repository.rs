@@ -1,26 +1,64 @@
-use crate::database::Database;
-use chrono::Utc;
-use shared_types::{CreateRecipeRequest, MiniatureType, PaintingRecipe, UpdateRecipeRequest};
-use sqlx::{Pool, Postgres, Row, Sqlite};
+use super::escape_like;
+use crate::database::{Connection, Executor};
+use shared_types::{
+    CreateRecipeRequest, MiniatureType, PaintUsage, PaintingRecipe, UpdateRecipeRequest,
+};
+use sqlx::Connection as _;
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+
+/// Rearrange `steps` so that position `i` holds the step currently at `order[i]`. The order
+/// must name every existing index exactly once.
+fn apply_step_order(steps: Vec<String>, order: &[usize]) -> Result<Vec<String>, sqlx::Error> {
+    if order.len() != steps.len() {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "Step order has {} entries but the recipe has {} steps",
+            order.len(),
+            steps.len()
+        )));
+    }
+
+    let mut seen = vec![false; steps.len()];
+    for &index in order {
+        if index >= steps.len() {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "Step index {} is out of range for a recipe with {} steps",
+                index,
+                steps.len()
+            )));
+        }
+        if std::mem::replace(&mut seen[index], true) {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "Step index {} appears more than once",
+                index
+            )));
+        }
+    }
+
+    let mut steps: Vec<Option<String>> = steps.into_iter().map(Some).collect();
+    Ok(order
+        .iter()
+        .filter_map(|&index| steps[index].take())
+        .collect())
+}
 
 pub struct RecipeRepository;
 
 impl RecipeRepository {
     pub async fn create(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         request: CreateRecipeRequest,
     ) -> Result<PaintingRecipe, sqlx::Error> {
-        let now = Utc::now();
         let steps_json = serde_json::to_string(&request.steps).unwrap_or_default();
         let paints_json = serde_json::to_string(&request.paints_used).unwrap_or_default();
         let techniques_json = serde_json::to_string(&request.techniques).unwrap_or_default();
 
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     r#"
                     INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
-                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
                     RETURNING id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at
                     "#
                 )
@@ -30,9 +68,7 @@ impl RecipeRepository {
                 .bind(&paints_json)
                 .bind(&techniques_json)
                 .bind(&request.notes)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
@@ -53,11 +89,11 @@ impl RecipeRepository {
                     updated_at: row.get("updated_at"),
                 })
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
                     INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
                     RETURNING id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at
                     "#
                 )
@@ -67,9 +103,50 @@ impl RecipeRepository {
                 .bind(&paints_json)
                 .bind(&techniques_json)
                 .bind(&request.notes)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
+                let paints_used: Vec<String> =
+                    serde_json::from_str(row.get("paints_used")).unwrap_or_default();
+                let techniques: Vec<String> =
+                    serde_json::from_str(row.get("techniques")).unwrap_or_default();
+
+                Ok(PaintingRecipe {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    miniature_type: row.get("miniature_type"),
+                    steps,
+                    paints_used,
+                    techniques,
+                    notes: row.get("notes"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            }
+            Connection::Mysql(mut conn) => {
+                // No RETURNING in MySQL, so read the row back by its new id
+                let id = sqlx::query(
+                    r#"
+                    INSERT INTO painting_recipes (name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP(6), UTC_TIMESTAMP(6))
+                    "#
+                )
+                .bind(&request.name)
+                .bind(&request.miniature_type)
+                .bind(&steps_json)
+                .bind(&paints_json)
+                .bind(&techniques_json)
+                .bind(&request.notes)
+                .execute(&mut *conn)
+                .await?
+                .last_insert_id();
+
+                let row = sqlx::query(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
@@ -94,16 +171,16 @@ impl RecipeRepository {
     }
 
     pub async fn find_by_id(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         id: i64,
     ) -> Result<Option<PaintingRecipe>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id = ?1"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| {
@@ -127,12 +204,41 @@ impl RecipeRepository {
                     }
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id = $1"
                 )
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| {
+                    let steps: Vec<String> =
+                        serde_json::from_str(r.get("steps")).unwrap_or_default();
+                    let paints_used: Vec<String> =
+                        serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                    let techniques: Vec<String> =
+                        serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                    PaintingRecipe {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        steps,
+                        paints_used,
+                        techniques,
+                        notes: r.get("notes"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    }
+                }))
+            }
+            Connection::Mysql(mut conn) => {
+                let row = sqlx::query(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| {
@@ -159,13 +265,46 @@ impl RecipeRepository {
         }
     }
 
-    pub async fn find_all(database: &Database) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+    pub async fn find_all(
+        executor: impl Into<Executor<'_>>,
+    ) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes ORDER BY name"
+                )
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes ORDER BY name"
                 )
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -192,11 +331,11 @@ impl RecipeRepository {
                     })
                     .collect())
             }
-            Database::Postgres(pool) => {
+            Connection::Mysql(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes ORDER BY name"
                 )
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -226,17 +365,122 @@ impl RecipeRepository {
         }
     }
 
+    /// Count how many recipes use each paint, most used first. Names are grouped
+    /// case-insensitively and reported as first spelled. With `project_id`, only recipes
+    /// linked to that project's miniatures are counted.
+    pub async fn aggregate_paints(
+        executor: impl Into<Executor<'_>>,
+        project_id: Option<i64>,
+    ) -> Result<Vec<PaintUsage>, sqlx::Error> {
+        // Paints are JSON-encoded per recipe, so pull the lists and tally here
+        let paint_lists: Vec<String> = match (executor.into().acquire_read().await?, project_id) {
+            (Connection::Sqlite(mut conn), None) => {
+                sqlx::query_scalar("SELECT paints_used FROM painting_recipes ORDER BY id")
+                    .fetch_all(&mut *conn)
+                    .await?
+            }
+            (Connection::Sqlite(mut conn), Some(project_id)) => {
+                sqlx::query_scalar(
+                    r#"
+                    SELECT paints_used FROM painting_recipes
+                    WHERE id IN (
+                        SELECT mr.recipe_id FROM miniature_recipes mr
+                        JOIN miniatures m ON m.id = mr.miniature_id
+                        WHERE m.project_id = ?1
+                    )
+                    ORDER BY id
+                    "#,
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            (Connection::Postgres(mut conn), None) => {
+                sqlx::query_scalar("SELECT paints_used FROM painting_recipes ORDER BY id")
+                    .fetch_all(&mut *conn)
+                    .await?
+            }
+            (Connection::Postgres(mut conn), Some(project_id)) => {
+                sqlx::query_scalar(
+                    r#"
+                    SELECT paints_used FROM painting_recipes
+                    WHERE id IN (
+                        SELECT mr.recipe_id FROM miniature_recipes mr
+                        JOIN miniatures m ON m.id = mr.miniature_id
+                        WHERE m.project_id = $1
+                    )
+                    ORDER BY id
+                    "#,
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?
+            }
+            (Connection::Mysql(mut conn), None) => {
+                sqlx::query_scalar("SELECT paints_used FROM painting_recipes ORDER BY id")
+                    .fetch_all(&mut *conn)
+                    .await?
+            }
+            (Connection::Mysql(mut conn), Some(project_id)) => {
+                sqlx::query_scalar(
+                    r#"
+                    SELECT paints_used FROM painting_recipes
+                    WHERE id IN (
+                        SELECT mr.recipe_id FROM miniature_recipes mr
+                        JOIN miniatures m ON m.id = mr.miniature_id
+                        WHERE m.project_id = ?
+                    )
+                    ORDER BY id
+                    "#,
+                )
+                .bind(project_id)
+                .fetch_all(&mut *conn)
+                .await?
+            }
+        };
+
+        let mut usage: HashMap<String, PaintUsage> = HashMap::new();
+        for paint_list in paint_lists {
+            let paints: Vec<String> = serde_json::from_str(&paint_list).unwrap_or_default();
+
+            // A recipe naming the same paint twice still counts once
+            let mut seen = HashSet::new();
+            for paint in paints {
+                let paint = paint.trim();
+                let key = paint.to_lowercase();
+                if key.is_empty() || !seen.insert(key.clone()) {
+                    continue;
+                }
+                usage
+                    .entry(key)
+                    .or_insert_with(|| PaintUsage {
+                        paint: paint.to_string(),
+                        recipe_count: 0,
+                    })
+                    .recipe_count += 1;
+            }
+        }
+
+        let mut usage: Vec<PaintUsage> = usage.into_values().collect();
+        usage.sort_by(|a, b| {
+            b.recipe_count
+                .cmp(&a.recipe_count)
+                .then_with(|| a.paint.to_lowercase().cmp(&b.paint.to_lowercase()))
+        });
+        Ok(usage)
+    }
+
     pub async fn find_by_type(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         miniature_type: MiniatureType,
     ) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE miniature_type = ?1 ORDER BY name"
                 )
                 .bind(&miniature_type)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -263,12 +507,44 @@ impl RecipeRepository {
                     })
                     .collect())
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let rows = sqlx::query(
                     "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE miniature_type = $1 ORDER BY name"
                 )
                 .bind(&miniature_type)
-                .fetch_all(pool)
+                .fetch_all(&mut *conn)
+                .await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let rows = sqlx::query(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE miniature_type = ? ORDER BY name"
+                )
+                .bind(&miniature_type)
+                .fetch_all(&mut *conn)
                 .await?;
 
                 Ok(rows
@@ -298,15 +574,288 @@ impl RecipeRepository {
         }
     }
 
+    /// Recipes linked to any miniature in a live project, each once, optionally restricted to one
+    /// miniature type
+    pub async fn find_by_project(
+        executor: impl Into<Executor<'_>>,
+        project_id: i64,
+        miniature_type: Option<MiniatureType>,
+    ) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = ?2"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id IN (SELECT mr.recipe_id FROM miniature_recipes mr JOIN miniatures m ON m.id = mr.miniature_id JOIN projects p ON p.id = m.project_id WHERE m.project_id = ?1 AND p.deleted_at IS NULL){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql).bind(project_id);
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = $2"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id IN (SELECT mr.recipe_id FROM miniature_recipes mr JOIN miniatures m ON m.id = mr.miniature_id JOIN projects p ON p.id = m.project_id WHERE m.project_id = $1 AND p.deleted_at IS NULL){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql).bind(project_id);
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = ?"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id IN (SELECT mr.recipe_id FROM miniature_recipes mr JOIN miniatures m ON m.id = mr.miniature_id JOIN projects p ON p.id = m.project_id WHERE m.project_id = ? AND p.deleted_at IS NULL){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql).bind(project_id);
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Case-insensitive substring search over a recipe's name, notes, steps, paints, and techniques,
+    /// optionally restricted to one miniature type
+    pub async fn search(
+        executor: impl Into<Executor<'_>>,
+        query: &str,
+        miniature_type: Option<MiniatureType>,
+    ) -> Result<Vec<PaintingRecipe>, sqlx::Error> {
+        let pattern = format!("%{}%", escape_like(&query.to_lowercase()));
+
+        match executor.into().acquire_read().await? {
+            Connection::Sqlite(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = ?2"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE (LOWER(name) LIKE ?1 ESCAPE '\\' OR LOWER(COALESCE(notes, '')) LIKE ?1 ESCAPE '\\' OR LOWER(steps) LIKE ?1 ESCAPE '\\' OR LOWER(COALESCE(paints_used, '')) LIKE ?1 ESCAPE '\\' OR LOWER(COALESCE(techniques, '')) LIKE ?1 ESCAPE '\\'){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql).bind(&pattern);
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Postgres(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = $2"
+                } else {
+                    ""
+                };
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE (LOWER(name) LIKE $1 ESCAPE '\\' OR LOWER(COALESCE(notes, '')) LIKE $1 ESCAPE '\\' OR LOWER(steps) LIKE $1 ESCAPE '\\' OR LOWER(COALESCE(paints_used, '')) LIKE $1 ESCAPE '\\' OR LOWER(COALESCE(techniques, '')) LIKE $1 ESCAPE '\\'){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql).bind(&pattern);
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+            Connection::Mysql(mut conn) => {
+                let type_filter = if miniature_type.is_some() {
+                    " AND miniature_type = ?"
+                } else {
+                    ""
+                };
+                // Backslash is MySQL's default LIKE escape; each `?` takes the pattern again
+                let sql = format!(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE (LOWER(name) LIKE ? OR LOWER(COALESCE(notes, '')) LIKE ? OR LOWER(steps) LIKE ? OR LOWER(COALESCE(paints_used, '')) LIKE ? OR LOWER(COALESCE(techniques, '')) LIKE ?){} ORDER BY name",
+                    type_filter
+                );
+
+                let mut query = sqlx::query(&sql);
+                for _ in 0..5 {
+                    query = query.bind(&pattern);
+                }
+                if let Some(miniature_type) = &miniature_type {
+                    query = query.bind(miniature_type);
+                }
+                let rows = query.fetch_all(&mut *conn).await?;
+
+                Ok(rows
+                    .into_iter()
+                    .map(|r| {
+                        let steps: Vec<String> =
+                            serde_json::from_str(r.get("steps")).unwrap_or_default();
+                        let paints_used: Vec<String> =
+                            serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                        let techniques: Vec<String> =
+                            serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                        PaintingRecipe {
+                            id: r.get("id"),
+                            name: r.get("name"),
+                            miniature_type: r.get("miniature_type"),
+                            steps,
+                            paints_used,
+                            techniques,
+                            notes: r.get("notes"),
+                            created_at: r.get("created_at"),
+                            updated_at: r.get("updated_at"),
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+
     pub async fn update(
-        database: &Database,
+        executor: impl Into<Executor<'_>>,
         id: i64,
         request: UpdateRecipeRequest,
     ) -> Result<Option<PaintingRecipe>, sqlx::Error> {
-        let now = Utc::now();
-
+        let mut executor = executor.into();
         // First, get the current recipe to merge with updates
-        let current = Self::find_by_id(database, id).await?;
+        let current = Self::find_by_id(executor.reborrow(), id).await?;
         let Some(current) = current else {
             return Ok(None);
         };
@@ -321,13 +870,13 @@ impl RecipeRepository {
         let paints_json = serde_json::to_string(&paints_used).unwrap_or_default();
         let techniques_json = serde_json::to_string(&techniques).unwrap_or_default();
 
-        match database {
-            Database::Sqlite(pool) => {
+        match executor.reborrow().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let row = sqlx::query(
                     r#"
                     UPDATE painting_recipes 
-                    SET name = ?1, steps = ?2, paints_used = ?3, techniques = ?4, notes = ?5, updated_at = ?6
-                    WHERE id = ?7
+                    SET name = ?1, steps = ?2, paints_used = ?3, techniques = ?4, notes = ?5, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')
+                    WHERE id = ?6
                     RETURNING id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at
                     "#
                 )
@@ -336,9 +885,8 @@ impl RecipeRepository {
                 .bind(&paints_json)
                 .bind(&techniques_json)
                 .bind(&notes)
-                .bind(now)
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| {
@@ -362,12 +910,12 @@ impl RecipeRepository {
                     }
                 }))
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let row = sqlx::query(
                     r#"
                     UPDATE painting_recipes 
-                    SET name = $1, steps = $2, paints_used = $3, techniques = $4, notes = $5, updated_at = $6
-                    WHERE id = $7
+                    SET name = $1, steps = $2, paints_used = $3, techniques = $4, notes = $5, updated_at = CURRENT_TIMESTAMP
+                    WHERE id = $6
                     RETURNING id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at
                     "#
                 )
@@ -376,9 +924,8 @@ impl RecipeRepository {
                 .bind(&paints_json)
                 .bind(&techniques_json)
                 .bind(&notes)
-                .bind(now)
                 .bind(id)
-                .fetch_optional(pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
                 Ok(row.map(|r| {
@@ -402,23 +949,168 @@ impl RecipeRepository {
                     }
                 }))
             }
+            Connection::Mysql(mut conn) => {
+                let updated = sqlx::query(
+                    r#"
+                    UPDATE painting_recipes
+                    SET name = ?, steps = ?, paints_used = ?, techniques = ?, notes = ?, updated_at = UTC_TIMESTAMP(6)
+                    WHERE id = ?
+                    "#
+                )
+                .bind(&name)
+                .bind(&steps_json)
+                .bind(&paints_json)
+                .bind(&techniques_json)
+                .bind(&notes)
+                .bind(id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected();
+                if updated == 0 {
+                    return Ok(None);
+                }
+
+                let row = sqlx::query(
+                    "SELECT id, name, miniature_type, steps, paints_used, techniques, notes, created_at, updated_at FROM painting_recipes WHERE id = ?"
+                )
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(row.map(|r| {
+                    let steps: Vec<String> =
+                        serde_json::from_str(r.get("steps")).unwrap_or_default();
+                    let paints_used: Vec<String> =
+                        serde_json::from_str(r.get("paints_used")).unwrap_or_default();
+                    let techniques: Vec<String> =
+                        serde_json::from_str(r.get("techniques")).unwrap_or_default();
+
+                    PaintingRecipe {
+                        id: r.get("id"),
+                        name: r.get("name"),
+                        miniature_type: r.get("miniature_type"),
+                        steps,
+                        paints_used,
+                        techniques,
+                        notes: r.get("notes"),
+                        created_at: r.get("created_at"),
+                        updated_at: r.get("updated_at"),
+                    }
+                }))
+            }
+        }
+    }
+
+    /// Reorder a recipe's steps; `order` lists current step indices in their new order
+    pub async fn reorder_steps(
+        executor: impl Into<Executor<'_>>,
+        id: i64,
+        order: &[usize],
+    ) -> Result<Option<PaintingRecipe>, sqlx::Error> {
+        let mut executor = executor.into();
+        // Read and write in one transaction so a concurrent edit can't be reordered stale
+        match executor.reborrow().acquire().await? {
+            Connection::Sqlite(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(row) = sqlx::query("SELECT steps FROM painting_recipes WHERE id = ?1")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                else {
+                    return Ok(None);
+                };
+                let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
+                let steps_json =
+                    serde_json::to_string(&apply_step_order(steps, order)?).unwrap_or_default();
+
+                sqlx::query(
+                    "UPDATE painting_recipes SET steps = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now') WHERE id = ?2",
+                )
+                .bind(&steps_json)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+            }
+            Connection::Postgres(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(row) =
+                    sqlx::query("SELECT steps FROM painting_recipes WHERE id = $1 FOR UPDATE")
+                        .bind(id)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                else {
+                    return Ok(None);
+                };
+                let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
+                let steps_json =
+                    serde_json::to_string(&apply_step_order(steps, order)?).unwrap_or_default();
+
+                sqlx::query(
+                    "UPDATE painting_recipes SET steps = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2",
+                )
+                .bind(&steps_json)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+            }
+            Connection::Mysql(mut conn) => {
+                let mut tx = conn.begin().await?;
+
+                let Some(row) =
+                    sqlx::query("SELECT steps FROM painting_recipes WHERE id = ? FOR UPDATE")
+                        .bind(id)
+                        .fetch_optional(&mut *tx)
+                        .await?
+                else {
+                    return Ok(None);
+                };
+                let steps: Vec<String> = serde_json::from_str(row.get("steps")).unwrap_or_default();
+                let steps_json =
+                    serde_json::to_string(&apply_step_order(steps, order)?).unwrap_or_default();
+
+                sqlx::query(
+                    "UPDATE painting_recipes SET steps = ?, updated_at = UTC_TIMESTAMP(6) WHERE id = ?",
+                )
+                .bind(&steps_json)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+            }
         }
+
+        Self::find_by_id(executor.reborrow(), id).await
     }
 
-    pub async fn delete(database: &Database, id: i64) -> Result<bool, sqlx::Error> {
-        match database {
-            Database::Sqlite(pool) => {
+    pub async fn delete(executor: impl Into<Executor<'_>>, id: i64) -> Result<bool, sqlx::Error> {
+        match executor.into().acquire().await? {
+            Connection::Sqlite(mut conn) => {
                 let result = sqlx::query("DELETE FROM painting_recipes WHERE id = ?1")
                     .bind(id)
-                    .execute(pool)
+                    .execute(&mut *conn)
                     .await?;
 
                 Ok(result.rows_affected() > 0)
             }
-            Database::Postgres(pool) => {
+            Connection::Postgres(mut conn) => {
                 let result = sqlx::query("DELETE FROM painting_recipes WHERE id = $1")
                     .bind(id)
-                    .execute(pool)
+                    .execute(&mut *conn)
+                    .await?;
+
+                Ok(result.rows_affected() > 0)
+            }
+            Connection::Mysql(mut conn) => {
+                let result = sqlx::query("DELETE FROM painting_recipes WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *conn)
                     .await?;
 
                 Ok(result.rows_affected() > 0)
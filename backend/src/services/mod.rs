@@ -1 +1,4 @@
+pub mod reencode_service;
 pub mod storage_service;
+pub mod thumbnail_service;
+pub mod webhook_service;
@@ -0,0 +1,93 @@
+use image::{
+    error::{EncodingError, ImageFormatHint},
+    ImageError, ImageFormat,
+};
+
+/// MIME type of re-encoded uploads
+pub const WEBP_MIME_TYPE: &str = "image/webp";
+
+/// Decode an uploaded photo and re-encode it as a lossy WebP of `quality` (0 to 100) at the
+/// same size. Returns `None` when the WebP would be no smaller than the original, so it is
+/// kept as uploaded.
+pub fn reencode_as_webp(file_data: &[u8], quality: u8) -> Result<Option<Vec<u8>>, ImageError> {
+    let image = image::load_from_memory(file_data)?;
+    let (width, height) = (image.width(), image.height());
+
+    // libwebp only takes 8-bit RGB(A); keep transparency when the image has it
+    let encoded = if image.color().has_alpha() {
+        webp::Encoder::from_rgba(&image.to_rgba8(), width, height)
+            .encode_simple(false, f32::from(quality))
+    } else {
+        webp::Encoder::from_rgb(&image.to_rgb8(), width, height)
+            .encode_simple(false, f32::from(quality))
+    };
+    let webp = encoded.map_err(|e| {
+        ImageError::Encoding(EncodingError::new(
+            ImageFormatHint::Exact(ImageFormat::WebP),
+            format!("{:?}", e),
+        ))
+    })?;
+
+    Ok((webp.len() < file_data.len()).then(|| webp.to_vec()))
+}
+
+/// Whether the file is already a WebP, which re-encoding could only grow
+pub fn is_webp(file_data: &[u8]) -> bool {
+    image::guess_format(file_data).is_ok_and(|format| format == ImageFormat::WebP)
+}
+
+/// `filename` with its extension replaced by `.webp`, or added if it has none
+pub fn webp_filename(filename: &str) -> String {
+    let stem = filename
+        .rsplit_once('.')
+        .map_or(filename, |(stem, _extension)| stem);
+
+    format!("{}.webp", stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::png::PngEncoder, ImageEncoder, RgbImage};
+
+    /// An uncompressed-looking PNG of a flat colour, which WebP shrinks easily
+    fn flat_png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, image::Rgb([120, 30, 30]));
+        let mut png = Vec::new();
+        PngEncoder::new_with_quality(
+            &mut png,
+            image::codecs::png::CompressionType::Fast,
+            image::codecs::png::FilterType::NoFilter,
+        )
+        .write_image(&image, width, height, image::ExtendedColorType::Rgb8)
+        .unwrap();
+        png
+    }
+
+    #[test]
+    fn reencodes_to_a_smaller_webp_of_the_same_size() {
+        let png = flat_png(200, 100);
+
+        let webp = reencode_as_webp(&png, 80)
+            .unwrap()
+            .expect("WebP should be smaller");
+        assert!(webp.len() < png.len());
+        assert!(is_webp(&webp));
+
+        let decoded = image::load_from_memory(&webp).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (200, 100));
+    }
+
+    #[test]
+    fn undecodable_files_are_errors() {
+        assert!(reencode_as_webp(b"\x89PNG\r\n\x1a\nnot really", 80).is_err());
+        assert!(!is_webp(b"\xFF\xD8\xFF\xE0"));
+    }
+
+    #[test]
+    fn filenames_take_the_webp_extension() {
+        assert_eq!(webp_filename("front.jpg"), "front.webp");
+        assert_eq!(webp_filename("army.final.PNG"), "army.final.webp");
+        assert_eq!(webp_filename("scan"), "scan.webp");
+    }
+}
@@ -1,8 +1,92 @@
-use crate::config::{Config, StorageType};
+use crate::config::{Config, PathStrategy, StorageType};
+use crate::services::thumbnail_service;
 use crate::storage::{Storage, StorageConfig, StorageError};
+use chrono::{Datelike, Utc};
+use shared_types::Photo;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Directory every photo and its thumbnail are stored under
+const PHOTO_PREFIX: &str = "miniatures/";
+/// Longest stem kept from an uploaded filename
+const MAX_STEM_LENGTH: usize = 64;
+
+/// File extension for a stored photo of the given MIME type
+fn extension_for(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/jpeg" => "jpg",
+        other => image::ImageFormat::from_mime_type(other)
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("bin"),
+    }
+}
+
+/// Name a photo is stored under, before its uuid prefix: the client's filename with any
+/// directories and extension removed and anything but letters, digits, `-`, `_`, and `.`
+/// replaced, then the extension for its MIME type
+fn stored_file_name(filename: &str, mime_type: &str) -> String {
+    // Clients on Windows may send backslash-separated paths
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let stem = std::path::Path::new(name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+
+    let stem: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_STEM_LENGTH)
+        .collect();
+    let stem = stem.trim_matches('.');
+    let stem = if stem.is_empty() { "photo" } else { stem };
+
+    format!("{}.{}", stem, extension_for(mime_type))
+}
+
+/// One `StorageService` for the whole server, so requests and readiness probes share a client
+/// instead of each building its own. It is created on first use unless built up front.
+#[derive(Clone)]
+pub struct SharedStorage {
+    config: Arc<Config>,
+    service: Arc<OnceCell<StorageService>>,
+}
+
+impl SharedStorage {
+    /// Storage for `config`, set up when it's first needed
+    pub fn new(config: Arc<Config>) -> Self {
+        SharedStorage {
+            config,
+            service: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Storage that was already set up, e.g. at startup
+    pub fn with_service(config: Arc<Config>, service: StorageService) -> Self {
+        SharedStorage {
+            config,
+            service: Arc::new(OnceCell::new_with(Some(service))),
+        }
+    }
+
+    pub async fn get(&self) -> Result<&StorageService, StorageError> {
+        self.service
+            .get_or_try_init(|| StorageService::new(&self.config))
+            .await
+    }
+}
 
 pub struct StorageService {
     storage: Storage,
+    path_strategy: PathStrategy,
 }
 
 impl StorageService {
@@ -14,7 +98,10 @@ impl StorageService {
                     .as_ref()
                     .unwrap_or(&"./uploads".to_string())
                     .clone();
-                let base_url = format!("http://localhost:{}/uploads", config.port);
+                let base_url = config
+                    .local_storage_base_url
+                    .clone()
+                    .unwrap_or_else(|| format!("http://localhost:{}/uploads", config.port));
 
                 StorageConfig::Local {
                     base_path,
@@ -40,39 +127,97 @@ impl StorageService {
                 StorageConfig::S3 {
                     bucket,
                     region,
-                    base_url: None, // Could be configured for CloudFront later
+                    base_url: config.s3_base_url.clone(),
+                }
+            }
+            StorageType::Gcs => {
+                let bucket = config
+                    .gcs_bucket
+                    .as_ref()
+                    .ok_or_else(|| {
+                        StorageError::InvalidPath("GCS_BUCKET not configured".to_string())
+                    })?
+                    .clone();
+                let hmac_access_id = config
+                    .gcs_hmac_access_id
+                    .as_ref()
+                    .ok_or_else(|| {
+                        StorageError::InvalidPath("GCS_HMAC_ACCESS_ID not configured".to_string())
+                    })?
+                    .clone();
+                let hmac_secret = config
+                    .gcs_hmac_secret
+                    .as_ref()
+                    .ok_or_else(|| {
+                        StorageError::InvalidPath("GCS_HMAC_SECRET not configured".to_string())
+                    })?
+                    .clone();
+
+                StorageConfig::Gcs {
+                    bucket,
+                    base_url: config.gcs_base_url.clone(),
+                    hmac_access_id,
+                    hmac_secret,
                 }
             }
+            StorageType::Memory => StorageConfig::Memory,
         };
 
         let storage = Storage::new(storage_config).await?;
 
-        Ok(StorageService { storage })
+        Ok(StorageService {
+            storage,
+            path_strategy: config.path_strategy,
+        })
     }
 
+    /// Store a photo under a fresh path built from `filename`, with the extension for its
+    /// validated `mime_type` rather than whatever the client sent
     pub async fn store_photo(
         &self,
         file_data: &[u8],
         filename: &str,
+        mime_type: &str,
         miniature_id: i64,
     ) -> Result<String, StorageError> {
-        // Generate a unique path for the photo
-        let file_extension = std::path::Path::new(filename)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg");
-
+        let directory = match self.path_strategy {
+            PathStrategy::Flat => PHOTO_PREFIX.to_string(),
+            PathStrategy::ByMiniature => format!("{}{}/", PHOTO_PREFIX, miniature_id),
+            PathStrategy::ByDate => {
+                let now = Utc::now();
+                format!(
+                    "{}{}/{:02}/{}/",
+                    PHOTO_PREFIX,
+                    now.year(),
+                    now.month(),
+                    miniature_id
+                )
+            }
+        };
         let unique_filename = format!(
-            "miniatures/{}/{}_{}.{}",
-            miniature_id,
+            "{}{}_{}",
+            directory,
             uuid::Uuid::new_v4(),
-            filename.replace(&format!(".{}", file_extension), ""),
-            file_extension
+            stored_file_name(filename, mime_type)
         );
 
         self.storage.store(file_data, &unique_filename).await
     }
 
+    /// Store a thumbnail alongside the photo stored at `photo_path`
+    pub async fn store_thumbnail(
+        &self,
+        thumbnail_data: &[u8],
+        photo_path: &str,
+    ) -> Result<String, StorageError> {
+        self.storage
+            .store(
+                thumbnail_data,
+                &thumbnail_service::thumbnail_path(photo_path),
+            )
+            .await
+    }
+
     pub async fn retrieve_photo(&self, file_path: &str) -> Result<Vec<u8>, StorageError> {
         self.storage.retrieve(file_path).await
     }
@@ -81,11 +226,123 @@ impl StorageService {
         self.storage.delete(file_path).await
     }
 
+    /// Delete the stored files of photos whose records are already gone. A file that can't be
+    /// deleted, say because it's already missing, is logged and skipped.
+    pub async fn delete_photo_files(&self, photos: &[Photo]) {
+        for photo in photos {
+            if let Err(e) = self.delete_photo(&photo.file_path).await {
+                tracing::warn!("Failed to delete photo file {}: {}", photo.file_path, e);
+            }
+            if let Some(thumbnail_path) = &photo.thumbnail_path {
+                if let Err(e) = self.delete_photo(thumbnail_path).await {
+                    tracing::warn!("Failed to delete thumbnail file {}: {}", thumbnail_path, e);
+                }
+            }
+        }
+    }
+
+    pub async fn health_check(&self) -> Result<(), StorageError> {
+        self.storage.health_check().await
+    }
+
+    /// Storage path and size in bytes of every stored photo and thumbnail
+    pub async fn list_photo_files(&self) -> Result<Vec<(String, u64)>, StorageError> {
+        self.storage.list(PHOTO_PREFIX).await
+    }
+
+    /// The miniature a stored photo or thumbnail belongs to and the directory holding it, for
+    /// files in a by-miniature or by-date layout; flat files aren't grouped by miniature
+    pub fn miniature_directory(file_path: &str) -> Option<(i64, String)> {
+        let mut directories: Vec<&str> = file_path.strip_prefix(PHOTO_PREFIX)?.split('/').collect();
+        directories.pop();
+        if directories.last() == Some(&"thumbnails") {
+            directories.pop();
+        }
+
+        let miniature_id = match directories.as_slice() {
+            [id] => id.parse::<i64>().ok()?,
+            [year, month, id] if year.parse::<u16>().is_ok() && month.parse::<u8>().is_ok() => {
+                id.parse::<i64>().ok()?
+            }
+            _ => return None,
+        };
+
+        Some((
+            miniature_id,
+            format!("{}{}/", PHOTO_PREFIX, directories.join("/")),
+        ))
+    }
+
     pub async fn get_photo_url(&self, file_path: &str) -> Result<String, StorageError> {
         self.storage.get_url(file_path).await
     }
 
+    /// Lifetime of URLs returned by `get_photo_url`, or `None` if they don't expire
+    pub fn photo_url_expires_in(&self) -> Option<Duration> {
+        self.storage.url_expires_in()
+    }
+
     pub async fn photo_exists(&self, file_path: &str) -> Result<bool, StorageError> {
         self.storage.exists(file_path).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_stem_and_takes_the_extension_from_the_mime_type() {
+        assert_eq!(stored_file_name("front.png", "image/png"), "front.png");
+        assert_eq!(stored_file_name("front.png", "image/jpeg"), "front.jpg");
+        assert_eq!(stored_file_name("front.JPEG", "image/jpeg"), "front.jpg");
+    }
+
+    #[test]
+    fn names_without_an_extension_keep_their_whole_stem() {
+        assert_eq!(stored_file_name("photo", "image/jpeg"), "photo.jpg");
+        assert_eq!(stored_file_name("png", "image/png"), "png.png");
+    }
+
+    #[test]
+    fn only_the_last_extension_is_dropped() {
+        assert_eq!(
+            stored_file_name("my.photo.final.png", "image/png"),
+            "my.photo.final.png"
+        );
+        assert_eq!(
+            stored_file_name("archive.png.webp", "image/webp"),
+            "archive.png.webp"
+        );
+    }
+
+    #[test]
+    fn directories_are_stripped() {
+        assert_eq!(stored_file_name("../evil.png", "image/png"), "evil.png");
+        assert_eq!(
+            stored_file_name("../../etc/passwd", "image/jpeg"),
+            "passwd.jpg"
+        );
+        assert_eq!(
+            stored_file_name("C:\\Users\\me\\front.png", "image/png"),
+            "front.png"
+        );
+    }
+
+    #[test]
+    fn unsafe_characters_are_replaced_and_empty_stems_named() {
+        assert_eq!(
+            stored_file_name("my photo?#1.png", "image/png"),
+            "my_photo__1.png"
+        );
+        assert_eq!(stored_file_name("..", "image/png"), "photo.png");
+        assert_eq!(stored_file_name("", "image/jpeg"), "photo.jpg");
+        assert_eq!(stored_file_name(".png", "image/png"), "png.png");
+    }
+
+    #[test]
+    fn long_stems_are_truncated() {
+        let stored = stored_file_name(&format!("{}.png", "a".repeat(200)), "image/png");
+        assert_eq!(stored, format!("{}.png", "a".repeat(MAX_STEM_LENGTH)));
+    }
+}
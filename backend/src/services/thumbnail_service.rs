@@ -0,0 +1,47 @@
+use image::{imageops::FilterType, ImageFormat};
+use std::io::Cursor;
+
+/// Longest edge, in pixels, of a generated thumbnail
+pub const THUMBNAIL_MAX_EDGE: u32 = 256;
+
+/// MIME type of every generated thumbnail
+pub const THUMBNAIL_MIME_TYPE: &str = "image/jpeg";
+
+/// Whether uploads of this MIME type can be decoded, which thumbnails and re-encoding both
+/// need. Formats the `image` crate was built without count as unsupported.
+pub fn can_decode(mime_type: &str) -> bool {
+    ImageFormat::from_mime_type(mime_type).is_some_and(|format| format.reading_enabled())
+}
+
+/// Decode an uploaded photo and re-encode it as a JPEG no larger than `THUMBNAIL_MAX_EDGE`
+/// on its long edge. Images already small enough keep their size; transparency is dropped.
+pub fn generate_thumbnail(file_data: &[u8]) -> Result<Vec<u8>, image::ImageError> {
+    let image = image::load_from_memory(file_data)?;
+
+    let image = if image.width() > THUMBNAIL_MAX_EDGE || image.height() > THUMBNAIL_MAX_EDGE {
+        image.resize(THUMBNAIL_MAX_EDGE, THUMBNAIL_MAX_EDGE, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let mut thumbnail = Vec::new();
+    image
+        .to_rgb8()
+        .write_to(&mut Cursor::new(&mut thumbnail), ImageFormat::Jpeg)?;
+
+    Ok(thumbnail)
+}
+
+/// Storage path of the thumbnail for the photo stored at `file_path`: a `thumbnails`
+/// directory alongside the original, with a `.jpg` extension
+pub fn thumbnail_path(file_path: &str) -> String {
+    let (directory, filename) = match file_path.rsplit_once('/') {
+        Some((directory, filename)) => (format!("{}/", directory), filename),
+        None => (String::new(), file_path),
+    };
+    let stem = filename
+        .rsplit_once('.')
+        .map_or(filename, |(stem, _extension)| stem);
+
+    format!("{}thumbnails/{}.jpg", directory, stem)
+}
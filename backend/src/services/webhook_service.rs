@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use reqwest::{header, Client};
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How long a delivery may take before it is abandoned
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posted to `WEBHOOK_URL` when a miniature moves into `Completed`
+#[derive(Debug, Serialize)]
+pub struct MiniatureCompleted {
+    /// Always `miniature.completed`, so receivers can tell future events apart
+    pub event: &'static str,
+    pub miniature_id: i64,
+    pub miniature_name: String,
+    pub project_id: i64,
+    pub project_name: String,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Post `payload` as JSON to `url` from a background task. Failures are logged rather than
+/// returned, so a slow or broken receiver never delays or fails the request that fired it.
+pub fn spawn_delivery<T: Serialize>(url: String, payload: &T) -> JoinHandle<()> {
+    let body = serde_json::to_vec(payload);
+
+    tokio::spawn(async move {
+        let result = match body {
+            Ok(body) => tokio::time::timeout(DELIVERY_TIMEOUT, deliver(&url, body))
+                .await
+                .unwrap_or_else(|_| Err(format!("timed out after {:?}", DELIVERY_TIMEOUT))),
+            Err(e) => Err(format!("could not encode payload: {}", e)),
+        };
+
+        if let Err(e) = result {
+            tracing::warn!("Webhook delivery to {} failed: {}", url, e);
+        }
+    })
+}
+
+/// The HTTP client every delivery shares, so root certificates are loaded and connections
+/// pooled once rather than per webhook
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new)
+}
+
+/// Send one POST and treat any non-2xx answer as a failure
+async fn deliver(url: &str, body: Vec<u8>) -> Result<(), String> {
+    let response = client()
+        .post(url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("receiver answered {}", response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+    use tokio::sync::mpsc;
+
+    /// Serve a receiver on a free local port that forwards each body it gets and answers
+    /// with `status`
+    async fn receiver(status: StatusCode) -> (String, mpsc::UnboundedReceiver<serde_json::Value>) {
+        let (sender, bodies) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(sender): State<mpsc::UnboundedSender<serde_json::Value>>,
+                          Json(body): Json<serde_json::Value>| async move {
+                        sender.send(body).unwrap();
+                        status
+                    },
+                ),
+            )
+            .with_state(sender);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, bodies)
+    }
+
+    fn payload() -> MiniatureCompleted {
+        MiniatureCompleted {
+            event: "miniature.completed",
+            miniature_id: 7,
+            miniature_name: "Brother Captain".to_string(),
+            project_id: 3,
+            project_name: "Strike Force".to_string(),
+            completed_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_the_payload_as_json() {
+        let (url, mut bodies) = receiver(StatusCode::NO_CONTENT).await;
+
+        spawn_delivery(url, &payload()).await.unwrap();
+
+        let body = bodies.recv().await.unwrap();
+        assert_eq!(body["event"], "miniature.completed");
+        assert_eq!(body["miniature_name"], "Brother Captain");
+        assert_eq!(body["project_name"], "Strike Force");
+        assert!(body["completed_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn failures_are_reported_not_raised() {
+        let (url, mut bodies) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        assert_eq!(
+            deliver(&url, b"{}".to_vec()).await,
+            Err("receiver answered 500 Internal Server Error".to_string())
+        );
+        assert!(bodies.recv().await.is_some());
+
+        // Nothing listening: the task logs and finishes instead of panicking
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", closed.local_addr().unwrap());
+        drop(closed);
+        assert!(deliver(&url, b"{}".to_vec()).await.is_err());
+        spawn_delivery(url, &payload()).await.unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{config::Config, database::Database, services::storage_service::SharedStorage};
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+/// Router state. Handlers extract the part they need, e.g. `State<Database>`, so the settings
+/// and storage client are the ones the router was built with rather than re-read per request.
+#[derive(Clone)]
+pub struct AppState {
+    pub database: Database,
+    pub config: Arc<Config>,
+    pub storage: SharedStorage,
+}
+
+impl FromRef<AppState> for Database {
+    fn from_ref(state: &AppState) -> Self {
+        state.database.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedStorage {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
@@ -0,0 +1,263 @@
+use super::{StorageBackend, StorageError};
+use async_trait::async_trait;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::config::{
+    Credentials, Region, RequestChecksumCalculation, ResponseChecksumValidation,
+};
+use aws_sdk_s3::{primitives::ByteStream, Client};
+use std::time::Duration;
+
+/// Cloud Storage's S3-compatible XML API endpoint
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+
+/// Lifetime of signed GET URLs handed out by `get_url`
+const SIGNED_URL_EXPIRY: Duration = Duration::from_secs(3600); // 1 hour
+
+/// Google Cloud Storage backend.
+///
+/// Talks to the bucket through the XML API's S3 interoperability mode, authenticating with an
+/// HMAC key tied to a service account, so signed URLs are V4 signatures GCS accepts natively.
+pub struct GcsStorage {
+    client: Client,
+    bucket: String,
+    base_url: Option<String>,
+}
+
+impl GcsStorage {
+    pub async fn new(
+        bucket: String,
+        base_url: Option<String>,
+        hmac_access_id: String,
+        hmac_secret: String,
+    ) -> Result<Self, StorageError> {
+        let credentials = Credentials::new(hmac_access_id, hmac_secret, None, None, "gcs-hmac");
+
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .endpoint_url(GCS_ENDPOINT)
+            // GCS ignores the region, but SigV4 needs one to sign with
+            .region(Region::new("auto"))
+            .credentials_provider(credentials)
+            .force_path_style(true)
+            // GCS rejects the flexible checksum headers newer SDKs send by default
+            .request_checksum_calculation(RequestChecksumCalculation::WhenRequired)
+            .response_checksum_validation(ResponseChecksumValidation::WhenRequired)
+            .build();
+
+        let client = Client::from_conf(config);
+
+        Ok(GcsStorage {
+            client,
+            bucket,
+            base_url,
+        })
+    }
+
+    fn sanitize_key(&self, file_path: &str) -> Result<String, StorageError> {
+        // Remove any path traversal attempts and ensure safe object name; dropping empty segments
+        // keeps a removed `..` from leaving a double slash behind
+        let sanitized = file_path
+            .replace("..", "")
+            .replace("\\", "/")
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if sanitized.is_empty() {
+            return Err(StorageError::InvalidPath(
+                "Empty path after sanitization".to_string(),
+            ));
+        }
+
+        Ok(sanitized)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for GcsStorage {
+    async fn store(&self, file_data: &[u8], file_path: &str) -> Result<String, StorageError> {
+        let key = self.sanitize_key(file_path)?;
+
+        let body = ByteStream::from(file_data.to_vec());
+
+        let result = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .body(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(key),
+            Err(e) => Err(StorageError::GcsError(format!(
+                "Failed to upload to GCS: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn retrieve(&self, file_path: &str) -> Result<Vec<u8>, StorageError> {
+        let key = self.sanitize_key(file_path)?;
+
+        let result = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => {
+                let data = output.body.collect().await.map_err(|e| {
+                    StorageError::GcsError(format!("Failed to read GCS object body: {}", e))
+                })?;
+                Ok(data.into_bytes().to_vec())
+            }
+            Err(e) => {
+                if e.to_string().contains("NoSuchKey") {
+                    Err(StorageError::FileNotFound(file_path.to_string()))
+                } else {
+                    Err(StorageError::GcsError(format!(
+                        "Failed to retrieve from GCS: {}",
+                        e
+                    )))
+                }
+            }
+        }
+    }
+
+    async fn delete(&self, file_path: &str) -> Result<(), StorageError> {
+        let key = self.sanitize_key(file_path)?;
+
+        let result = self
+            .client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(StorageError::GcsError(format!(
+                "Failed to delete from GCS: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn exists(&self, file_path: &str) -> Result<bool, StorageError> {
+        let key = self.sanitize_key(file_path)?;
+
+        let result = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.to_string().contains("NotFound") {
+                    Ok(false)
+                } else {
+                    Err(StorageError::GcsError(format!(
+                        "Failed to check GCS object existence: {}",
+                        e
+                    )))
+                }
+            }
+        }
+    }
+
+    async fn get_url(&self, file_path: &str) -> Result<String, StorageError> {
+        let key = self.sanitize_key(file_path)?;
+
+        if let Some(base_url) = &self.base_url {
+            // Use custom base URL (e.g., a public bucket or Cloud CDN)
+            let url = format!("{}/{}", base_url.trim_end_matches('/'), key);
+            Ok(url)
+        } else {
+            // Generate a V4 signed URL for direct bucket access
+            let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
+                .expires_in(SIGNED_URL_EXPIRY)
+                .build()
+                .map_err(|e| {
+                    StorageError::GcsError(format!("Failed to create signing config: {}", e))
+                })?;
+
+            let presigned_request = self
+                .client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .presigned(presigning_config)
+                .await
+                .map_err(|e| {
+                    StorageError::GcsError(format!("Failed to create signed URL: {}", e))
+                })?;
+
+            Ok(presigned_request.uri().to_string())
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        let mut files = Vec::new();
+        let mut continuation_token = None;
+
+        // Each response holds at most 1000 keys; follow the continuation token for the rest
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| {
+                    StorageError::GcsError(format!("Failed to list GCS objects: {}", e))
+                })?;
+
+            files.extend(output.contents().iter().filter_map(|object| {
+                let size = object.size().unwrap_or(0).max(0) as u64;
+                object.key().map(|key| (key.to_string(), size))
+            }));
+
+            match output.next_continuation_token() {
+                Some(token) => continuation_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        // Verifies credentials and bucket access without touching any object
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                StorageError::GcsError(format!("Failed to reach GCS bucket {}: {}", self.bucket, e))
+            })
+    }
+
+    fn url_expires_in(&self) -> Option<Duration> {
+        // A custom base URL is served publicly; only signed URLs expire
+        if self.base_url.is_some() {
+            None
+        } else {
+            Some(SIGNED_URL_EXPIRY)
+        }
+    }
+}
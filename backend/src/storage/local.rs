@@ -1,8 +1,7 @@
 use super::{StorageBackend, StorageError};
 use async_trait::async_trait;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use tokio::fs;
-use uuid::Uuid;
 
 pub struct LocalStorage {
     base_path: PathBuf,
@@ -93,6 +92,64 @@ impl StorageBackend for LocalStorage {
         Ok(full_path.exists())
     }
 
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        let prefix = prefix
+            .replace("..", "")
+            .replace("\\", "/")
+            .trim_start_matches('/')
+            .to_string();
+
+        // Only the directory the prefix names can hold matches; walk it and everything below
+        let start = match prefix.rsplit_once('/') {
+            Some((directory, _)) => self.get_full_path(directory),
+            None => self.base_path.clone(),
+        };
+        if !start.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        let mut directories = vec![start];
+        while let Some(directory) = directories.pop() {
+            let mut entries = fs::read_dir(&directory).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    directories.push(path);
+                    continue;
+                }
+
+                let Ok(relative) = path.strip_prefix(&self.base_path) else {
+                    continue;
+                };
+                let key = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if key.starts_with(&prefix) {
+                    files.push((key, metadata.len()));
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        // The directory is created at startup; make sure it hasn't gone away since
+        let metadata = fs::metadata(&self.base_path).await?;
+        if !metadata.is_dir() {
+            return Err(StorageError::InvalidPath(format!(
+                "{} is not a directory",
+                self.base_path.display()
+            )));
+        }
+        Ok(())
+    }
+
     async fn get_url(&self, file_path: &str) -> Result<String, StorageError> {
         let sanitized_path = self.sanitize_path(file_path)?;
         let url = format!("{}/{}", self.base_url.trim_end_matches('/'), sanitized_path);
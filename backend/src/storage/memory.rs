@@ -0,0 +1,196 @@
+use super::{StorageBackend, StorageError};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Store shared by every `MemoryStorage::shared()` instance. Handlers build a fresh
+/// `StorageService` per request, so a per-instance map would forget files between requests.
+static SHARED_FILES: OnceLock<Files> = OnceLock::new();
+
+/// In-memory storage backend for tests; nothing touches the filesystem or the network
+pub struct MemoryStorage {
+    files: Files,
+}
+
+impl MemoryStorage {
+    /// An empty store private to this instance
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        MemoryStorage {
+            files: Arc::default(),
+        }
+    }
+
+    /// A handle on the process-wide store used when selected through configuration
+    pub fn shared() -> Self {
+        MemoryStorage {
+            files: SHARED_FILES.get_or_init(Arc::default).clone(),
+        }
+    }
+
+    fn sanitize_path(&self, file_path: &str) -> Result<String, StorageError> {
+        // Match the other backends so paths round-trip the same way
+        let sanitized = file_path
+            .replace("..", "")
+            .replace("\\", "/")
+            .trim_start_matches('/')
+            .to_string();
+
+        if sanitized.is_empty() {
+            return Err(StorageError::InvalidPath(
+                "Empty path after sanitization".to_string(),
+            ));
+        }
+
+        Ok(sanitized)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    async fn store(&self, file_data: &[u8], file_path: &str) -> Result<String, StorageError> {
+        let key = self.sanitize_path(file_path)?;
+        self.files
+            .lock()
+            .unwrap()
+            .insert(key.clone(), file_data.to_vec());
+        Ok(key)
+    }
+
+    async fn retrieve(&self, file_path: &str) -> Result<Vec<u8>, StorageError> {
+        let key = self.sanitize_path(file_path)?;
+        self.files
+            .lock()
+            .unwrap()
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| StorageError::FileNotFound(file_path.to_string()))
+    }
+
+    async fn delete(&self, file_path: &str) -> Result<(), StorageError> {
+        let key = self.sanitize_path(file_path)?;
+        self.files
+            .lock()
+            .unwrap()
+            .remove(&key)
+            .map(|_| ())
+            .ok_or_else(|| StorageError::FileNotFound(file_path.to_string()))
+    }
+
+    async fn exists(&self, file_path: &str) -> Result<bool, StorageError> {
+        let key = self.sanitize_path(file_path)?;
+        Ok(self.files.lock().unwrap().contains_key(&key))
+    }
+
+    async fn get_url(&self, file_path: &str) -> Result<String, StorageError> {
+        let key = self.sanitize_path(file_path)?;
+        Ok(format!("memory://{}", key))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        let mut files: Vec<(String, u64)> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, data)| (key.clone(), data.len() as u64))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn store_then_retrieve_round_trips() {
+        let storage = MemoryStorage::new();
+
+        let path = storage
+            .store(b"painted", "/miniatures/1/photo.png")
+            .await
+            .unwrap();
+        assert_eq!(path, "miniatures/1/photo.png");
+        assert_eq!(storage.retrieve(&path).await.unwrap(), b"painted");
+
+        // Overwriting replaces the contents
+        storage.store(b"repainted", &path).await.unwrap();
+        assert_eq!(storage.retrieve(&path).await.unwrap(), b"repainted");
+    }
+
+    #[tokio::test]
+    async fn delete_and_exists_track_contents() {
+        let storage = MemoryStorage::new();
+        let path = storage.store(b"data", "a/b.jpg").await.unwrap();
+
+        assert!(storage.exists(&path).await.unwrap());
+        storage.delete(&path).await.unwrap();
+        assert!(!storage.exists(&path).await.unwrap());
+        assert!(matches!(
+            storage.retrieve(&path).await,
+            Err(StorageError::FileNotFound(_))
+        ));
+
+        // Deleting again reports the file missing, like the local backend
+        assert!(matches!(
+            storage.delete(&path).await,
+            Err(StorageError::FileNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn paths_are_sanitized() {
+        let storage = MemoryStorage::new();
+
+        let path = storage.store(b"x", "..\\..\\etc\\passwd").await.unwrap();
+        assert_eq!(path, "etc/passwd");
+        assert!(matches!(
+            storage.store(b"x", "../").await,
+            Err(StorageError::InvalidPath(_))
+        ));
+        assert_eq!(
+            storage.get_url("/a/b.png").await.unwrap(),
+            "memory://a/b.png"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_filters_by_prefix() {
+        let storage = MemoryStorage::new();
+        storage.store(b"xx", "miniatures/2/b.png").await.unwrap();
+        storage.store(b"x", "miniatures/1/a.png").await.unwrap();
+        storage.store(b"x", "other/c.png").await.unwrap();
+
+        assert_eq!(
+            storage.list("miniatures/").await.unwrap(),
+            vec![
+                ("miniatures/1/a.png".to_string(), 1),
+                ("miniatures/2/b.png".to_string(), 2)
+            ]
+        );
+        assert_eq!(storage.list("").await.unwrap().len(), 3);
+        assert!(storage.list("missing/").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn instances_are_isolated_unless_shared() {
+        let first = MemoryStorage::new();
+        let second = MemoryStorage::new();
+        first.store(b"x", "isolated.png").await.unwrap();
+        assert!(!second.exists("isolated.png").await.unwrap());
+
+        MemoryStorage::shared()
+            .store(b"x", "shared-test.png")
+            .await
+            .unwrap();
+        assert!(MemoryStorage::shared()
+            .exists("shared-test.png")
+            .await
+            .unwrap());
+    }
+}
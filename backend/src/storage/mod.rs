@@ -1,13 +1,16 @@
 use async_trait::async_trait;
-use std::path::Path;
+use std::time::Duration;
 
+pub mod gcs;
 pub mod local;
+pub mod memory;
 pub mod s3;
 
 #[derive(Debug)]
 pub enum StorageError {
     IoError(std::io::Error),
     S3Error(String),
+    GcsError(String),
     InvalidPath(String),
     FileNotFound(String),
 }
@@ -17,6 +20,7 @@ impl std::fmt::Display for StorageError {
         match self {
             StorageError::IoError(e) => write!(f, "IO error: {}", e),
             StorageError::S3Error(e) => write!(f, "S3 error: {}", e),
+            StorageError::GcsError(e) => write!(f, "GCS error: {}", e),
             StorageError::InvalidPath(path) => write!(f, "Invalid path: {}", path),
             StorageError::FileNotFound(path) => write!(f, "File not found: {}", path),
         }
@@ -47,6 +51,20 @@ pub trait StorageBackend: Send + Sync {
 
     /// Get the public URL for a file (if applicable)
     async fn get_url(&self, file_path: &str) -> Result<String, StorageError>;
+
+    /// Storage path and size in bytes of every file whose path starts with `prefix`, sorted
+    /// by path; an empty prefix lists everything
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError>;
+
+    /// How long URLs returned by `get_url` stay valid, if they expire at all
+    fn url_expires_in(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Cheaply confirm the backend is reachable; a lookup of a key that needn't exist by default
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.exists(".health-check").await.map(|_| ())
+    }
 }
 
 pub enum StorageConfig {
@@ -59,6 +77,14 @@ pub enum StorageConfig {
         region: String,
         base_url: Option<String>,
     },
+    Gcs {
+        bucket: String,
+        base_url: Option<String>,
+        hmac_access_id: String,
+        hmac_secret: String,
+    },
+    /// Process-wide in-memory store, for tests
+    Memory,
 }
 
 pub struct Storage {
@@ -77,6 +103,15 @@ impl Storage {
                 region,
                 base_url,
             } => Box::new(s3::S3Storage::new(bucket, region, base_url).await?),
+            StorageConfig::Gcs {
+                bucket,
+                base_url,
+                hmac_access_id,
+                hmac_secret,
+            } => {
+                Box::new(gcs::GcsStorage::new(bucket, base_url, hmac_access_id, hmac_secret).await?)
+            }
+            StorageConfig::Memory => Box::new(memory::MemoryStorage::shared()),
         };
 
         Ok(Storage { backend })
@@ -94,6 +129,7 @@ impl Storage {
         self.backend.delete(file_path).await
     }
 
+    #[allow(dead_code)]
     pub async fn exists(&self, file_path: &str) -> Result<bool, StorageError> {
         self.backend.exists(file_path).await
     }
@@ -101,4 +137,16 @@ impl Storage {
     pub async fn get_url(&self, file_path: &str) -> Result<String, StorageError> {
         self.backend.get_url(file_path).await
     }
+
+    pub async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        self.backend.list(prefix).await
+    }
+
+    pub fn url_expires_in(&self) -> Option<Duration> {
+        self.backend.url_expires_in()
+    }
+
+    pub async fn health_check(&self) -> Result<(), StorageError> {
+        self.backend.health_check().await
+    }
 }
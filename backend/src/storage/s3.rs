@@ -4,6 +4,9 @@ use aws_config::BehaviorVersion;
 use aws_sdk_s3::{primitives::ByteStream, Client};
 use std::time::Duration;
 
+/// Lifetime of presigned GET URLs handed out by `get_url`
+const PRESIGNED_URL_EXPIRY: Duration = Duration::from_secs(3600); // 1 hour
+
 pub struct S3Storage {
     client: Client,
     bucket: String,
@@ -31,12 +34,15 @@ impl S3Storage {
     }
 
     fn sanitize_key(&self, file_path: &str) -> Result<String, StorageError> {
-        // Remove any path traversal attempts and ensure safe S3 key
+        // Remove any path traversal attempts and ensure safe S3 key; dropping empty segments
+        // keeps a removed `..` from leaving a double slash behind
         let sanitized = file_path
             .replace("..", "")
             .replace("\\", "/")
-            .trim_start_matches('/')
-            .to_string();
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
 
         if sanitized.is_empty() {
             return Err(StorageError::InvalidPath(
@@ -160,7 +166,7 @@ impl StorageBackend for S3Storage {
         } else {
             // Generate presigned URL for direct S3 access
             let presigning_config = aws_sdk_s3::presigning::PresigningConfig::builder()
-                .expires_in(Duration::from_secs(3600)) // 1 hour
+                .expires_in(PRESIGNED_URL_EXPIRY)
                 .build()
                 .map_err(|e| {
                     StorageError::S3Error(format!("Failed to create presigning config: {}", e))
@@ -180,4 +186,56 @@ impl StorageBackend for S3Storage {
             Ok(presigned_request.uri().to_string())
         }
     }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<(String, u64)>, StorageError> {
+        let mut files = Vec::new();
+        let mut continuation_token = None;
+
+        // Each response holds at most 1000 keys; follow the continuation token for the rest
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| StorageError::S3Error(format!("Failed to list S3 objects: {}", e)))?;
+
+            files.extend(output.contents().iter().filter_map(|object| {
+                let size = object.size().unwrap_or(0).max(0) as u64;
+                object.key().map(|key| (key.to_string(), size))
+            }));
+
+            match output.next_continuation_token() {
+                Some(token) => continuation_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        // Verifies credentials and bucket access without touching any object
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                StorageError::S3Error(format!("Failed to reach S3 bucket {}: {}", self.bucket, e))
+            })
+    }
+
+    fn url_expires_in(&self) -> Option<Duration> {
+        // A custom base URL is served publicly; only presigned URLs expire
+        if self.base_url.is_some() {
+            None
+        } else {
+            Some(PRESIGNED_URL_EXPIRY)
+        }
+    }
 }
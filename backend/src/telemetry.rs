@@ -0,0 +1,93 @@
+use crate::database::Database;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::Span;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Histogram of request latencies, labelled by method, route and status
+const REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+
+/// Latency buckets in seconds, from a quick lookup up to a large photo upload
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The metrics macros record into one process-wide recorder, so every router shares it
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Handle on the Prometheus recorder, installing it on first use
+pub fn prometheus_handle() -> &'static PrometheusHandle {
+    PROMETHEUS_HANDLE.get_or_init(|| {
+        PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full(REQUEST_DURATION_METRIC.to_string()),
+                REQUEST_DURATION_BUCKETS,
+            )
+            .expect("Request duration buckets must not be empty")
+            .install_recorder()
+            .expect("Failed to install Prometheus recorder")
+    })
+}
+
+/// Span wrapping each request, so handler spans and their events carry the request id that
+/// `SetRequestIdLayer` assigned or the client sent
+pub fn request_span(request: &Request) -> Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id,
+    )
+}
+
+/// Middleware counting requests and timing them per route
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    // Label by route template rather than raw path so each id doesn't get its own series
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+    let method = request.method().to_string();
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!(REQUEST_DURATION_METRIC, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+/// Render all recorded metrics in the Prometheus text exposition format
+pub async fn render_metrics(State(database): State<Database>) -> impl IntoResponse {
+    // Pool usage is sampled at scrape time rather than tracked on every checkout
+    let (size, idle) = database.pool_connections();
+    metrics::gauge!("db_pool_connections", "state" => "active")
+        .set(size.saturating_sub(idle as u32) as f64);
+    metrics::gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus_handle().render(),
+    )
+}
@@ -1,12 +1,13 @@
 #[cfg(test)]
 mod property_tests {
     use crate::database::{Database, DatabaseConfig};
+    use crate::repositories::photo_repository::validate_photo_path;
     use crate::repositories::{MiniatureRepository, PhotoRepository, ProjectRepository};
     use quickcheck::TestResult;
     use quickcheck_macros::quickcheck;
     use shared_types::{
-        CreateMiniatureRequest, CreateProjectRequest, GameSystem, MiniatureType, ProgressStatus,
-        UpdateProjectRequest,
+        CreateMiniatureRequest, CreateProjectRequest, GameSystem, MiniatureType,
+        PatchProjectRequest,
     };
     use std::time::Duration;
 
@@ -19,6 +20,7 @@ mod property_tests {
             game_system: GameSystem::AgeOfSigmar, // Always provide a valid game system
             army: army.clone(),
             description: None,
+            tags: Vec::new(),
         };
 
         // Project should be valid if and only if both name and army are valid strings
@@ -36,6 +38,7 @@ mod property_tests {
             name: name.clone(),
             miniature_type: MiniatureType::Troop, // Always provide a valid miniature type
             notes: None,
+            points_value: None,
         };
 
         // Miniature should be valid if and only if name is a valid string
@@ -64,6 +67,7 @@ mod property_tests {
                 game_system: GameSystem::AgeOfSigmar,
                 army: "Original Army".to_string(),
                 description: Some("Original description".to_string()),
+                tags: Vec::new(),
             };
 
             let project = ProjectRepository::create(&database, create_request)
@@ -71,11 +75,13 @@ mod property_tests {
                 .unwrap();
 
             // Update the project
-            let update_request = UpdateProjectRequest {
+            let update_request = PatchProjectRequest {
                 name: Some(name.clone()),
                 game_system: None,
                 army: Some(army.clone()),
                 description: None,
+                tags: None,
+                updated_at: None,
             };
 
             let updated_project = ProjectRepository::update(&database, project.id, update_request)
@@ -117,6 +123,7 @@ mod property_tests {
                 game_system: GameSystem::AgeOfSigmar,
                 army: "Test Army".to_string(),
                 description: None,
+                tags: Vec::new(),
             };
 
             let project = ProjectRepository::create(&database, create_request)
@@ -128,6 +135,7 @@ mod property_tests {
                 name: miniature_name.clone(),
                 miniature_type: MiniatureType::Troop,
                 notes: None,
+                points_value: None,
             };
 
             let miniature = MiniatureRepository::create(&database, project.id, miniature_request)
@@ -142,6 +150,8 @@ mod property_tests {
                 "/tmp/test.jpg".to_string(),
                 1024,
                 "image/jpeg".to_string(),
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -176,8 +186,8 @@ mod property_tests {
     // Feature: miniature-painting-tracker, Property 7: Miniature cascade deletion
     #[quickcheck]
     fn test_miniature_cascade_deletion(photo_filename: String) -> TestResult {
-        // Skip invalid inputs
-        if photo_filename.trim().is_empty() {
+        // Skip invalid inputs, including names the repository refuses to store as paths
+        if photo_filename.trim().is_empty() || validate_photo_path(&photo_filename).is_err() {
             return TestResult::discard();
         }
 
@@ -192,6 +202,7 @@ mod property_tests {
                 game_system: GameSystem::AgeOfSigmar,
                 army: "Test Army".to_string(),
                 description: None,
+                tags: Vec::new(),
             };
 
             let project = ProjectRepository::create(&database, create_request)
@@ -203,6 +214,7 @@ mod property_tests {
                 name: "Test Miniature".to_string(),
                 miniature_type: MiniatureType::Character,
                 notes: None,
+                points_value: None,
             };
 
             let miniature = MiniatureRepository::create(&database, project.id, miniature_request)
@@ -217,6 +229,8 @@ mod property_tests {
                 format!("/tmp/{}", photo_filename),
                 2048,
                 "image/png".to_string(),
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -256,11 +270,16 @@ mod property_tests {
                 game_system: GameSystem::Warhammer40k,
                 army: army.clone(),
                 description: None,
+                tags: Vec::new(),
             };
 
             let project_result = crate::handlers::projects::create_project(
                 axum::extract::State(database.clone()),
-                axum::Json(project_request),
+                axum::extract::State(std::sync::Arc::new(
+                    crate::config::Config::from_env().unwrap(),
+                )),
+                axum::http::HeaderMap::new(),
+                Ok(axum::Json(project_request)),
             )
             .await;
 
@@ -281,18 +300,20 @@ mod property_tests {
             // Test miniature creation validation (only if we have a valid project)
             let miniature_validation_correct = if project_validation_passed {
                 // Create a project first
-                let project = project_result.unwrap().0;
+                let (_, _, axum::Json(project)) = project_result.unwrap();
 
                 let miniature_request = CreateMiniatureRequest {
                     name: miniature_name.clone(),
                     miniature_type: MiniatureType::Troop,
                     notes: None,
+                    points_value: None,
                 };
 
                 let miniature_result = crate::handlers::miniatures::create_miniature(
                     axum::extract::State(database.clone()),
-                    axum::extract::Path(project.id),
-                    axum::Json(miniature_request),
+                    crate::handlers::Path(project.id),
+                    axum::http::HeaderMap::new(),
+                    Ok(axum::Json(miniature_request)),
                 )
                 .await;
 
@@ -325,11 +346,16 @@ mod property_tests {
                 game_system: GameSystem::AgeOfSigmar,
                 army: army.clone(),
                 description: None,
+                tags: Vec::new(),
             };
 
             let result = crate::handlers::projects::create_project(
                 axum::extract::State(database.clone()),
-                axum::Json(project_request),
+                axum::extract::State(std::sync::Arc::new(
+                    crate::config::Config::from_env().unwrap(),
+                )),
+                axum::http::HeaderMap::new(),
+                Ok(axum::Json(project_request)),
             )
             .await;
 
@@ -346,7 +372,7 @@ mod property_tests {
                 // Valid input should result in success (2xx status codes)
                 (true, Ok(_)) => TestResult::from_bool(true),
                 // Invalid input should result in client error (4xx status codes)
-                (false, Err(crate::error::AppError::ValidationError(_))) => {
+                (false, Err(crate::error::AppError::FieldValidation(_))) => {
                     TestResult::from_bool(true)
                 }
                 // Any other combination is incorrect
@@ -374,30 +400,37 @@ mod property_tests {
                 game_system: GameSystem::HorusHeresy,
                 army: "Test Army".to_string(),
                 description: None,
+                tags: Vec::new(),
             };
 
             let result = crate::handlers::projects::create_project(
                 axum::extract::State(database.clone()),
-                axum::Json(project_request),
+                axum::extract::State(std::sync::Arc::new(
+                    crate::config::Config::from_env().unwrap(),
+                )),
+                axum::http::HeaderMap::new(),
+                Ok(axum::Json(project_request)),
             )
             .await;
 
-            // Should get a validation error
+            // Should get a validation error naming the offending field
             match result {
-                Err(crate::error::AppError::ValidationError(msg)) => {
+                Err(error @ crate::error::AppError::FieldValidation(_)) => {
                     // Convert the error to a response to test the structure
-                    let response = axum::response::IntoResponse::into_response(
-                        crate::error::AppError::ValidationError(msg),
-                    );
-
-                    // Extract the status code
+                    let response = axum::response::IntoResponse::into_response(error);
                     let status = response.status();
-                    let is_client_error = status.is_client_error(); // 4xx status codes
+                    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                        .await
+                        .unwrap();
+                    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
 
-                    // The error should be properly structured (we can't easily extract the JSON body in this test,
-                    // but we can verify the status code is correct for validation errors)
                     TestResult::from_bool(
-                        is_client_error && status == axum::http::StatusCode::BAD_REQUEST,
+                        status == axum::http::StatusCode::BAD_REQUEST
+                            && body["error"]["error_type"] == "validation_error"
+                            && body["error"]["details"]
+                                == serde_json::json!([
+                                    { "field": "name", "message": "Project name is required" }
+                                ]),
                     )
                 }
                 _ => TestResult::from_bool(false), // Should have gotten a validation error
@@ -408,8 +441,8 @@ mod property_tests {
     // Feature: miniature-painting-tracker, Property 10: Photo-miniature association
     #[quickcheck]
     fn test_photo_miniature_association(filename: String, file_size: u32) -> TestResult {
-        // Skip invalid inputs
-        if filename.trim().is_empty() || file_size == 0 {
+        // Skip invalid inputs, including names the repository refuses to store as paths
+        if filename.trim().is_empty() || file_size == 0 || validate_photo_path(&filename).is_err() {
             return TestResult::discard();
         }
 
@@ -430,12 +463,14 @@ mod property_tests {
                 format!("/tmp/{}", filename),
                 file_size as i64,
                 "image/jpeg".to_string(),
+                None,
+                None,
             )
             .await
             .unwrap();
 
             // Query the miniature's photos
-            let photos = PhotoRepository::find_by_miniature_id(&database, miniature.id)
+            let photos = PhotoRepository::find_by_miniature_id(&database, miniature.id, false)
                 .await
                 .unwrap();
 
@@ -489,6 +524,8 @@ mod property_tests {
                     format!("/tmp/photo_{}.jpg", i),
                     1024,
                     "image/jpeg".to_string(),
+                    None,
+                    None,
                 )
                 .await
                 .unwrap();
@@ -499,7 +536,7 @@ mod property_tests {
             }
 
             // Query photos for the miniature
-            let photos = PhotoRepository::find_by_miniature_id(&database, miniature.id)
+            let photos = PhotoRepository::find_by_miniature_id(&database, miniature.id, false)
                 .await
                 .unwrap();
 
@@ -526,8 +563,8 @@ mod property_tests {
     // Feature: miniature-painting-tracker, Property 13: Photo storage deletion
     #[quickcheck]
     fn test_photo_storage_deletion(filename: String) -> TestResult {
-        // Skip invalid inputs
-        if filename.trim().is_empty() {
+        // Skip invalid inputs, including names the repository refuses to store as paths
+        if filename.trim().is_empty() || validate_photo_path(&filename).is_err() {
             return TestResult::discard();
         }
 
@@ -548,6 +585,8 @@ mod property_tests {
                 format!("/tmp/{}", filename),
                 1024,
                 "image/jpeg".to_string(),
+                None,
+                None,
             )
             .await
             .unwrap();
@@ -579,6 +618,7 @@ mod property_tests {
             game_system: GameSystem::AgeOfSigmar,
             army: "Test Army".to_string(),
             description: None,
+            tags: Vec::new(),
         };
         ProjectRepository::create(database, create_request)
             .await
@@ -593,6 +633,7 @@ mod property_tests {
             name: "Test Miniature".to_string(),
             miniature_type: MiniatureType::Troop,
             notes: None,
+            points_value: None,
         };
         MiniatureRepository::create(database, project_id, miniature_request)
             .await
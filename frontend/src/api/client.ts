@@ -2,14 +2,28 @@ import axios, { AxiosError } from 'axios'
 import type {
   Project,
   Miniature,
+  MiniatureWithCounts,
+  MiniatureDetail,
+  MiniaturePage,
   PaintingRecipe,
+  Paint,
+  LinkedRecipe,
+  RecipeProjectUsage,
+  ProgressChange,
   Photo,
+  PhotoBatchResult,
+  BulkLinkResult,
+  ArmyUsage,
+  ProjectPoints,
+  GameSystem,
+  MiniatureSort,
   CreateProjectRequest,
   UpdateProjectRequest,
   CreateMiniatureRequest,
   UpdateMiniatureRequest,
   CreateRecipeRequest,
   UpdateRecipeRequest,
+  PaintRequest,
   ErrorResponse,
 } from '../types'
 
@@ -62,58 +76,142 @@ apiClient.interceptors.response.use(
 
 // Project API
 export const projectApi = {
-  list: async () => {
-    const response = await apiClient.get<{ projects: Project[] }>('/projects')
+  // Archived projects are left out unless asked for
+  list: async (includeArchived = false) => {
+    const params = includeArchived ? { include_archived: true } : {}
+    const response = await apiClient.get<{ projects: Project[] }>('/projects', { params })
     return { ...response, data: response.data.projects }
   },
   get: (id: number) => apiClient.get<Project>(`/projects/${id}`),
-  create: (data: CreateProjectRequest) => apiClient.post<Project>('/projects', data),
-  update: (id: number, data: UpdateProjectRequest) => apiClient.put<Project>(`/projects/${id}`, data),
+  // Reuse the same idempotencyKey when retrying so a lost response can't create the project twice
+  create: (data: CreateProjectRequest, idempotencyKey?: string) =>
+    apiClient.post<Project>('/projects', data, {
+      headers: idempotencyKey ? { 'Idempotency-Key': idempotencyKey } : {},
+    }),
+  update: (id: number, data: UpdateProjectRequest) => apiClient.patch<Project>(`/projects/${id}`, data),
   delete: (id: number) => apiClient.delete(`/projects/${id}`),
+  archive: (id: number) => apiClient.post<Project>(`/projects/${id}/archive`),
+  unarchive: (id: number) => apiClient.post<Project>(`/projects/${id}/unarchive`),
+  armies: async (gameSystem?: GameSystem) => {
+    const params = gameSystem ? { game_system: gameSystem } : {}
+    const response = await apiClient.get<{ armies: ArmyUsage[] }>('/armies', { params })
+    return { ...response, data: response.data.armies }
+  },
+  points: (id: number) => apiClient.get<ProjectPoints>(`/projects/${id}/points`),
 }
 
 // Miniature API
 export const miniatureApi = {
-  listByProject: async (projectId: number) => {
-    const response = await apiClient.get<{ miniatures: Miniature[] }>(`/projects/${projectId}/miniatures`)
+  listByProject: async (projectId: number, sort?: MiniatureSort) => {
+    const response = await apiClient.get<{ miniatures: Miniature[] }>(
+      `/projects/${projectId}/miniatures`,
+      { params: { sort } }
+    )
     return { ...response, data: response.data.miniatures }
   },
+  listByProjectWithPhotoCounts: async (projectId: number, sort?: MiniatureSort) => {
+    const response = await apiClient.get<{ miniatures: MiniatureWithCounts[] }>(
+      `/projects/${projectId}/miniatures`,
+      { params: { include: 'photo_count', sort } }
+    )
+    return { ...response, data: response.data.miniatures }
+  },
+  // For infinite scroll: pages stay consistent while miniatures are added
+  listPageByProject: (projectId: number, limit: number, after?: string) =>
+    apiClient.get<MiniaturePage>(`/projects/${projectId}/miniatures`, {
+      params: { limit, after },
+    }),
   get: (id: number) => apiClient.get<Miniature>(`/miniatures/${id}`),
-  create: (projectId: number, data: CreateMiniatureRequest) => 
-    apiClient.post<Miniature>(`/projects/${projectId}/miniatures`, data),
-  update: (id: number, data: UpdateMiniatureRequest) => 
-    apiClient.put<Miniature>(`/miniatures/${id}`, data),
+  // Preferred when the project is known: a miniature from another project is a 404
+  getInProject: (projectId: number, id: number) =>
+    apiClient.get<Miniature>(`/projects/${projectId}/miniatures/${id}`),
+  // The miniature with its photos and linked recipes in one request
+  getDetail: (id: number, expand: ('photos' | 'recipes')[] = ['photos', 'recipes']) =>
+    apiClient.get<MiniatureDetail>(`/miniatures/${id}`, { params: { expand: expand.join(',') } }),
+  create: (projectId: number, data: CreateMiniatureRequest, idempotencyKey?: string) =>
+    apiClient.post<Miniature>(`/projects/${projectId}/miniatures`, data, {
+      headers: idempotencyKey ? { 'Idempotency-Key': idempotencyKey } : {},
+    }),
+  // All or nothing: an invalid entry fails the batch with errors like `miniatures[2].name`
+  createBatch: (projectId: number, miniatures: CreateMiniatureRequest[]) =>
+    apiClient.post<Miniature[]>(`/projects/${projectId}/miniatures/batch`, { miniatures }),
+  // With STRICT_PROGRESS_TRANSITIONS on, skipping statuses ahead answers 400 unless forced
+  update: (id: number, data: UpdateMiniatureRequest, force = false) =>
+    apiClient.patch<Miniature>(`/miniatures/${id}`, data, { params: force ? { force } : {} }),
   delete: (id: number) => apiClient.delete(`/miniatures/${id}`),
+  // Photos and recipe links go with the miniature
+  move: (id: number, targetProjectId: number) =>
+    apiClient.patch<Miniature>(`/miniatures/${id}/move`, { target_project_id: targetProjectId }),
+  // Status changes, oldest first
+  history: async (id: number) => {
+    const response = await apiClient.get<{ history: ProgressChange[] }>(`/miniatures/${id}/history`)
+    return { ...response, data: response.data.history }
+  },
 }
 
 // Recipe API
 export const recipeApi = {
-  list: async (type?: string) => {
-    const params = type ? { type } : {}
+  // With a project id, only the recipes that project's miniatures are linked to
+  list: async (type?: string, projectId?: number) => {
+    const params = {
+      ...(type ? { type } : {}),
+      ...(projectId !== undefined ? { project_id: projectId } : {}),
+    }
     const response = await apiClient.get<{ recipes: PaintingRecipe[] }>('/recipes', { params })
     return { ...response, data: response.data.recipes }
   },
   get: (id: number) => apiClient.get<PaintingRecipe>(`/recipes/${id}`),
   create: (data: CreateRecipeRequest) => apiClient.post<PaintingRecipe>('/recipes', data),
+  // Runs the create checks without saving; invalid recipes answer 400 with field details
+  validate: (data: CreateRecipeRequest) => apiClient.post<{ valid: true }>('/recipes/validate', data),
   update: (id: number, data: UpdateRecipeRequest) => 
     apiClient.put<PaintingRecipe>(`/recipes/${id}`, data),
-  delete: (id: number) => apiClient.delete(`/recipes/${id}`),
+  // Recipes still linked to miniatures answer 409 unless forced
+  delete: (id: number, force = false) =>
+    apiClient.delete(`/recipes/${id}`, { params: force ? { force } : {} }),
   getUsageCount: async (id: number) => {
     const response = await apiClient.get<{ recipe_id: number; miniature_count: number }>(`/recipes/${id}/usage`)
     return response.data.miniature_count
   },
+  // Live projects whose miniatures use the recipe, by name
+  projects: async (id: number) => {
+    const response = await apiClient.get<{ projects: RecipeProjectUsage[] }>(`/recipes/${id}/projects`)
+    return { ...response, data: response.data.projects }
+  },
+  // Paints the recipe calls for that aren't in the inventory or have run out
+  missingPaints: async (id: number) => {
+    const response = await apiClient.get<{ missing: string[] }>(`/recipes/${id}/missing-paints`)
+    return response.data.missing
+  },
+}
+
+// Paint inventory API
+export const paintApi = {
+  list: async () => {
+    const response = await apiClient.get<{ paints: Paint[] }>('/paints')
+    return { ...response, data: response.data.paints }
+  },
+  get: (id: number) => apiClient.get<Paint>(`/paints/${id}`),
+  create: (data: PaintRequest) => apiClient.post<Paint>('/paints', data),
+  update: (id: number, data: PaintRequest) => apiClient.put<Paint>(`/paints/${id}`, data),
+  delete: (id: number) => apiClient.delete(`/paints/${id}`),
 }
 
 // Miniature Recipe Linking API
 export const miniatureRecipeApi = {
   getRecipes: async (miniatureId: number) => {
-    const response = await apiClient.get<{ recipes: PaintingRecipe[] }>(`/miniatures/${miniatureId}/recipes`)
+    const response = await apiClient.get<{ recipes: LinkedRecipe[] }>(`/miniatures/${miniatureId}/recipes`)
     return response.data.recipes
   },
   linkRecipe: (miniatureId: number, recipeId: number) =>
     apiClient.post(`/miniatures/${miniatureId}/recipes/${recipeId}`),
   unlinkRecipe: (miniatureId: number, recipeId: number) =>
     apiClient.delete(`/miniatures/${miniatureId}/recipes/${recipeId}`),
+  // One recipe onto many miniatures; each id gets its own linked, skipped, or error result
+  linkMany: (recipeId: number, miniatureIds: number[], force = false) =>
+    apiClient.post<BulkLinkResult>(`/recipes/${recipeId}/link`, { miniature_ids: miniatureIds }, {
+      params: force ? { force } : {},
+    }),
 }
 
 // Photo API
@@ -129,7 +227,20 @@ export const photoApi = {
       },
     })
   },
+  // Stores each file on its own; rejected files are listed in `errors` rather than failing the batch
+  uploadBatch: (miniatureId: number, files: File[]) => {
+    const formData = new FormData()
+    files.forEach((file) => formData.append('photo', file))
+    return apiClient.post<PhotoBatchResult>(`/miniatures/${miniatureId}/photos/batch`, formData, {
+      headers: {
+        'Content-Type': 'multipart/form-data',
+      },
+    })
+  },
   delete: (id: number) => apiClient.delete(`/photos/${id}`),
+  // Deletes every photo of the miniature along with its files
+  clearForMiniature: (miniatureId: number) =>
+    apiClient.delete<{ deleted: number }>(`/miniatures/${miniatureId}/photos`),
 }
 
 export default apiClient
\ No newline at end of file
@@ -86,6 +86,7 @@ const MiniatureCard: React.FC<MiniatureCardProps> = ({
 
   const getProgressColor = (status: ProgressStatus) => {
     switch (status) {
+      case ProgressStatus.Varnished:
       case ProgressStatus.Completed:
         return 'success'
       case ProgressStatus.Detailed:
@@ -110,6 +111,7 @@ const MiniatureCard: React.FC<MiniatureCardProps> = ({
       case ProgressStatus.Detailed:
         return 80
       case ProgressStatus.Completed:
+      case ProgressStatus.Varnished:
         return 100
       default:
         return 0
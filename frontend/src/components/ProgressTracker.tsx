@@ -15,6 +15,7 @@ import {
   Palette as PaletteIcon,
   AutoFixHigh as DetailIcon,
   CheckCircle as CompleteIcon,
+  Shield as VarnishedIcon,
   RadioButtonUnchecked as UnpaintedIcon,
 } from '@mui/icons-material'
 import type { ProgressStatus } from '../types'
@@ -66,6 +67,12 @@ const ProgressTracker: React.FC<ProgressTrackerProps> = ({
       icon: <CompleteIcon />,
       description: 'Painting finished',
     },
+    {
+      status: ProgressStatusEnum.Varnished,
+      label: 'Varnished',
+      icon: <VarnishedIcon />,
+      description: 'Sealed and protected',
+    },
   ]
 
   const currentStepIndex = steps.findIndex(step => step.status === currentStatus)
@@ -111,7 +118,7 @@ const ProgressTracker: React.FC<ProgressTrackerProps> = ({
               Unpainted
             </Typography>
             <Typography variant="caption" color="text.secondary">
-              Completed
+              Varnished
             </Typography>
           </Box>
         )}
@@ -135,6 +135,7 @@ function ProjectDetail() {
 
   const getProgressColor = (status: string) => {
     switch (status) {
+      case 'varnished':
       case 'completed':
         return 'success'
       case 'detailed':
@@ -152,7 +153,10 @@ function ProjectDetail() {
     const total = miniatures.length
     if (total === 0) return { completed: 0, percentage: 0 }
 
-    const completed = miniatures.filter(m => m.progress_status === 'completed').length
+    // Varnished models are finished too
+    const completed = miniatures.filter(
+      m => m.progress_status === 'completed' || m.progress_status === 'varnished'
+    ).length
     const percentage = Math.round((completed / total) * 100)
     
     return { completed, total, percentage }
@@ -2,12 +2,16 @@
 export enum GameSystem {
   AgeOfSigmar = 'age_of_sigmar',
   HorusHeresy = 'horus_heresy',
+  KillTeam = 'kill_team',
+  MiddleEarth = 'middle_earth',
   Warhammer40k = 'warhammer_40k',
 }
 
 export enum MiniatureType {
   Troop = 'troop',
   Character = 'character',
+  Vehicle = 'vehicle',
+  Monster = 'monster',
 }
 
 export enum ProgressStatus {
@@ -16,8 +20,12 @@ export enum ProgressStatus {
   Basecoated = 'basecoated',
   Detailed = 'detailed',
   Completed = 'completed',
+  Varnished = 'varnished',
 }
 
+// Orders a project's miniatures can be listed in; `status` follows painting progression
+export type MiniatureSort = 'name' | 'created_at' | 'status'
+
 export interface Project {
   id: number
   name: string
@@ -26,6 +34,10 @@ export interface Project {
   description?: string
   created_at: string
   updated_at: string
+  deleted_at?: string | null
+  tags: string[]
+  // Finished work hidden from the default list; not the same as deleted
+  archived: boolean
 }
 
 export interface Miniature {
@@ -35,10 +47,20 @@ export interface Miniature {
   miniature_type: MiniatureType
   progress_status: ProgressStatus
   notes?: string
+  points_value?: number
   created_at: string
   updated_at: string
 }
 
+// One move of a miniature from one progress status to another
+export interface ProgressChange {
+  id: number
+  miniature_id: number
+  from_status: ProgressStatus
+  to_status: ProgressStatus
+  changed_at: string
+}
+
 export interface PaintingRecipe {
   id: number
   name: string
@@ -51,6 +73,33 @@ export interface PaintingRecipe {
   updated_at: string
 }
 
+// A recipe linked to a miniature, with when the link was made
+export interface LinkedRecipe extends PaintingRecipe {
+  linked_at: string
+}
+
+export interface MiniatureWithCounts extends Miniature {
+  photo_count: number
+}
+
+// A page of a project's miniatures in creation order; pass next_cursor back as `after`
+export interface MiniaturePage {
+  count: number
+  miniatures: Miniature[]
+  next_cursor: string | null
+}
+
+// A miniature with the nested data its request expanded
+export interface MiniatureDetail extends Miniature {
+  photos?: Photo[]
+  recipes?: LinkedRecipe[]
+}
+
+// A project using a recipe, with how many of its miniatures do
+export interface RecipeProjectUsage extends Project {
+  miniature_count: number
+}
+
 export interface Photo {
   id: number
   miniature_id: number
@@ -59,6 +108,57 @@ export interface Photo {
   file_size: number
   mime_type: string
   uploaded_at: string
+  content_hash?: string | null
+  thumbnail_path?: string | null
+  is_primary: boolean
+}
+
+// A file from a batch upload that was not stored
+export interface PhotoUploadFailure {
+  index: number
+  filename?: string
+  error_type: string
+  message: string
+}
+
+// What a bulk recipe link did to one miniature
+export interface BulkLinkOutcome {
+  miniature_id: number
+  status: 'linked' | 'skipped' | 'error'
+  message: string | null
+}
+
+export interface BulkLinkResult {
+  recipe_id: number
+  linked: number
+  results: BulkLinkOutcome[]
+}
+
+export interface PhotoBatchResult {
+  count: number
+  photos: Photo[]
+  errors: PhotoUploadFailure[]
+}
+
+// An army name already used by projects, for autocompleting new ones
+export interface ArmyUsage {
+  army: string
+  project_count: number
+}
+
+export interface ProjectPoints {
+  project_id: number
+  total_points: number
+  points_by_type: Record<MiniatureType, number>
+}
+
+export interface Paint {
+  id: number
+  name: string
+  brand?: string
+  quantity_remaining: number
+  created_at: string
+  updated_at: string
 }
 
 // Request/Response DTOs
@@ -67,25 +167,39 @@ export interface CreateProjectRequest {
   game_system: GameSystem
   army: string
   description?: string
+  tags?: string[]
 }
 
 export interface UpdateProjectRequest {
   name?: string
   game_system?: GameSystem
   army?: string
-  description?: string
+  // Sent as a PATCH: omitted fields are kept, null clears the description
+  description?: string | null
+  tags?: string[]
+  // The updated_at last seen; the update fails with 409 if the project changed since
+  updated_at?: string
 }
 
 export interface CreateMiniatureRequest {
   name: string
   miniature_type: MiniatureType
   notes?: string
+  points_value?: number
 }
 
 export interface UpdateMiniatureRequest {
   name?: string
   progress_status?: ProgressStatus
-  notes?: string
+  // Sent as a PATCH: omitted fields are kept, null clears the notes or points
+  notes?: string | null
+  points_value?: number | null
+  // The updated_at last seen; the update fails with 409 if the miniature changed since
+  updated_at?: string
+}
+
+export interface BulkDeleteMiniaturesRequest {
+  miniature_ids: number[]
 }
 
 export interface CreateRecipeRequest {
@@ -105,11 +219,28 @@ export interface UpdateRecipeRequest {
   notes?: string
 }
 
+// Used for both create and update; an update replaces every field
+export interface PaintRequest {
+  name: string
+  brand?: string
+  quantity_remaining: number
+}
+
+export interface ReorderRecipeStepsRequest {
+  order: number[]
+}
+
+export interface FieldError {
+  field: string
+  message: string
+}
+
 export interface ErrorResponse {
   error: {
     error_type: string
     message: string
-    details?: any
+    // FieldError[] when a create request has invalid fields
+    details?: FieldError[] | any
     timestamp: string
   }
 }
@@ -146,12 +277,16 @@ export type MiniatureWithPhotos = Miniature & {
 export const GAME_SYSTEM_LABELS: Record<GameSystem, string> = {
   [GameSystem.AgeOfSigmar]: 'Age of Sigmar',
   [GameSystem.HorusHeresy]: 'Horus Heresy',
+  [GameSystem.KillTeam]: 'Kill Team',
+  [GameSystem.MiddleEarth]: 'Middle-earth SBG',
   [GameSystem.Warhammer40k]: 'Warhammer 40K',
 }
 
 export const MINIATURE_TYPE_LABELS: Record<MiniatureType, string> = {
   [MiniatureType.Troop]: 'Troop',
   [MiniatureType.Character]: 'Character',
+  [MiniatureType.Vehicle]: 'Vehicle',
+  [MiniatureType.Monster]: 'Monster',
 }
 
 export const PROGRESS_STATUS_LABELS: Record<ProgressStatus, string> = {
@@ -160,4 +295,5 @@ export const PROGRESS_STATUS_LABELS: Record<ProgressStatus, string> = {
   [ProgressStatus.Basecoated]: 'Basecoated',
   [ProgressStatus.Detailed]: 'Detailed',
   [ProgressStatus.Completed]: 'Completed',
+  [ProgressStatus.Varnished]: 'Varnished',
 }
\ No newline at end of file
@@ -196,7 +196,7 @@ export class MiniatureTrackerStack extends cdk.Stack {
 
     // Configure health check
     fargateService.targetGroup.configureHealthCheck({
-      path: '/health',
+      path: '/readyz',
       healthyHttpCodes: '200',
       interval: cdk.Duration.seconds(30),
       timeout: cdk.Duration.seconds(5),
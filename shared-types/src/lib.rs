@@ -1,26 +1,99 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+// The enums below deserialize by hand so an unknown value is reported with every accepted
+// one; `as_str` must spell each variant the way serde serializes it.
+
+#[derive(Debug, Clone, Serialize, sqlx::Type, ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum GameSystem {
     AgeOfSigmar,
     HorusHeresy,
+    KillTeam,
+    MiddleEarth,
     #[serde(rename = "warhammer_40k")]
     #[sqlx(rename = "warhammer_40k")]
     Warhammer40k,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+impl GameSystem {
+    pub fn all() -> &'static [GameSystem] {
+        &[
+            GameSystem::AgeOfSigmar,
+            GameSystem::HorusHeresy,
+            GameSystem::KillTeam,
+            GameSystem::MiddleEarth,
+            GameSystem::Warhammer40k,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameSystem::AgeOfSigmar => "age_of_sigmar",
+            GameSystem::HorusHeresy => "horus_heresy",
+            GameSystem::KillTeam => "kill_team",
+            GameSystem::MiddleEarth => "middle_earth",
+            GameSystem::Warhammer40k => "warhammer_40k",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GameSystem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known_value(
+            deserializer,
+            "game_system",
+            GameSystem::all(),
+            GameSystem::as_str,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::Type, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum MiniatureType {
     Troop,
     Character,
+    Vehicle,
+    Monster,
+}
+
+impl MiniatureType {
+    pub fn all() -> &'static [MiniatureType] {
+        &[
+            MiniatureType::Troop,
+            MiniatureType::Character,
+            MiniatureType::Vehicle,
+            MiniatureType::Monster,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MiniatureType::Troop => "troop",
+            MiniatureType::Character => "character",
+            MiniatureType::Vehicle => "vehicle",
+            MiniatureType::Monster => "monster",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MiniatureType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known_value(
+            deserializer,
+            "miniature_type",
+            MiniatureType::all(),
+            MiniatureType::as_str,
+        )
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Serialize, sqlx::Type, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum ProgressStatus {
     Unpainted,
@@ -28,9 +101,97 @@ pub enum ProgressStatus {
     Basecoated,
     Detailed,
     Completed,
+    Varnished,
+}
+
+impl ProgressStatus {
+    /// All statuses in painting progression order
+    pub fn all() -> &'static [ProgressStatus] {
+        &[
+            ProgressStatus::Unpainted,
+            ProgressStatus::Primed,
+            ProgressStatus::Basecoated,
+            ProgressStatus::Detailed,
+            ProgressStatus::Completed,
+            ProgressStatus::Varnished,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProgressStatus::Unpainted => "unpainted",
+            ProgressStatus::Primed => "primed",
+            ProgressStatus::Basecoated => "basecoated",
+            ProgressStatus::Detailed => "detailed",
+            ProgressStatus::Completed => "completed",
+            ProgressStatus::Varnished => "varnished",
+        }
+    }
+
+    /// Whether painting is finished; varnishing is an optional step after completion
+    pub fn is_done(&self) -> bool {
+        matches!(self, ProgressStatus::Completed | ProgressStatus::Varnished)
+    }
+
+    /// Position in the painting progression, spelled out rather than taken from the
+    /// declaration order so reordering the variants can't change which moves are allowed
+    pub fn step(&self) -> usize {
+        match self {
+            ProgressStatus::Unpainted => 0,
+            ProgressStatus::Primed => 1,
+            ProgressStatus::Basecoated => 2,
+            ProgressStatus::Detailed => 3,
+            ProgressStatus::Completed => 4,
+            ProgressStatus::Varnished => 5,
+        }
+    }
+
+    /// Whether a miniature can move straight to `next`: staying put, one step forward, or
+    /// any step back to correct a mistake
+    pub fn can_move_to(&self, next: &ProgressStatus) -> bool {
+        next.step() <= self.step() + 1
+    }
+}
+
+impl<'de> Deserialize<'de> for ProgressStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        known_value(
+            deserializer,
+            "progress_status",
+            ProgressStatus::all(),
+            ProgressStatus::as_str,
+        )
+    }
+}
+
+/// Deserialize one of `values` by name, or fail with a message listing every accepted name
+fn known_value<'de, D, T>(
+    deserializer: D,
+    kind: &str,
+    values: &[T],
+    name: fn(&T) -> &'static str,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Clone,
+{
+    let value = String::deserialize(deserializer)?;
+    values
+        .iter()
+        .find(|candidate| name(candidate) == value)
+        .cloned()
+        .ok_or_else(|| {
+            let names: Vec<&str> = values.iter().map(name).collect();
+            D::Error::custom(format!(
+                "unknown {} {:?}; expected one of: {}",
+                kind,
+                value,
+                names.join(", ")
+            ))
+        })
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Project {
     pub id: i64,
     pub name: String,
@@ -39,9 +200,18 @@ pub struct Project {
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set when the project has been soft-deleted
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Lowercase, deduplicated labels such as "tournament" or "commission"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Set for finished work kept out of the default projects list; not the same as deleted
+    #[serde(default)]
+    pub archived: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Miniature {
     pub id: i64,
     pub project_id: i64,
@@ -49,11 +219,23 @@ pub struct Miniature {
     pub miniature_type: MiniatureType,
     pub progress_status: ProgressStatus,
     pub notes: Option<String>,
+    /// Points cost for army list building
+    pub points_value: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One move of a miniature from one progress status to another
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProgressChange {
+    pub id: i64,
+    pub miniature_id: i64,
+    pub from_status: ProgressStatus,
+    pub to_status: ProgressStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PaintingRecipe {
     pub id: i64,
     pub name: String,
@@ -66,7 +248,7 @@ pub struct PaintingRecipe {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Photo {
     pub id: i64,
     pub miniature_id: i64,
@@ -75,40 +257,275 @@ pub struct Photo {
     pub file_size: i64,
     pub mime_type: String,
     pub uploaded_at: DateTime<Utc>,
+    /// Hex SHA-256 of the file contents; absent for photos uploaded before hashing
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Storage path of the generated thumbnail, if the upload could be decoded
+    #[serde(default)]
+    pub thumbnail_path: Option<String>,
+    /// Whether this is the miniature's featured photo; at most one per miniature
+    #[serde(default)]
+    pub is_primary: bool,
+}
+
+/// Current version of the `ProjectExport` document format
+pub const PROJECT_EXPORT_VERSION: u32 = 1;
+
+/// Self-contained backup of a project, its miniatures, and the recipes they use
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProjectExport {
+    /// Format version the document was written in; servers refuse versions newer than
+    /// their own `PROJECT_EXPORT_VERSION`
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub project: Project,
+    pub miniatures: Vec<MiniatureExport>,
+    pub recipes: Vec<PaintingRecipe>,
+}
+
+/// A recipe linked to a miniature, with when the link was made
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LinkedRecipe {
+    #[serde(flatten)]
+    pub recipe: PaintingRecipe,
+    pub linked_at: DateTime<Utc>,
+}
+
+/// A miniature with how many photos it has
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MiniatureWithCounts {
+    #[serde(flatten)]
+    pub miniature: Miniature,
+    pub photo_count: i64,
+}
+
+/// A miniature with whichever of its photos and linked recipes were asked for
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MiniatureDetail {
+    #[serde(flatten)]
+    pub miniature: Miniature,
+    /// Primary photo first, then the rest in upload order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub photos: Option<Vec<Photo>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipes: Option<Vec<LinkedRecipe>>,
+}
+
+/// A miniature in a `ProjectExport`, with its recipe links and photo metadata (not file contents)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MiniatureExport {
+    #[serde(flatten)]
+    pub miniature: Miniature,
+    pub recipe_ids: Vec<i64>,
+    pub photos: Vec<Photo>,
+}
+
+/// Outcome of importing a `ProjectExport`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ProjectImportSummary {
+    pub project_id: i64,
+    pub miniatures_imported: i64,
+    pub recipes_created: i64,
+    pub recipes_reused: i64,
+}
+
+/// How many recipes call for a paint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PaintUsage {
+    pub paint: String,
+    pub recipe_count: i64,
+}
+
+/// A paint in the inventory
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Paint {
+    pub id: i64,
+    pub name: String,
+    pub brand: Option<String>,
+    /// Pots on hand; a paint with none left doesn't count as owned
+    pub quantity_remaining: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A project using a recipe, with how many of its miniatures are linked to it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecipeProjectUsage {
+    #[serde(flatten)]
+    pub project: Project,
+    pub miniature_count: i64,
+}
+
+/// An army name already used by projects, and how many
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ArmyUsage {
+    pub army: String,
+    pub project_count: i64,
 }
 
 // Request/Response DTOs
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateProjectRequest {
     pub name: String,
     pub game_system: GameSystem,
     pub army: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Replaces every editable field of a project (PUT); leaving out `description` or `tags`
+/// clears them
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateProjectRequest {
+    pub name: String,
+    pub game_system: GameSystem,
+    pub army: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The `updated_at` the client last saw; when present, the update is refused with
+    /// 409 Conflict if the project has changed since
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Changes some of a project's fields (PATCH). Absent fields keep their value, and
+/// `"description": null` clears the description.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PatchProjectRequest {
     pub name: Option<String>,
     pub game_system: Option<GameSystem>,
     pub army: Option<String>,
-    pub description: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+    /// Replaces the whole tag list when present
+    pub tags: Option<Vec<String>>,
+    /// The `updated_at` the client last saw; when present, the update is refused with
+    /// 409 Conflict if the project has changed since
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<UpdateProjectRequest> for PatchProjectRequest {
+    fn from(request: UpdateProjectRequest) -> Self {
+        PatchProjectRequest {
+            name: Some(request.name),
+            game_system: Some(request.game_system),
+            army: Some(request.army),
+            description: Some(request.description),
+            tags: Some(request.tags),
+            updated_at: request.updated_at,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateMiniatureRequest {
     pub name: String,
     pub miniature_type: MiniatureType,
     pub notes: Option<String>,
+    pub points_value: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Replaces every editable field of a miniature (PUT); leaving out `notes` or
+/// `points_value` clears it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateMiniatureRequest {
+    pub name: String,
+    pub progress_status: ProgressStatus,
+    pub notes: Option<String>,
+    pub points_value: Option<i32>,
+    /// The `updated_at` the client last saw; when present, the update is refused with
+    /// 409 Conflict if the miniature has changed since
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Changes some of a miniature's fields (PATCH). Absent fields keep their value, and
+/// `"notes": null` or `"points_value": null` clears that field.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PatchMiniatureRequest {
     pub name: Option<String>,
     pub progress_status: Option<ProgressStatus>,
-    pub notes: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub notes: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<i32>)]
+    pub points_value: Option<Option<i32>>,
+    /// The `updated_at` the client last saw; when present, the update is refused with
+    /// 409 Conflict if the miniature has changed since
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<UpdateMiniatureRequest> for PatchMiniatureRequest {
+    fn from(request: UpdateMiniatureRequest) -> Self {
+        PatchMiniatureRequest {
+            name: Some(request.name),
+            progress_status: Some(request.progress_status),
+            notes: Some(request.notes),
+            points_value: Some(request.points_value),
+            updated_at: request.updated_at,
+        }
+    }
+}
+
+/// Deserialize a nullable PATCH field so that, paired with `#[serde(default)]`, an absent
+/// field is `None` and an explicit `null` is `Some(None)`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkUpdateStatusRequest {
+    pub miniature_ids: Vec<i64>,
+    pub progress_status: ProgressStatus,
+}
+
+/// The project to move a miniature into
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MoveMiniatureRequest {
+    pub target_project_id: i64,
+}
+
+/// Miniatures to link a recipe to, each reported on separately
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkLinkRecipeRequest {
+    pub miniature_ids: Vec<i64>,
+}
+
+/// Miniatures to add to a project together, such as a whole squad; all are created or none
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkCreateMiniaturesRequest {
+    pub miniatures: Vec<CreateMiniatureRequest>,
+}
+
+/// Miniatures to delete from a project; every id must belong to it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkDeleteMiniaturesRequest {
+    pub miniature_ids: Vec<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateRecipeRequest {
     pub name: String,
     pub miniature_type: MiniatureType,
@@ -118,7 +535,7 @@ pub struct CreateRecipeRequest {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateRecipeRequest {
     pub name: Option<String>,
     pub steps: Option<Vec<String>>,
@@ -127,15 +544,138 @@ pub struct UpdateRecipeRequest {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreatePaintRequest {
+    pub name: String,
+    pub brand: Option<String>,
+    pub quantity_remaining: i64,
+}
+
+/// Replaces every field of a paint; an absent `brand` clears it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePaintRequest {
+    pub name: String,
+    pub brand: Option<String>,
+    pub quantity_remaining: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct CloneProjectRequest {
+    pub name: Option<String>,
+    /// Start every copied miniature as unpainted; defaults to true
+    pub reset_progress: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateRecipeRequest {
+    pub name: Option<String>,
+}
+
+/// New order for a recipe's steps, as indices into its current `steps`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReorderRecipeStepsRequest {
+    pub order: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: ErrorDetails,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorDetails {
     pub error_type: String,
     pub message: String,
     pub details: Option<serde_json::Value>,
     pub timestamp: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_system_round_trips_through_json() {
+        for (system, wire) in [
+            (GameSystem::KillTeam, "\"kill_team\""),
+            (GameSystem::MiddleEarth, "\"middle_earth\""),
+        ] {
+            let json = serde_json::to_string(&system).unwrap();
+            assert_eq!(json, wire);
+
+            let parsed: GameSystem = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), wire);
+        }
+    }
+
+    #[test]
+    fn enum_names_match_their_serialized_form() {
+        for system in GameSystem::all() {
+            assert_eq!(
+                serde_json::to_string(system).unwrap(),
+                format!("\"{}\"", system.as_str())
+            );
+        }
+        for miniature_type in MiniatureType::all() {
+            assert_eq!(
+                serde_json::to_string(miniature_type).unwrap(),
+                format!("\"{}\"", miniature_type.as_str())
+            );
+        }
+        for status in ProgressStatus::all() {
+            let json = serde_json::to_string(status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status.as_str()));
+            assert_eq!(
+                &serde_json::from_str::<ProgressStatus>(&json).unwrap(),
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn progress_moves_one_step_forward_or_any_step_back() {
+        for (step, status) in ProgressStatus::all().iter().enumerate() {
+            assert_eq!(status.step(), step);
+        }
+        assert!(ProgressStatus::Unpainted.can_move_to(&ProgressStatus::Unpainted));
+        assert!(ProgressStatus::Unpainted.can_move_to(&ProgressStatus::Primed));
+        assert!(!ProgressStatus::Unpainted.can_move_to(&ProgressStatus::Basecoated));
+        assert!(!ProgressStatus::Primed.can_move_to(&ProgressStatus::Completed));
+        assert!(ProgressStatus::Completed.can_move_to(&ProgressStatus::Varnished));
+        assert!(ProgressStatus::Varnished.can_move_to(&ProgressStatus::Unpainted));
+    }
+
+    #[test]
+    fn unknown_enum_values_list_the_valid_ones() {
+        let error = serde_json::from_str::<GameSystem>("\"warhammer_30k\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown game_system \"warhammer_30k\"; expected one of: age_of_sigmar, \
+             horus_heresy, kill_team, middle_earth, warhammer_40k"
+        );
+
+        let error = serde_json::from_str::<MiniatureType>("\"Troop\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown miniature_type \"Troop\""));
+
+        let error = serde_json::from_str::<ProgressStatus>("\"painted\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("expected one of: unpainted, primed, basecoated"));
+    }
+
+    #[test]
+    fn patch_fields_tell_absent_from_null() {
+        let absent: PatchMiniatureRequest = serde_json::from_str(r#"{"name": "Reaver"}"#).unwrap();
+        assert_eq!(absent.notes, None);
+
+        let cleared: PatchMiniatureRequest = serde_json::from_str(r#"{"notes": null}"#).unwrap();
+        assert_eq!(cleared.notes, Some(None));
+        assert_eq!(cleared.name, None);
+
+        let set: PatchProjectRequest =
+            serde_json::from_str(r#"{"description": "Warband"}"#).unwrap();
+        assert_eq!(set.description, Some(Some("Warband".to_string())));
+    }
+}